@@ -11,3 +11,32 @@ macro_rules! trans_row_col_to_index {
 		$row * 9 + $col
 	};
 }
+
+/// Defines a str8ts game inline, with one string literal per row.
+///
+/// Each row consists of 9 cell characters: `.` for an empty white cell, `1`-`9` for a white cell with that (given)
+/// value, `#` for an empty black cell, and `a`-`i` for a black cell with the value 1-9.
+///
+/// Panics if there are not exactly 9 rows, or if a row is invalid.
+///
+/// ```ignore
+/// let board = str8ts![
+///     "#.a..#...",
+///     "..#6..#.3",
+///     // ... seven more rows
+/// ];
+/// ```
+#[macro_export]
+macro_rules! str8ts {
+	[$($row:expr),* $(,)?] => {{
+		let rows: &[&str] = &[$($row),*];
+		assert_eq!(rows.len(), 9, "a str8ts game has 9 rows");
+		let mut str8ts = $crate::str8ts::Str8ts::new();
+		for (row, line) in rows.iter().enumerate() {
+			if let Err(error) = str8ts.set_row_from_str(row as u8, line) {
+				panic!("invalid str8ts row: {}", error);
+			}
+		}
+		str8ts
+	}};
+}
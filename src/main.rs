@@ -1,11 +1,212 @@
+use std::path::Path;
+
+use crate::str8ts::{LabelScheme, Str8ts};
+
+use crate::str8ts_batch::rate_dir;
+use crate::str8ts_clues::clue_report;
+use crate::str8ts_format::parse_cell_reference;
 use crate::str8ts_gui::run;
+use crate::str8ts_logic::{Technique, TechniqueSet};
+use crate::str8ts_replay::Replay;
+use crate::str8ts_walkthrough::Walkthrough;
+use crate::str8ts_watch::watch;
 
 #[macro_use]
 pub mod macros;
 pub mod str8ts;
+pub mod str8ts_analysis;
+pub mod str8ts_batch;
+pub mod str8ts_cache;
+pub mod str8ts_candidates;
+pub mod str8ts_clue_floor;
+pub mod str8ts_clues;
+pub mod str8ts_explain;
+pub mod str8ts_format;
 pub mod str8ts_gui;
+pub mod str8ts_logic;
+pub mod str8ts_moves;
+pub mod str8ts_replay;
+pub mod str8ts_rules;
+pub mod str8ts_snapshots;
 pub mod str8ts_solver;
+pub mod str8ts_symmetry;
+pub mod str8ts_terminal;
+pub mod str8ts_tutorial;
+pub mod str8ts_validation;
+pub mod str8ts_walkthrough;
+pub mod str8ts_watch;
 
 fn main() {
-	let _ = run();
+	let args: Vec<String> = std::env::args().collect();
+	match &args[1..] {
+		[flag, path] if flag == "--rate-dir" => {
+			if let Err(error) = rate_dir(Path::new(path)) {
+				eprintln!("Cannot rate {}: {}", path, error);
+				std::process::exit(1);
+			}
+		}
+		[flag, path] if flag == "--print" => match read_str8ts(path) {
+			Ok(str8ts) => print!("{}", str8ts.to_terminal()),
+			Err(error) => {
+				eprintln!("Cannot print {}: {}", path, error);
+				std::process::exit(1);
+			}
+		},
+		[flag, path, techniques @ ..] if flag == "--grade" => {
+			let report = read_str8ts(path).and_then(|str8ts| {
+				if techniques.is_empty() {
+					Ok(str8ts.grading_report())
+				} else {
+					Ok(str8ts.grading_report_with(parse_techniques(techniques)?))
+				}
+			});
+			match report {
+				Ok(report) => println!("{}", report),
+				Err(error) => {
+					eprintln!("Cannot grade {}: {}", path, error);
+					std::process::exit(1);
+				}
+			}
+		}
+		[flag, path, pinned @ ..] if flag == "--clues" => {
+			let pinned: Result<Vec<_>, String> = pinned
+				.iter()
+				.map(|cell| {
+					parse_cell_reference(cell).ok_or_else(|| format!("invalid cell '{}'", cell))
+				})
+				.collect();
+			match pinned.and_then(|pinned| Ok((read_str8ts(path)?, pinned))) {
+				Ok((str8ts, pinned)) => print!("{}", clue_report(&str8ts, &pinned)),
+				Err(error) => {
+					eprintln!("Cannot analyze {}: {}", path, error);
+					std::process::exit(1);
+				}
+			}
+		}
+		[flag, path, format @ ..] if flag == "--walkthrough" && format.len() <= 1 => {
+			let walkthrough = read_str8ts(path).and_then(|str8ts| {
+				str8ts
+					.solution_walkthrough()
+					.map_err(|error| error.to_string())
+			});
+			let render = match format.first().map(String::as_str) {
+				None | Some("text") => Walkthrough::to_text,
+				Some("markdown") => Walkthrough::to_markdown,
+				Some("html") => Walkthrough::to_html,
+				Some(format) => {
+					eprintln!(
+						"Unknown walkthrough format {}, expected text, markdown or html",
+						format
+					);
+					std::process::exit(1);
+				}
+			};
+			match walkthrough {
+				Ok(walkthrough) => print!("{}", render(&walkthrough)),
+				Err(error) => {
+					eprintln!("Cannot explain {}: {}", path, error);
+					std::process::exit(1);
+				}
+			}
+		}
+		[flag, path] if flag == "--script" => {
+			let script = read_str8ts(path).and_then(|str8ts| {
+				str8ts
+					.export_solution_script()
+					.ok_or_else(|| String::from("the logical solver gets stuck"))
+			});
+			match script {
+				Ok(script) => print!("{}", script),
+				Err(error) => {
+					eprintln!("Cannot export a script for {}: {}", path, error);
+					std::process::exit(1);
+				}
+			}
+		}
+		[flag, path, script_path] if flag == "--check-script" => {
+			let played = read_str8ts(path).and_then(|str8ts| {
+				let script =
+					std::fs::read_to_string(script_path).map_err(|error| error.to_string())?;
+				let solved = str8ts
+					.solved()
+					.ok_or_else(|| String::from("the puzzle has no solution"))?;
+				Ok(solved.diff_from(&str8ts.play_solution_script(&script)?))
+			});
+			match played {
+				Ok(wrong) if wrong.is_empty() => println!("The script solves the puzzle"),
+				Ok(wrong) => {
+					let labels = LabelScheme::default();
+					let cells: Vec<String> = wrong
+						.into_iter()
+						.map(|((row, col), _)| labels.cell_reference(row, col))
+						.collect();
+					println!(
+						"The script leaves {} cells empty or wrong: {}",
+						cells.len(),
+						cells.join(", ")
+					);
+					std::process::exit(1);
+				}
+				Err(error) => {
+					eprintln!("Cannot check {}: {}", script_path, error);
+					std::process::exit(1);
+				}
+			}
+		}
+		[flag, path] if flag == "--watch" => watch(Path::new(path)),
+		[flag, path, options @ ..]
+			if flag == "--replay" && (options.is_empty() || options == ["--step"]) =>
+		{
+			let played = Replay::load(Path::new(path)).and_then(|replay| {
+				replay
+					.play_in_terminal(!options.is_empty())
+					.map_err(Into::into)
+			});
+			if let Err(error) = played {
+				eprintln!("Cannot replay {}: {}", path, error);
+				std::process::exit(1);
+			}
+		}
+		_ => {
+			let _ = run();
+		}
+	}
+}
+
+/// Reads a selection of techniques from arguments such as `hidden-single` or `no-hidden-single`.
+///
+/// Naming any technique selects only the named ones; otherwise, all techniques but the excluded ones are selected.
+fn parse_techniques(args: &[String]) -> Result<TechniqueSet, String> {
+	let mut techniques = if args.iter().any(|arg| !arg.starts_with("no-")) {
+		TechniqueSet::NONE
+	} else {
+		TechniqueSet::ALL
+	};
+	for arg in args {
+		let (keyword, is_excluded) = match arg.strip_prefix("no-") {
+			Some(keyword) => (keyword, true),
+			None => (arg.as_str(), false),
+		};
+		let technique = Technique::ALL
+			.into_iter()
+			.find(|technique| technique.keyword() == keyword)
+			.ok_or_else(|| format!("unknown technique '{}'", keyword))?;
+		techniques = if is_excluded {
+			techniques.without(technique)
+		} else {
+			techniques.with(technique)
+		};
+	}
+	Ok(techniques)
+}
+
+/// Reads a str8ts game from the file at the given path.
+fn read_str8ts(path: &str) -> Result<Str8ts, String> {
+	std::fs::read_to_string(path)
+		.map_err(|error| error.to_string())
+		.and_then(|contents| {
+			contents
+				.parse::<Str8ts>()
+				.map_err(|error| error.to_string())
+		})
 }
@@ -1,5 +1,7 @@
 use std::fmt::Display;
 
+use crate::str8ts_rules::RuleSet;
+
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub(crate) enum CellColor {
 	#[default]
@@ -101,9 +103,15 @@ impl From<CellValue> for usize {
 
 impl PartialOrd for CellValue {
 	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for CellValue {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
 		let my_value: u8 = (*self).into();
 		let other_value: u8 = (*other).into();
-		my_value.partial_cmp(&other_value)
+		my_value.cmp(&other_value)
 	}
 }
 
@@ -244,10 +252,33 @@ impl From<CellValue> for char {
 	}
 }
 
+/// A bound on the value of a white cell, as used by some str8ts variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ValueConstraint {
+	/// The value must be less than or equal to the given value.
+	AtMost(CellValue),
+	/// The value must be greater than or equal to the given value.
+	AtLeast(CellValue),
+}
+
+impl ValueConstraint {
+	/// Whether the given (non-empty) value satisfies the constraint.
+	pub(crate) fn allows(&self, value: CellValue) -> bool {
+		match self {
+			ValueConstraint::AtMost(bound) => value <= *bound,
+			ValueConstraint::AtLeast(bound) => value >= *bound,
+		}
+	}
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub(crate) struct Cell {
 	pub(crate) color: CellColor,
 	pub(crate) value: CellValue,
+	/// Whether the value is part of the puzzle, rather than filled in while solving it.
+	pub(crate) given: bool,
+	/// An optional bound on the value of the cell.
+	pub(crate) constraint: Option<ValueConstraint>,
 }
 
 impl Display for Cell {
@@ -261,21 +292,143 @@ impl Display for Cell {
 
 impl Cell {
 	pub(crate) fn new(color: CellColor, value: CellValue) -> Self {
-		Cell { color, value }
+		Cell {
+			color,
+			value,
+			given: false,
+			constraint: None,
+		}
+	}
+}
+
+/// A cell packed into 16 bits, as stored on the board.
+///
+/// Bits 0-3 hold the value (0 for empty), bit 4 is set for black cells, and bit 5 is set for given values.
+/// Bits 6-7 hold the kind of value constraint (0 for none, 1 for at most, 2 for at least), and bits 8-11 its bound.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub(crate) struct PackedCell(u16);
+
+impl PackedCell {
+	const VALUE_MASK: u16 = 0b1111;
+	const BLACK: u16 = 1 << 4;
+	const GIVEN: u16 = 1 << 5;
+	const CONSTRAINT_SHIFT: u16 = 6;
+	const CONSTRAINT_MASK: u16 = 0b11;
+	const AT_MOST: u16 = 1;
+	const AT_LEAST: u16 = 2;
+	const BOUND_SHIFT: u16 = 8;
+}
+
+impl From<Cell> for PackedCell {
+	fn from(cell: Cell) -> Self {
+		let mut bits = u16::from(u8::from(cell.value));
+		if cell.color == CellColor::Black {
+			bits |= PackedCell::BLACK;
+		}
+		if cell.given {
+			bits |= PackedCell::GIVEN;
+		}
+		if let Some(constraint) = cell.constraint {
+			let (kind, bound) = match constraint {
+				ValueConstraint::AtMost(bound) => (PackedCell::AT_MOST, bound),
+				ValueConstraint::AtLeast(bound) => (PackedCell::AT_LEAST, bound),
+			};
+			bits |= kind << PackedCell::CONSTRAINT_SHIFT;
+			bits |= u16::from(u8::from(bound)) << PackedCell::BOUND_SHIFT;
+		}
+		PackedCell(bits)
+	}
+}
+
+impl From<PackedCell> for Cell {
+	fn from(packed: PackedCell) -> Self {
+		Cell {
+			color: if packed.0 & PackedCell::BLACK != 0 {
+				CellColor::Black
+			} else {
+				CellColor::White
+			},
+			value: CellValue::from((packed.0 & PackedCell::VALUE_MASK) as u8),
+			given: packed.0 & PackedCell::GIVEN != 0,
+			constraint: {
+				let bound = CellValue::from(
+					((packed.0 >> PackedCell::BOUND_SHIFT) & PackedCell::VALUE_MASK) as u8,
+				);
+				match (packed.0 >> PackedCell::CONSTRAINT_SHIFT) & PackedCell::CONSTRAINT_MASK {
+					PackedCell::AT_MOST => Some(ValueConstraint::AtMost(bound)),
+					PackedCell::AT_LEAST => Some(ValueConstraint::AtLeast(bound)),
+					_ => None,
+				}
+			},
+		}
+	}
+}
+
+/// The number of rows and columns of the board, and of the values a cell can hold.
+pub(crate) const SIZE: usize = 9;
+
+/// The number of cells of the board.
+pub(crate) const CELLS: usize = SIZE * SIZE;
+
+/// The position of a cell on the board, given as `(row, col)`.
+pub(crate) type Position = (u8, u8);
+
+/// The direction of a compartment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Orientation {
+	Row,
+	Column,
+}
+
+/// Identifies a compartment by its orientation and its first cell, which is also where its sum clue is stored.
+///
+/// Ids only stay valid as long as no cell changes its color. Afterwards, an id may name a different compartment, or none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct CompartmentId {
+	pub(crate) orientation: Orientation,
+	pub(crate) first: Position,
+}
+
+/// The cells in which the values of one str8ts game do not fit onto the puzzle of another, see `Str8ts::overlay`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct OverlayError {
+	pub(crate) clashes: Vec<Position>,
+}
+
+impl Display for OverlayError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let labels = LabelScheme::default();
+		let cells: Vec<String> = self
+			.clashes
+			.iter()
+			.map(|(row, col)| labels.cell_reference(*row, *col))
+			.collect();
+		write!(
+			f,
+			"the values clash with the puzzle in {}",
+			cells.join(", ")
+		)
 	}
 }
 
-#[derive(Debug, Clone, Copy)]
+impl std::error::Error for OverlayError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) struct Str8ts {
-	pub(crate) cells: [[Cell; 9]; 9],
+	/// The cells in reading order, see `Str8ts::index`.
+	pub(crate) cells: [PackedCell; CELLS],
+	pub(crate) rules: RuleSet,
+	/// The sum clues of compartments ("killer straights"), keyed by the index of the compartment's first cell and the
+	/// compartment's orientation. A clue whose cell is no longer the first of a compartment is ignored.
+	pub(crate) sums: [[Option<u8>; 2]; CELLS],
 }
 
 impl Display for Str8ts {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		let mut result = String::new();
-		for row in 0..9 {
-			for col in 0..9 {
-				result.push_str(&format!("{} ", self.cells[row][col]));
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				result.push_str(&format!("{} ", self.get_cell(row, col)));
 			}
 			result.push('\n');
 		}
@@ -287,46 +440,125 @@ impl Display for Str8ts {
 impl Str8ts {
 	pub(crate) fn new() -> Self {
 		Str8ts {
-			cells: [[Cell::default(); 9]; 9],
+			cells: [PackedCell::default(); CELLS],
+			rules: RuleSet::default(),
+			sums: [[None; 2]; CELLS],
 		}
 	}
 
+	/// Returns the index of the cell in reading order, as used to store it.
+	///
+	/// This and `coords` are the only conversions between positions and indices; the row and column must be on the board.
+	pub(crate) fn index(row: u8, col: u8) -> u8 {
+		debug_assert!(
+			usize::from(row) < SIZE && usize::from(col) < SIZE,
+			"cell ({}, {}) is not on the board",
+			row,
+			col
+		);
+		row * SIZE as u8 + col
+	}
+
+	/// Returns the position of the cell with the given index in reading order, which must be on the board.
+	pub(crate) fn coords(index: u8) -> Position {
+		debug_assert!(
+			usize::from(index) < CELLS,
+			"index {} is not on the board",
+			index
+		);
+		(index / SIZE as u8, index % SIZE as u8)
+	}
+
 	pub(crate) fn set_cell(&mut self, row: u8, col: u8, cell: Cell) {
-		self.cells[row as usize][col as usize] = cell;
+		self.cells[Str8ts::index(row, col) as usize] = cell.into();
 	}
 
 	pub(crate) fn set_cell_by_index(&mut self, index: u8, cell: Cell) {
-		let (row, col) = trans_index_to_row_col!(index);
+		let (row, col) = Str8ts::coords(index);
 		self.set_cell(row, col, cell);
 	}
 
 	pub(crate) fn set_cell_color(&mut self, row: u8, col: u8, color: CellColor) {
-		self.cells[row as usize][col as usize].color = color;
+		let cell = self.get_cell(row, col);
+		self.set_cell(row, col, Cell { color, ..cell });
 	}
 
 	pub(crate) fn set_cell_color_by_index(&mut self, index: u8, color: CellColor) {
-		let (row, col) = trans_index_to_row_col!(index);
+		let (row, col) = Str8ts::coords(index);
 		self.set_cell_color(row, col, color);
 	}
 
 	pub(crate) fn set_cell_value(&mut self, row: u8, col: u8, value: CellValue) {
-		self.cells[row as usize][col as usize].value = value;
+		let cell = self.get_cell(row, col);
+		self.set_cell(row, col, Cell { value, ..cell });
 	}
 
 	pub(crate) fn set_cell_value_by_index(&mut self, index: u8, value: CellValue) {
-		let (row, col) = trans_index_to_row_col!(index);
+		let (row, col) = Str8ts::coords(index);
 		self.set_cell_value(row, col, value);
 	}
 
+	pub(crate) fn set_cell_given(&mut self, row: u8, col: u8, given: bool) {
+		let cell = self.get_cell(row, col);
+		self.set_cell(row, col, Cell { given, ..cell });
+	}
+
+	pub(crate) fn set_cell_constraint(
+		&mut self,
+		row: u8,
+		col: u8,
+		constraint: Option<ValueConstraint>,
+	) {
+		let cell = self.get_cell(row, col);
+		self.set_cell(row, col, Cell { constraint, ..cell });
+	}
+
 	pub(crate) fn get_cell(&self, row: u8, col: u8) -> Cell {
-		self.cells[row as usize][col as usize]
+		self.cells[Str8ts::index(row, col) as usize].into()
 	}
 
 	pub(crate) fn get_cell_by_index(&self, index: u8) -> Cell {
-		let (row, col) = trans_index_to_row_col!(index);
+		let (row, col) = Str8ts::coords(index);
 		self.get_cell(row, col)
 	}
 
+	/// Returns all cells together with their positions, in reading order.
+	///
+	/// Cells are stored packed, so they are yielded by value. Like the filtered iterators below, this is lazy, does not
+	/// allocate, and visits each of the 81 cells once.
+	pub(crate) fn cells(&self) -> impl Iterator<Item = (Position, Cell)> + '_ {
+		(0..CELLS as u8).map(|index| (Str8ts::coords(index), self.get_cell_by_index(index)))
+	}
+
+	/// Returns the white cells together with their positions, in reading order.
+	pub(crate) fn white_cells(&self) -> impl Iterator<Item = (Position, Cell)> + '_ {
+		self.cells()
+			.filter(|(_, cell)| cell.color == CellColor::White)
+	}
+
+	/// Returns the black cells together with their positions, in reading order.
+	pub(crate) fn black_cells(&self) -> impl Iterator<Item = (Position, Cell)> + '_ {
+		self.cells()
+			.filter(|(_, cell)| cell.color == CellColor::Black)
+	}
+
+	/// Returns the black cells that hold a value (the black clues) together with their positions, in reading order.
+	pub(crate) fn black_clues(&self) -> impl Iterator<Item = (Position, Cell)> + '_ {
+		self.black_cells()
+			.filter(|(_, cell)| cell.value != CellValue::Empty)
+	}
+
+	/// Returns the empty white cells together with their positions, in reading order.
+	pub(crate) fn empty_white_cells(&self) -> impl Iterator<Item = (Position, Cell)> + '_ {
+		self.white_cells()
+			.filter(|(_, cell)| cell.value == CellValue::Empty)
+	}
+
+	/// Returns the white cells holding a given value together with their positions, in reading order.
+	pub(crate) fn given_cells(&self) -> impl Iterator<Item = (Position, Cell)> + '_ {
+		self.white_cells().filter(|(_, cell)| cell.given)
+	}
+
 	pub(crate) fn toggle_cell_color(&mut self, row: u8, col: u8) {
 		let cell = self.get_cell(row, col);
 		match cell.color {
@@ -336,34 +568,246 @@ impl Str8ts {
 	}
 
 	pub(crate) fn toggle_cell_color_by_index(&mut self, index: u8) {
-		let (row, cell) = trans_index_to_row_col!(index);
+		let (row, cell) = Str8ts::coords(index);
 		self.toggle_cell_color(row, cell);
 	}
 
 	pub(crate) fn copy_from(&mut self, other: &Str8ts) {
-		for row in 0..9 {
-			for col in 0..9 {
-				let other_cell = other.get_cell(row, col);
-				self.set_cell_color(row, col, other_cell.color);
-				self.set_cell_value(row, col, other_cell.value);
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				self.set_cell(row, col, other.get_cell(row, col));
+			}
+		}
+	}
+
+	/// Returns the str8ts game mirrored along its main diagonal, so rows become columns and vice versa.
+	///
+	/// Sum clues swap their orientation along with their compartments. The variant rules are symmetric under
+	/// transposition, so they carry over unchanged. The tests solve puzzles and their transposes with it, to check that rows
+	/// and columns are treated alike.
+	#[cfg(test)]
+	pub(crate) fn transpose(&self) -> Str8ts {
+		let mut transposed = *self;
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				transposed.set_cell(col, row, self.get_cell(row, col));
+				let [row_sum, col_sum] = self.sums[Str8ts::index(row, col) as usize];
+				transposed.sums[Str8ts::index(col, row) as usize] = [col_sum, row_sum];
+			}
+		}
+		transposed
+	}
+
+	/// Combines the puzzle of one str8ts game with the values filled into another one.
+	///
+	/// The result has the colors, black clues, givens, value constraints, sum clues and rules of `layout_from`, and the
+	/// values `values_from` has in its other white cells. Fails with the clashing cells if `values_from` has a value in a
+	/// cell that is black in `layout_from`, or a value other than the given one in a given cell.
+	pub(crate) fn overlay(
+		layout_from: &Str8ts,
+		values_from: &Str8ts,
+	) -> Result<Str8ts, OverlayError> {
+		let mut result = *layout_from;
+		result.clear_solution();
+		let mut clashes = Vec::new();
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				let layout = layout_from.get_cell(row, col);
+				let values = values_from.get_cell(row, col);
+				if values.color == CellColor::Black || values.value == CellValue::Empty {
+					continue;
+				}
+				if layout.color == CellColor::Black
+					|| (layout.given && layout.value != values.value)
+				{
+					clashes.push((row, col));
+				} else if !layout.given {
+					result.set_cell_value(row, col, values.value);
+				}
 			}
 		}
+		if clashes.is_empty() {
+			Ok(result)
+		} else {
+			Err(OverlayError { clashes })
+		}
 	}
 
 	pub(crate) fn clear_all(&mut self) {
-		for row in 0..9 {
-			for col in 0..9 {
-				self.set_cell_color(row, col, CellColor::White);
-				self.set_cell_value(row, col, CellValue::Empty);
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				self.set_cell(row, col, Cell::default());
 			}
 		}
 	}
 
 	pub(crate) fn clear_values(&mut self) {
-		for row in 0..9 {
-			for col in 0..9 {
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
 				self.set_cell_value(row, col, CellValue::Empty);
+				self.set_cell_given(row, col, false);
+			}
+		}
+	}
+
+	/// Clears the values of all white cells that are not given, leaving the puzzle itself intact.
+	pub(crate) fn clear_solution(&mut self) {
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				let cell = self.get_cell(row, col);
+				if cell.color == CellColor::White && !cell.given {
+					self.set_cell_value(row, col, CellValue::Empty);
+				}
+			}
+		}
+	}
+
+	/// Returns the cells of the row- and column-compartment containing the given cell.
+	///
+	/// Black cells are not part of any compartment, so two empty lists are returned for them.
+	pub(crate) fn compartment_cells(&self, row: u8, col: u8) -> (Vec<Position>, Vec<Position>) {
+		if self.get_cell(row, col).color == CellColor::Black {
+			return (Vec::new(), Vec::new());
+		}
+		let is_white = |row: u8, col: u8| self.get_cell(row, col).color == CellColor::White;
+
+		// Walk left and right within the row.
+		let mut first_col = col;
+		while first_col > 0 && is_white(row, first_col - 1) {
+			first_col -= 1;
+		}
+		let mut last_col = col;
+		while last_col < SIZE as u8 - 1 && is_white(row, last_col + 1) {
+			last_col += 1;
+		}
+
+		// Walk up and down within the column.
+		let mut first_row = row;
+		while first_row > 0 && is_white(first_row - 1, col) {
+			first_row -= 1;
+		}
+		let mut last_row = row;
+		while last_row < SIZE as u8 - 1 && is_white(last_row + 1, col) {
+			last_row += 1;
+		}
+
+		(
+			(first_col..=last_col).map(|c| (row, c)).collect(),
+			(first_row..=last_row).map(|r| (r, col)).collect(),
+		)
+	}
+
+	/// Returns the ids of the row- and column-compartment containing the given cell, or None for a black cell.
+	///
+	/// This walks the cell's row and column. `AnalyzedBoard` answers the same from its cached compartments.
+	pub(crate) fn compartments_of(
+		&self,
+		position: Position,
+	) -> (Option<CompartmentId>, Option<CompartmentId>) {
+		let (row, col) = position;
+		let (row_compartment, col_compartment) = self.compartment_cells(row, col);
+		let id = |cells: &[Position], orientation| {
+			cells.first().map(|first| CompartmentId {
+				orientation,
+				first: *first,
+			})
+		};
+		(
+			id(&row_compartment, Orientation::Row),
+			id(&col_compartment, Orientation::Column),
+		)
+	}
+
+	/// Returns the cells of the compartment with the given id, or None if no compartment starts at its first cell.
+	pub(crate) fn compartment(&self, id: CompartmentId) -> Option<Vec<Position>> {
+		let (row, col) = id.first;
+		let (row_compartment, col_compartment) = self.compartment_cells(row, col);
+		let cells = match id.orientation {
+			Orientation::Row => row_compartment,
+			Orientation::Column => col_compartment,
+		};
+		(cells.first() == Some(&id.first)).then_some(cells)
+	}
+
+	/// Returns the sum clue of the compartment starting at the given cell in the given orientation, if any.
+	pub(crate) fn compartment_sum(&self, first: Position, orientation: Orientation) -> Option<u8> {
+		let (row, col) = first;
+		self.sums[Str8ts::index(row, col) as usize][orientation as usize]
+	}
+
+	/// Sets (or removes) the sum clue of the compartment starting at the given cell in the given orientation.
+	pub(crate) fn set_compartment_sum(
+		&mut self,
+		first: Position,
+		orientation: Orientation,
+		sum: Option<u8>,
+	) {
+		let (row, col) = first;
+		self.sums[Str8ts::index(row, col) as usize][orientation as usize] = sum;
+	}
+
+	/// Returns the lengths of the row- and column-compartment containing the given cell.
+	///
+	/// The shorter of both limits the range of values the cell can take: in a compartment of length `n`, no value can be
+	/// `n` or more away from another value of it.
+	///
+	/// Black cells are not part of any compartment, so `(0, 0)` is returned for them.
+	pub(crate) fn compartment_length_at(&self, row: u8, col: u8) -> (u8, u8) {
+		let (row_compartment, col_compartment) = self.compartment_cells(row, col);
+		(row_compartment.len() as u8, col_compartment.len() as u8)
+	}
+
+	/// Whether turning the black cell with the given index white would merge two compartments into one, i.e. whether it
+	/// separates two white cells of its row or of its column.
+	///
+	/// Returns false for white cells, as they separate nothing.
+	pub(crate) fn would_merge_compartments(&self, index: u8) -> bool {
+		let (row, col) = Str8ts::coords(index);
+		if self.get_cell(row, col).color != CellColor::Black {
+			return false;
+		}
+		let is_white = |row: Option<u8>, col: Option<u8>| match (row, col) {
+			(Some(row), Some(col)) if usize::from(row) < SIZE && usize::from(col) < SIZE => {
+				self.get_cell(row, col).color == CellColor::White
+			}
+			_ => false,
+		};
+		(is_white(Some(row), col.checked_sub(1)) && is_white(Some(row), col.checked_add(1)))
+			|| (is_white(row.checked_sub(1), Some(col)) && is_white(row.checked_add(1), Some(col)))
+	}
+}
+
+/// The scheme used to label rows and columns, both around the grid and in textual output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum LabelScheme {
+	/// Rows and columns are both labelled 1-9, cells are referenced as `R4C7`.
+	#[default]
+	Numeric,
+	/// Rows are labelled 1-9 and columns A-I, cells are referenced as `G4`.
+	Letters,
+}
+
+impl LabelScheme {
+	/// Returns the label of the given (zero-based) row.
+	pub(crate) fn row_label(&self, row: u8) -> String {
+		(row + 1).to_string()
+	}
+
+	/// Returns the label of the given (zero-based) column.
+	pub(crate) fn col_label(&self, col: u8) -> String {
+		match self {
+			LabelScheme::Numeric => (col + 1).to_string(),
+			LabelScheme::Letters => char::from(b'A' + col).to_string(),
+		}
+	}
+
+	/// Returns the reference of the given cell, e.g. `R4C7` or `G4`.
+	pub(crate) fn cell_reference(&self, row: u8, col: u8) -> String {
+		match self {
+			LabelScheme::Numeric => {
+				format!("R{}C{}", self.row_label(row), self.col_label(col))
 			}
+			LabelScheme::Letters => format!("{}{}", self.col_label(col), self.row_label(row)),
 		}
 	}
 }
@@ -389,7 +833,7 @@ impl Iterator for Str8tsIterator {
 	type Item = Cell;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		if self.index < 81 {
+		if (self.index as usize) < CELLS {
 			let value = self.str8ts.get_cell_by_index(self.index);
 			self.index += 1;
 			Some(value)
@@ -398,3 +842,187 @@ impl Iterator for Str8tsIterator {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts_tutorial::rules_board;
+
+	#[test]
+	fn clear_solution_keeps_givens_and_clues() {
+		let mut str8ts = rules_board();
+		let puzzle = str8ts;
+		str8ts.set_cell_value(0, 2, CellValue::Three);
+		str8ts.set_cell_value(2, 2, CellValue::Two);
+		str8ts.clear_solution();
+		assert_eq!(str8ts, puzzle);
+		assert_eq!(str8ts.get_cell(0, 1).value, CellValue::Two);
+		assert!(str8ts.get_cell(0, 1).given);
+		assert_eq!(str8ts.get_cell(2, 3).value, CellValue::Four);
+		assert_eq!(str8ts.get_cell(0, 2).value, CellValue::Empty);
+	}
+
+	#[test]
+	fn compartment_lengths_at_a_long_row_and_a_short_column() {
+		let str8ts = str8ts![
+			"#########",
+			"#########",
+			"#########",
+			"####.####",
+			".........",
+			"####.####",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert_eq!(str8ts.compartment_length_at(4, 4), (9, 3));
+		assert_eq!(str8ts.compartment_length_at(3, 4), (1, 3));
+		assert_eq!(str8ts.compartment_length_at(0, 0), (0, 0));
+	}
+
+	#[test]
+	fn overlay_fills_the_values_into_the_puzzle() {
+		let layout = rules_board();
+		let values = str8ts![
+			"..3......",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let overlaid = Str8ts::overlay(&layout, &values).unwrap();
+		assert_eq!(overlaid.get_cell(0, 2).value, CellValue::Three);
+		assert!(!overlaid.get_cell(0, 2).given);
+		assert_eq!(overlaid.get_cell(0, 1).value, CellValue::Two);
+	}
+
+	#[test]
+	fn overlay_reports_the_clashing_cells() {
+		let layout = rules_board();
+		let values = str8ts![
+			"13.4#####",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert_eq!(
+			Str8ts::overlay(&layout, &values),
+			Err(OverlayError {
+				clashes: vec![(0, 1), (0, 3)]
+			})
+		);
+	}
+
+	#[test]
+	fn cell_iterators_filter_in_reading_order() {
+		let mut str8ts = rules_board();
+		str8ts.set_cell_value(1, 2, CellValue::Three);
+		let positions = |cells: Vec<(Position, Cell)>| -> Vec<Position> {
+			cells.into_iter().map(|(position, _)| position).collect()
+		};
+		assert_eq!(str8ts.cells().count(), CELLS);
+		assert_eq!(str8ts.white_cells().count(), 9);
+		assert_eq!(str8ts.black_cells().count(), CELLS - 9);
+		assert_eq!(positions(str8ts.black_clues().collect()), vec![(2, 3)]);
+		assert_eq!(
+			positions(str8ts.given_cells().collect()),
+			vec![(0, 0), (0, 1), (1, 1)]
+		);
+		assert_eq!(
+			positions(str8ts.empty_white_cells().collect()),
+			vec![(0, 2), (1, 0), (2, 0), (2, 1), (2, 2)]
+		);
+	}
+
+	#[test]
+	fn index_and_coords_are_inverse() {
+		assert_eq!(Str8ts::index(0, 0), 0);
+		assert_eq!(Str8ts::index(1, 0), SIZE as u8);
+		assert_eq!(Str8ts::coords(CELLS as u8 - 1), (8, 8));
+		for index in 0..CELLS as u8 {
+			let (row, col) = Str8ts::coords(index);
+			assert_eq!(Str8ts::index(row, col), index);
+		}
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic(expected = "is not on the board")]
+	fn index_rejects_a_column_off_the_board() {
+		Str8ts::index(0, SIZE as u8);
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic(expected = "is not on the board")]
+	fn coords_reject_an_index_off_the_board() {
+		Str8ts::coords(CELLS as u8);
+	}
+
+	#[test]
+	fn compartment_ids_name_the_first_cell() {
+		let str8ts = rules_board();
+		let row_id = CompartmentId {
+			orientation: Orientation::Row,
+			first: (2, 0),
+		};
+		let col_id = CompartmentId {
+			orientation: Orientation::Column,
+			first: (0, 1),
+		};
+		assert_eq!(str8ts.compartments_of((2, 1)), (Some(row_id), Some(col_id)));
+		assert_eq!(str8ts.compartments_of((2, 3)), (None, None));
+		assert_eq!(
+			str8ts.compartment(row_id),
+			Some(vec![(2, 0), (2, 1), (2, 2)])
+		);
+		assert_eq!(
+			str8ts.compartment(col_id),
+			Some(vec![(0, 1), (1, 1), (2, 1)])
+		);
+		// (2, 1) lies in a compartment, but does not start one.
+		assert_eq!(
+			str8ts.compartment(CompartmentId {
+				orientation: Orientation::Row,
+				first: (2, 1),
+			}),
+			None
+		);
+	}
+
+	#[test]
+	fn black_cells_between_white_cells_would_merge_compartments() {
+		let str8ts = str8ts![
+			".#.#.####",
+			"########.",
+			"####.####",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert!(str8ts.would_merge_compartments(Str8ts::index(0, 1)));
+		assert!(str8ts.would_merge_compartments(Str8ts::index(1, 4)));
+		// A white cell on one side only, or at the edge of the board, merges nothing.
+		assert!(!str8ts.would_merge_compartments(Str8ts::index(0, 5)));
+		assert!(!str8ts.would_merge_compartments(Str8ts::index(0, 8)));
+		assert!(!str8ts.would_merge_compartments(Str8ts::index(0, 0)));
+	}
+
+	#[test]
+	fn board_has_81_cells() {
+		assert_eq!(CELLS, 81);
+		assert_eq!(Str8ts::new().into_iter().count(), CELLS);
+	}
+}
@@ -0,0 +1,427 @@
+use crate::str8ts::{CellValue, CompartmentId, Orientation, Position, Str8ts, CELLS, SIZE};
+use crate::str8ts_solver::{find_compartments, find_compartments_cols, find_compartments_rows};
+use crate::str8ts_validation::Conflict;
+
+/// A str8ts game together with its compartments, kept up to date as the board is edited.
+///
+/// Compartments only depend on the colors of the cells, so they are only recomputed when a cell's color actually
+/// changes. Value edits leave them untouched.
+#[derive(Debug, Clone)]
+pub(crate) struct AnalyzedBoard {
+	board: Str8ts,
+	/// The row-compartments, followed by the column-compartments.
+	compartments: Vec<Vec<Position>>,
+	/// The number of row-compartments.
+	row_compartments: usize,
+	/// For each cell, the index of its row- and column-compartment in `compartments`.
+	compartment_ids: [[Option<(usize, usize)>; SIZE]; SIZE],
+}
+
+impl AnalyzedBoard {
+	pub(crate) fn new(board: Str8ts) -> Self {
+		let mut analyzed = AnalyzedBoard {
+			board,
+			compartments: Vec::new(),
+			row_compartments: 0,
+			compartment_ids: [[None; SIZE]; SIZE],
+		};
+		analyzed.update_compartments();
+		analyzed
+	}
+
+	pub(crate) fn board(&self) -> &Str8ts {
+		&self.board
+	}
+
+	/// Bring the board up to date with the edited one.
+	///
+	/// The compartments are only recomputed if the color of any cell changed.
+	pub(crate) fn update_to(&mut self, board: &Str8ts) {
+		let colors_changed = self
+			.board
+			.cells()
+			.zip(board.cells())
+			.any(|((_, old), (_, new))| old.color != new.color);
+		self.board = *board;
+		if colors_changed {
+			self.update_compartments();
+		}
+	}
+
+	/// Returns all compartments, rows first and columns second.
+	pub(crate) fn compartments(&self) -> &[Vec<Position>] {
+		&self.compartments
+	}
+
+	/// Returns the orientation of the compartment with the given index in `compartments`.
+	pub(crate) fn orientation(&self, id: usize) -> Orientation {
+		if id < self.row_compartments {
+			Orientation::Row
+		} else {
+			Orientation::Column
+		}
+	}
+
+	/// Returns the cells of the compartment containing the given cell in the given orientation.
+	///
+	/// Black cells are not part of any compartment, so `None` is returned for them.
+	pub(crate) fn compartment_of(
+		&self,
+		position: Position,
+		orientation: Orientation,
+	) -> Option<&[Position]> {
+		let (row, col) = position;
+		let (row_id, col_id) = self.compartment_ids[row as usize][col as usize]?;
+		match orientation {
+			Orientation::Row => Some(&self.compartments[row_id]),
+			Orientation::Column => Some(&self.compartments[col_id]),
+		}
+	}
+
+	/// Returns the ids of the row- and column-compartment containing the given cell, or None for a black cell.
+	///
+	/// This is the cached counterpart of `Str8ts::compartments_of`.
+	pub(crate) fn compartments_of(
+		&self,
+		position: Position,
+	) -> (Option<CompartmentId>, Option<CompartmentId>) {
+		let id = |orientation| {
+			self.compartment_of(position, orientation)
+				.map(|cells| CompartmentId {
+					orientation,
+					first: cells[0],
+				})
+		};
+		(id(Orientation::Row), id(Orientation::Column))
+	}
+
+	/// Recompute the compartments and the compartment index of every cell.
+	fn update_compartments(&mut self) {
+		let to_positions = |compartment: Vec<u8>| -> Vec<Position> {
+			compartment.into_iter().map(Str8ts::coords).collect()
+		};
+		let rows: Vec<Vec<Position>> = find_compartments_rows(&self.board)
+			.into_iter()
+			.map(to_positions)
+			.collect();
+		let cols: Vec<Vec<Position>> = find_compartments_cols(&self.board)
+			.into_iter()
+			.map(to_positions)
+			.collect();
+
+		let mut row_ids = [[0; SIZE]; SIZE];
+		for (id, compartment) in rows.iter().enumerate() {
+			for (row, col) in compartment {
+				row_ids[*row as usize][*col as usize] = id;
+			}
+		}
+		self.compartment_ids = [[None; SIZE]; SIZE];
+		for (id, compartment) in cols.iter().enumerate() {
+			for (row, col) in compartment {
+				self.compartment_ids[*row as usize][*col as usize] =
+					Some((row_ids[*row as usize][*col as usize], rows.len() + id));
+			}
+		}
+
+		self.row_compartments = rows.len();
+		self.compartments = rows;
+		self.compartments.extend(cols);
+	}
+}
+
+/// The candidates and conflicts of a str8ts game, computed together in a single pass.
+#[derive(Debug, Clone)]
+pub(crate) struct BoardAnalysis {
+	/// The candidates of each cell, in reading order.
+	candidates: Vec<Vec<CellValue>>,
+	/// The conflicts, in the same order as `Str8ts::conflicts` returns them.
+	pub(crate) conflicts: Vec<Conflict>,
+}
+
+impl BoardAnalysis {
+	/// Returns the candidates of the given cell, see `Str8ts::candidates`.
+	pub(crate) fn candidates(&self, row: u8, col: u8) -> &[CellValue] {
+		&self.candidates[Str8ts::index(row, col) as usize]
+	}
+}
+
+/// Compute the candidates of all cells and the conflicts of the analyzed str8ts game.
+///
+/// This gives the same results as `Str8ts::candidates` and `Str8ts::conflicts`, but scans the board only once: the
+/// values used per row and column, and the range of values placed per compartment, are shared between both.
+pub(crate) fn analyze(analyzed: &AnalyzedBoard) -> BoardAnalysis {
+	let str8ts = analyzed.board();
+
+	// The cells holding each value, per row and per column (black cells included).
+	let mut row_cells: [[Vec<Position>; 10]; SIZE] = Default::default();
+	let mut col_cells: [[Vec<Position>; 10]; SIZE] = Default::default();
+	for row in 0..SIZE as u8 {
+		for col in 0..SIZE as u8 {
+			let value: usize = str8ts.get_cell(row, col).value.into();
+			if value != 0 {
+				row_cells[row as usize][value].push((row, col));
+				col_cells[col as usize][value].push((row, col));
+			}
+		}
+	}
+
+	// The cells holding each value, per group of the variant rules.
+	let groups = str8ts.rules.distinct_groups();
+	let group_cells: Vec<[Vec<Position>; 10]> = groups
+		.iter()
+		.map(|group| {
+			let mut cells: [Vec<Position>; 10] = Default::default();
+			for (row, col) in group.cells.iter() {
+				let value: usize = str8ts.get_cell(*row, *col).value.into();
+				if value != 0 {
+					cells[value].push((*row, *col));
+				}
+			}
+			cells
+		})
+		.collect();
+
+	// The least and greatest value placed in each compartment, if any.
+	let ranges: Vec<Option<(u8, u8)>> = analyzed
+		.compartments()
+		.iter()
+		.map(|compartment| {
+			compartment
+				.iter()
+				.map(|(row, col)| u8::from(str8ts.get_cell(*row, *col).value))
+				.filter(|value| *value != 0)
+				.fold(None, |range, value| match range {
+					None => Some((value, value)),
+					Some((min, max)) => Some((min.min(value), max.max(value))),
+				})
+		})
+		.collect();
+	let fits = |id: usize, value: u8| match ranges[id] {
+		None => true,
+		Some((min, max)) => {
+			usize::from(max.max(value) - min.min(value)) < analyzed.compartments()[id].len()
+		}
+	};
+
+	let mut candidates = Vec::with_capacity(CELLS);
+	for row in 0..SIZE as u8 {
+		for col in 0..SIZE as u8 {
+			let cell = str8ts.get_cell(row, col);
+			let cell_candidates = match analyzed.compartment_ids[row as usize][col as usize] {
+				Some((row_id, col_id)) if cell.value == CellValue::Empty => {
+					CellValue::into_iter(false)
+						.filter(|value| {
+							let number_value: u8 = (*value).into();
+							row_cells[row as usize][number_value as usize].is_empty()
+								&& col_cells[col as usize][number_value as usize].is_empty()
+								&& cell.constraint.is_none_or(|c| c.allows(*value))
+								&& groups.iter().zip(&group_cells).all(|(group, cells)| {
+									!group.cells.contains(&(row, col))
+										|| cells[number_value as usize].is_empty()
+								}) && fits(row_id, number_value)
+								&& fits(col_id, number_value)
+						})
+						.collect()
+				}
+				_ => Vec::new(),
+			};
+			candidates.push(cell_candidates);
+		}
+	}
+
+	let mut conflicts = Vec::new();
+	for (row, values) in row_cells.iter().enumerate() {
+		for (value, cells) in values.iter().enumerate() {
+			if cells.len() > 1 {
+				conflicts.push(Conflict::RowDuplicate {
+					row: row as u8,
+					value: CellValue::from(value),
+					cells: cells.clone(),
+				});
+			}
+		}
+	}
+	for (col, values) in col_cells.iter().enumerate() {
+		for (value, cells) in values.iter().enumerate() {
+			if cells.len() > 1 {
+				conflicts.push(Conflict::ColumnDuplicate {
+					col: col as u8,
+					value: CellValue::from(value),
+					cells: cells.clone(),
+				});
+			}
+		}
+	}
+	for (group, values) in groups.iter().zip(&group_cells) {
+		for (value, cells) in values.iter().enumerate() {
+			if cells.len() > 1 {
+				conflicts.push(Conflict::RuleDuplicate {
+					rule: group.rule,
+					value: CellValue::from(value),
+					cells: cells.clone(),
+				});
+			}
+		}
+	}
+	for (id, (compartment, range)) in analyzed.compartments().iter().zip(&ranges).enumerate() {
+		if let Some((min, max)) = range {
+			if usize::from(max - min) >= compartment.len() {
+				conflicts.push(Conflict::CompartmentNotStraight {
+					cells: compartment.clone(),
+				});
+			}
+		}
+		conflicts.extend(str8ts.sum_conflict(compartment, analyzed.orientation(id)));
+	}
+
+	BoardAnalysis {
+		candidates,
+		conflicts,
+	}
+}
+
+impl Str8ts {
+	/// Describes the board in one line, such as `32 white cells, 14 compartments (avg len 2.3), 18 clues given, 12 empty`.
+	///
+	/// Compartments are counted in both orientations, clues are the values of given white cells and of black cells, and
+	/// empty counts the white cells without a value. The format is kept stable, so it can be parsed.
+	pub(crate) fn summary(&self) -> String {
+		let compartments = find_compartments(self);
+		let compartment_cells: usize = compartments.iter().map(Vec::len).sum();
+		let average_length = if compartments.is_empty() {
+			0.0
+		} else {
+			compartment_cells as f64 / compartments.len() as f64
+		};
+		format!(
+			"{} white cells, {} compartments (avg len {:.1}), {} clues given, {} empty",
+			self.white_cells().count(),
+			compartments.len(),
+			average_length,
+			self.given_cells().count() + self.black_clues().count(),
+			self.empty_white_cells().count()
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts::{CellColor, ValueConstraint};
+	use crate::str8ts_candidates::stranded_board;
+	use crate::str8ts_tutorial::rules_board;
+
+	#[test]
+	fn cached_compartments_match_the_board() {
+		let mut board = rules_board();
+		for col in 4..SIZE as u8 {
+			board.set_cell_color(4, col, CellColor::White);
+		}
+		let analyzed = AnalyzedBoard::new(board);
+		for (position, _) in board.cells() {
+			let ids = board.compartments_of(position);
+			assert_eq!(analyzed.compartments_of(position), ids);
+			for id in [ids.0, ids.1].into_iter().flatten() {
+				assert_eq!(
+					analyzed
+						.compartment_of(id.first, id.orientation)
+						.map(<[Position]>::to_vec),
+					board.compartment(id)
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn updating_the_colors_splits_and_merges_compartments() {
+		let mut board = str8ts![
+			"...######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let mut analyzed = AnalyzedBoard::new(board);
+		board.set_cell_value(0, 0, CellValue::One);
+		analyzed.update_to(&board);
+		assert_eq!(analyzed.board().get_cell(0, 0).value, CellValue::One);
+		assert_eq!(
+			analyzed.compartment_of((0, 2), Orientation::Row),
+			Some(&[(0, 0), (0, 1), (0, 2)][..])
+		);
+
+		board.toggle_cell_color(0, 1);
+		analyzed.update_to(&board);
+		assert_eq!(
+			analyzed.compartment_of((0, 2), Orientation::Row),
+			Some(&[(0, 2)][..])
+		);
+		assert_eq!(analyzed.compartment_of((0, 1), Orientation::Row), None);
+
+		board.toggle_cell_color(0, 1);
+		analyzed.update_to(&board);
+		assert_eq!(
+			analyzed.compartment_of((0, 2), Orientation::Row),
+			Some(&[(0, 0), (0, 1), (0, 2)][..])
+		);
+	}
+
+	#[test]
+	fn summary_counts_cells_compartments_and_clues() {
+		let board = rules_board();
+		assert_eq!(
+			board.summary(),
+			"9 white cells, 6 compartments (avg len 3.0), 4 clues given, 6 empty"
+		);
+		assert_eq!(
+			Str8ts::new().summary(),
+			"81 white cells, 18 compartments (avg len 9.0), 0 clues given, 81 empty"
+		);
+	}
+
+	/// Asserts that `analyze` finds the same candidates and conflicts as `Str8ts::candidates` and `Str8ts::conflicts`.
+	fn assert_analysis_matches(board: Str8ts) {
+		let analysis = analyze(&AnalyzedBoard::new(board));
+		assert_eq!(analysis.conflicts, board.conflicts());
+		for ((row, col), _) in board.cells() {
+			assert_eq!(
+				analysis.candidates(row, col),
+				board.candidates(row, col),
+				"candidates of ({}, {})",
+				row,
+				col
+			);
+		}
+	}
+
+	#[test]
+	fn analysis_matches_candidates_and_conflicts() {
+		assert_analysis_matches(Str8ts::new());
+		assert_analysis_matches(stranded_board());
+
+		let mut board = rules_board();
+		for col in 4..SIZE as u8 {
+			board.set_cell_color(4, col, CellColor::White);
+		}
+		assert_analysis_matches(board);
+		// Variant rules, breaking the main diagonal with the two 1s on it.
+		board.rules.diagonals = true;
+		board.rules.anti_knight = true;
+		assert_analysis_matches(board);
+		// A sum clue the values exceed, a value constraint, and a row duplicate.
+		board.set_compartment_sum((4, 4), Orientation::Row, Some(10));
+		board.set_cell_value(4, 4, CellValue::Nine);
+		board.set_cell_value(4, 5, CellValue::Eight);
+		board.set_cell_constraint(4, 7, Some(ValueConstraint::AtMost(CellValue::Seven)));
+		board.set_cell_value(0, 2, CellValue::Two);
+		assert_analysis_matches(board);
+		assert!(board
+			.conflicts()
+			.iter()
+			.any(|conflict| matches!(conflict, Conflict::CompartmentSum { .. })));
+	}
+}
@@ -0,0 +1,68 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use crate::str8ts::Str8ts;
+use crate::str8ts_solver::Uniqueness;
+
+/// The rating of a single puzzle file.
+struct Rating {
+	unique: bool,
+	difficulty: String,
+	solve_ms: u128,
+}
+
+/// Solve and rate every `.str8ts` file in the given directory, and print the results as CSV to stdout.
+///
+/// A file that cannot be read, parsed, or contains conflicting values is reported on stderr and skipped,
+/// so one bad file does not abort the batch.
+pub(crate) fn rate_dir(path: &Path) -> std::io::Result<()> {
+	let mut files: Vec<_> = fs::read_dir(path)?
+		.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+		.filter(|file| {
+			file.extension()
+				.is_some_and(|extension| extension == "str8ts")
+		})
+		.collect();
+	files.sort();
+
+	println!("filename,unique,difficulty,solve_ms");
+	for file in files {
+		let filename = file
+			.file_name()
+			.map(|name| name.to_string_lossy().into_owned())
+			.unwrap_or_default();
+		match rate_file(&file) {
+			Ok(rating) => println!(
+				"{},{},{},{}",
+				filename, rating.unique, rating.difficulty, rating.solve_ms
+			),
+			Err(error) => eprintln!("{}: {}", filename, error),
+		}
+	}
+	Ok(())
+}
+
+/// Load, solve, and rate a single puzzle file.
+fn rate_file(file: &Path) -> Result<Rating, Box<dyn Error>> {
+	let str8ts: Str8ts = fs::read_to_string(file)?.parse()?;
+	if let Some(conflict) = str8ts.conflicts().first() {
+		return Err(conflict.to_string().into());
+	}
+
+	let start = Instant::now();
+	let uniqueness = str8ts.uniqueness();
+	let solve_ms = start.elapsed().as_millis();
+
+	let (unique, difficulty) = match uniqueness {
+		Uniqueness::Infeasible => (false, String::from("Unsolvable")),
+		Uniqueness::Unique(_) => (true, str8ts.solve_logically().difficulty().to_string()),
+		Uniqueness::Multiple(_, _) => (false, str8ts.solve_logically().difficulty().to_string()),
+	};
+	Ok(Rating {
+		unique,
+		difficulty,
+		solve_ms,
+	})
+}
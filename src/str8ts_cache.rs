@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::str8ts::{CellValue, Position, Str8ts};
+use crate::str8ts_logic::Difficulty;
+use crate::str8ts_solver::{FillSelection, SolveError, SolvedBoard, SplitMix64, Uniqueness};
+use crate::str8ts_symmetry::Isomorphism;
+
+/// How many puzzles a `SolutionCache` remembers by default.
+const DEFAULT_CAPACITY: usize = 64;
+
+/// What is known about a puzzle, in the orientation of its canonical form. Each part is filled in when first needed.
+#[derive(Debug, Clone, Default)]
+struct CachedPuzzle {
+	/// A solution, or None if the puzzle has none. Unset until the puzzle is solved.
+	solution: Option<Option<Str8ts>>,
+	uniqueness: Option<Uniqueness>,
+	difficulty: Option<Difficulty>,
+	/// The value of the cache's clock when the puzzle was last looked up, to evict the least recently used one.
+	last_used: u64,
+}
+
+/// The puzzle of a board (its layout and givens) as the cache knows it.
+struct PuzzleKey {
+	canonical: Str8ts,
+	/// Whether the board holds values besides its givens, which its puzzle does not constrain.
+	has_entries: bool,
+	/// Transforms the canonical form into the puzzle.
+	to_board: Isomorphism,
+	/// Transforms the puzzle into its canonical form.
+	to_canonical: Isomorphism,
+}
+
+impl PuzzleKey {
+	fn of(board: &Str8ts) -> PuzzleKey {
+		let mut puzzle = *board;
+		puzzle.clear_solution();
+		let canonical = puzzle.canonical();
+		PuzzleKey {
+			canonical,
+			has_entries: puzzle != *board,
+			// Both exist, as the canonical form is a rotation or reflection of the puzzle.
+			to_board: canonical.is_isomorphic_to(&puzzle).unwrap(),
+			to_canonical: puzzle.is_isomorphic_to(&canonical).unwrap(),
+		}
+	}
+}
+
+/// How often a `SolutionCache` answered from memory (a hit), and how often the solver had to run instead (a miss).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct CacheStats {
+	pub(crate) hits: usize,
+	pub(crate) misses: usize,
+}
+
+impl Display for CacheStats {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} cache hits, {} misses", self.hits, self.misses)
+	}
+}
+
+/// Remembers the solutions, uniqueness and ratings of recently solved puzzles, so solving the same puzzle again is free.
+///
+/// Puzzles are keyed by the canonical form of their layout and givens (see `Str8ts::canonical`), so a rotation or
+/// reflection of a puzzle shares its entry, and so do boards that only differ in the values entered while solving. As
+/// the key is the puzzle itself, changing a given or a color makes it another puzzle, and the results of the old one
+/// are never returned for it. Once full, the least recently used puzzle is forgotten.
+///
+/// The cache is passed to whatever solves, so one cache can serve all boards of a session.
+#[derive(Debug, Clone)]
+pub(crate) struct SolutionCache {
+	capacity: usize,
+	puzzles: HashMap<Str8ts, CachedPuzzle>,
+	/// Counts the lookups, to tell which puzzle was used least recently.
+	clock: u64,
+	stats: CacheStats,
+}
+
+impl Default for SolutionCache {
+	fn default() -> Self {
+		SolutionCache::with_capacity(DEFAULT_CAPACITY)
+	}
+}
+
+impl SolutionCache {
+	/// Creates an empty cache remembering up to the given number of puzzles (at least one).
+	pub(crate) fn with_capacity(capacity: usize) -> SolutionCache {
+		SolutionCache {
+			capacity: capacity.max(1),
+			puzzles: HashMap::new(),
+			clock: 0,
+			stats: CacheStats::default(),
+		}
+	}
+
+	/// Returns how often the cache was hit and missed so far.
+	pub(crate) fn stats(&self) -> CacheStats {
+		self.stats
+	}
+
+	/// Solves the board like `Str8ts::solve`, unless a solution of its puzzle is known that agrees with the values
+	/// entered on the board.
+	pub(crate) fn solve(&mut self, board: &Str8ts) -> Option<Str8ts> {
+		if let Some(solution) = self.cached_solution(board) {
+			return solution;
+		}
+		let solution = board.solve();
+		self.store_solution(board, solution);
+		solution
+	}
+
+	/// Like `Str8ts::solved`, but solving through the cache.
+	pub(crate) fn solved(&mut self, board: &Str8ts) -> Option<SolvedBoard> {
+		self.solve(board).map(SolvedBoard::new)
+	}
+
+	/// Fills the selected cells of the board like `Str8ts::fill_from`, with the solution of its puzzle taken from the cache.
+	pub(crate) fn fill_from_solution(
+		&mut self,
+		board: &mut Str8ts,
+		selection: FillSelection,
+		rng: &mut SplitMix64,
+	) -> Result<Vec<(Position, CellValue)>, SolveError> {
+		let mut puzzle = *board;
+		puzzle.clear_solution();
+		let solution = self.solve(&puzzle).ok_or(SolveError::Infeasible)?;
+		board.fill_from(&solution, selection, rng)
+	}
+
+	/// Returns the result of solving the board, if it is known: Some(None) if the board has no solution, and
+	/// Some(Some(solution)) for a solution agreeing with the values entered on the board.
+	///
+	/// Use this and `store_solution` instead of `solve` to solve in the background, where the cache cannot be borrowed.
+	pub(crate) fn cached_solution(&mut self, board: &Str8ts) -> Option<Option<Str8ts>> {
+		let key = PuzzleKey::of(board);
+		let solution = match self.lookup(&key).and_then(|puzzle| puzzle.solution) {
+			// Without a solution for the puzzle, there is none for the board either.
+			Some(None) => Some(None),
+			Some(Some(solution)) => Some(solution.transformed(key.to_board))
+				.filter(|solution| agrees(board, solution))
+				.map(Some),
+			None => None,
+		};
+		self.count(solution.is_some());
+		solution
+	}
+
+	/// Remembers the result of solving the board.
+	///
+	/// Any solution of the board also solves its puzzle. Boards with entered values may have none even though their
+	/// puzzle has one, though, so that is only remembered for boards without entered values.
+	pub(crate) fn store_solution(&mut self, board: &Str8ts, solution: Option<Str8ts>) {
+		let key = PuzzleKey::of(board);
+		if solution.is_none() && key.has_entries {
+			return;
+		}
+		self.entry(&key).solution =
+			Some(solution.map(|solution| solution.transformed(key.to_canonical)));
+	}
+
+	/// Returns whether the board has a solution, like `Str8ts::is_solvable`, if that is known.
+	pub(crate) fn cached_solvability(&mut self, board: &Str8ts) -> Option<bool> {
+		self.cached_solution(board)
+			.map(|solution| solution.is_some())
+	}
+
+	/// Remembers whether the board has a solution. Like `store_solution`, only an unsolvable board without entered values
+	/// tells anything about its puzzle.
+	pub(crate) fn store_solvability(&mut self, board: &Str8ts, solvable: bool) {
+		if !solvable {
+			self.store_solution(board, None);
+		}
+	}
+
+	/// Returns whether the board has a unique solution like `Str8ts::uniqueness`, solving it only if that is not known
+	/// yet.
+	pub(crate) fn uniqueness(&mut self, board: &Str8ts) -> Uniqueness {
+		if let Some(uniqueness) = self.cached_uniqueness(board) {
+			return uniqueness;
+		}
+		let uniqueness = board.uniqueness();
+		self.store_uniqueness(board, &uniqueness);
+		uniqueness
+	}
+
+	/// Returns whether the board has a unique solution, if that is known.
+	///
+	/// Values entered on the board rule out some solutions of its puzzle, so only boards without entered values are
+	/// looked up.
+	pub(crate) fn cached_uniqueness(&mut self, board: &Str8ts) -> Option<Uniqueness> {
+		let key = PuzzleKey::of(board);
+		let uniqueness = if key.has_entries {
+			None
+		} else {
+			self.lookup(&key)
+				.and_then(|puzzle| puzzle.uniqueness.as_ref())
+				.map(|uniqueness| transform_uniqueness(uniqueness, key.to_board))
+		};
+		self.count(uniqueness.is_some());
+		uniqueness
+	}
+
+	/// Remembers whether the board has a unique solution, along with the solution it implies. Only boards without
+	/// entered values are remembered, see `cached_uniqueness`.
+	pub(crate) fn store_uniqueness(&mut self, board: &Str8ts, uniqueness: &Uniqueness) {
+		let key = PuzzleKey::of(board);
+		if key.has_entries {
+			return;
+		}
+		let uniqueness = transform_uniqueness(uniqueness, key.to_canonical);
+		let puzzle = self.entry(&key);
+		puzzle.solution = Some(match &uniqueness {
+			Uniqueness::Infeasible => None,
+			Uniqueness::Unique(solution) | Uniqueness::Multiple(solution, _) => Some(*solution),
+		});
+		puzzle.uniqueness = Some(uniqueness);
+	}
+
+	/// Returns the difficulty of the board's puzzle (ignoring the values entered on the board), rating it only if that
+	/// is not known yet.
+	pub(crate) fn difficulty(&mut self, board: &Str8ts) -> Difficulty {
+		let key = PuzzleKey::of(board);
+		if let Some(difficulty) = self.lookup(&key).and_then(|puzzle| puzzle.difficulty) {
+			self.count(true);
+			return difficulty;
+		}
+		self.count(false);
+		// The rating does not depend on the orientation, so the canonical form can be rated directly.
+		let difficulty = key.canonical.solve_logically().difficulty();
+		self.entry(&key).difficulty = Some(difficulty);
+		difficulty
+	}
+
+	/// Returns the cached puzzle, if any, marking it as used.
+	fn lookup(&mut self, key: &PuzzleKey) -> Option<&CachedPuzzle> {
+		self.clock += 1;
+		let clock = self.clock;
+		self.puzzles.get_mut(&key.canonical).map(|puzzle| {
+			puzzle.last_used = clock;
+			&*puzzle
+		})
+	}
+
+	/// Returns the cached puzzle, adding it first if needed, which evicts the least recently used puzzle if the cache
+	/// is full.
+	fn entry(&mut self, key: &PuzzleKey) -> &mut CachedPuzzle {
+		self.clock += 1;
+		if !self.puzzles.contains_key(&key.canonical) && self.puzzles.len() >= self.capacity {
+			if let Some(oldest) = self
+				.puzzles
+				.iter()
+				.min_by_key(|(_, puzzle)| puzzle.last_used)
+				.map(|(canonical, _)| *canonical)
+			{
+				self.puzzles.remove(&oldest);
+			}
+		}
+		let puzzle = self.puzzles.entry(key.canonical).or_default();
+		puzzle.last_used = self.clock;
+		puzzle
+	}
+
+	fn count(&mut self, is_hit: bool) {
+		if is_hit {
+			self.stats.hits += 1;
+		} else {
+			self.stats.misses += 1;
+		}
+	}
+}
+
+/// Whether the solution holds every value entered into a white cell of the board.
+fn agrees(board: &Str8ts, solution: &Str8ts) -> bool {
+	board.white_cells().all(|((row, col), cell)| {
+		cell.value == CellValue::Empty || solution.get_cell(row, col).value == cell.value
+	})
+}
+
+/// Returns the uniqueness with its solutions transformed by the isomorphism.
+fn transform_uniqueness(uniqueness: &Uniqueness, isomorphism: Isomorphism) -> Uniqueness {
+	match uniqueness {
+		Uniqueness::Infeasible => Uniqueness::Infeasible,
+		Uniqueness::Unique(solution) => Uniqueness::Unique(solution.transformed(isomorphism)),
+		Uniqueness::Multiple(first, second) => Uniqueness::Multiple(
+			first.transformed(isomorphism),
+			second.transformed(isomorphism),
+		),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts_symmetry::Symmetry;
+
+	/// A puzzle with a single straight, starting with the given value and fully given except for its middle cell.
+	fn straight_from(first: u8) -> Str8ts {
+		let mut str8ts = str8ts![
+			"...######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		str8ts.set_cell_value(0, 0, CellValue::from(first));
+		str8ts.set_cell_given(0, 0, true);
+		str8ts.set_cell_value(0, 2, CellValue::from(first + 2));
+		str8ts.set_cell_given(0, 2, true);
+		str8ts
+	}
+
+	#[test]
+	fn solving_again_hits_the_cache() {
+		let mut cache = SolutionCache::default();
+		let solution = cache.solve(&straight_from(2));
+		assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 1 });
+		assert_eq!(cache.solve(&straight_from(2)), solution);
+		assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1 });
+		assert_eq!(solution.unwrap().get_cell(0, 1).value, CellValue::Three);
+	}
+
+	#[test]
+	fn rotated_puzzle_shares_the_entry() {
+		let mut cache = SolutionCache::default();
+		let solution = cache.solve(&straight_from(2)).unwrap();
+		let rotation = Isomorphism {
+			symmetry: Symmetry::Rotate90,
+			reversed: false,
+		};
+		let rotated = straight_from(2).transformed(rotation);
+		assert_eq!(cache.solve(&rotated), Some(solution.transformed(rotation)));
+		assert_eq!(cache.stats().hits, 1);
+	}
+
+	#[test]
+	fn least_recently_used_puzzle_is_evicted() {
+		let mut cache = SolutionCache::with_capacity(2);
+		cache.solve(&straight_from(1));
+		cache.solve(&straight_from(2));
+		// Using the first puzzle again makes the second one the least recently used.
+		cache.solve(&straight_from(1));
+		cache.solve(&straight_from(3));
+		assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 3 });
+		cache.solve(&straight_from(1));
+		assert_eq!(cache.stats().hits, 2);
+		cache.solve(&straight_from(2));
+		assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 4 });
+	}
+
+	#[test]
+	fn checking_entries_after_a_solve_hits_the_cache() {
+		let mut cache = SolutionCache::default();
+		let puzzle = straight_from(2);
+		let solution = puzzle.solve();
+		cache.store_solution(&puzzle, solution);
+		let stats = cache.stats();
+
+		let mut board = puzzle;
+		board.set_cell_value(0, 1, CellValue::Three);
+		let solved = cache.solved(&board).unwrap();
+		assert!(solved.is_correct(0, 1, CellValue::Three));
+		assert_eq!(
+			cache.stats(),
+			CacheStats {
+				hits: stats.hits + 1,
+				misses: stats.misses
+			}
+		);
+	}
+}
@@ -0,0 +1,494 @@
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
+use crate::str8ts::{
+	CellColor, CellValue, LabelScheme, Orientation, Position, Str8ts, ValueConstraint, SIZE,
+};
+
+/// The reason a value cannot be placed into a cell, see `Str8ts::place_check`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PlacementError {
+	/// The cell at `at` (on `cell`'s row, column or variant group) already holds the value.
+	Duplicate {
+		value: CellValue,
+		cell: Position,
+		at: Position,
+	},
+	/// The value breaks the cell's value constraint.
+	Constraint {
+		value: CellValue,
+		constraint: ValueConstraint,
+	},
+	/// The value does not fit into a straight with the values placed in the cell's compartment of this orientation.
+	OutOfRange {
+		value: CellValue,
+		orientation: Orientation,
+	},
+}
+
+impl Display for PlacementError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let labels = LabelScheme::default();
+		match self {
+			PlacementError::Duplicate { value, cell, at } => {
+				let ((row, col), (at_row, at_col)) = (*cell, *at);
+				if at_row == row {
+					write!(f, "{} already appears in this row", value)
+				} else if at_col == col {
+					write!(f, "{} already appears in this column", value)
+				} else {
+					write!(
+						f,
+						"{} already appears in {}, which the variant rules forbid",
+						value,
+						labels.cell_reference(at_row, at_col)
+					)
+				}
+			}
+			PlacementError::Constraint { value, constraint } => match constraint {
+				ValueConstraint::AtMost(bound) => {
+					write!(f, "{} is larger than this cell's bound of {}", value, bound)
+				}
+				ValueConstraint::AtLeast(bound) => {
+					write!(
+						f,
+						"{} is smaller than this cell's bound of {}",
+						value, bound
+					)
+				}
+			},
+			PlacementError::OutOfRange { value, orientation } => write!(
+				f,
+				"{} does not form a straight with the other values of this {}",
+				value,
+				match orientation {
+					Orientation::Row => "row-compartment",
+					Orientation::Column => "column-compartment",
+				}
+			),
+		}
+	}
+}
+
+impl std::error::Error for PlacementError {}
+
+/// The values of a row or column, split into those placed, those no cell of the line can take anymore, and those still
+/// to be placed. Each list is sorted, and every value is in exactly one of them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct LineSummary {
+	/// The values placed in the line, black clues included.
+	pub(crate) placed: Vec<CellValue>,
+	/// The values not placed, which are no candidate of any empty white cell of the line.
+	pub(crate) impossible: Vec<CellValue>,
+	/// The values not placed, which are a candidate of some empty white cell of the line.
+	pub(crate) missing: Vec<CellValue>,
+}
+
+/// The `LineSummary` of every row and every column, see `Str8ts::line_summaries`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LineSummaries {
+	pub(crate) rows: [LineSummary; SIZE],
+	pub(crate) cols: [LineSummary; SIZE],
+}
+
+impl Str8ts {
+	/// Returns the values that can still be placed into the given cell.
+	///
+	/// A value is a candidate if it does not appear in the cell's row or column yet (black cells included), nor in any
+	/// other group of the cell's variant rules, if it satisfies the cell's value constraint (if any), and if it still fits
+	/// into a straight together with the values already placed in the cell's row- and column-compartment.
+	/// Black cells and white cells that already contain a value have no candidates.
+	pub(crate) fn candidates(&self, row: u8, col: u8) -> Vec<CellValue> {
+		let cell = self.get_cell(row, col);
+		if cell.color == CellColor::Black || cell.value != CellValue::Empty {
+			return Vec::new();
+		}
+
+		// Values used anywhere in the same row or column, or in the same group of a variant rule.
+		let mut used = [false; 10];
+		for (i, j) in self.peers(row, col) {
+			let peer_value: usize = self.get_cell(i, j).value.into();
+			used[peer_value] = true;
+		}
+
+		let (row_compartment, col_compartment) = self.compartment_cells(row, col);
+		CellValue::into_iter(false)
+			.filter(|value| {
+				let number_value: usize = (*value).into();
+				!used[number_value]
+					&& cell.constraint.is_none_or(|c| c.allows(*value))
+					&& self.fits_compartment(&row_compartment, *value)
+					&& self.fits_compartment(&col_compartment, *value)
+			})
+			.collect()
+	}
+
+	/// Checks whether the given value can be placed into the given cell, given the values placed so far.
+	///
+	/// The value must not appear elsewhere in the cell's row or column (black cells included), nor in any other group of
+	/// the cell's variant rules. For white cells, it must also satisfy the cell's value constraint and still fit into a
+	/// straight with the rest of the cell's compartments. Unlike `solve`, this does not check whether the board remains
+	/// solvable. Any value already in the cell is ignored, as it would be replaced.
+	pub(crate) fn can_place(&self, row: u8, col: u8, value: CellValue) -> bool {
+		self.place_check(row, col, value).is_ok()
+	}
+
+	/// Checks whether the given value can be placed into the given cell like `can_place`, and if not, tells why.
+	///
+	/// If the value breaks several rules, the first one is reported in the order of `can_place`.
+	pub(crate) fn place_check(
+		&self,
+		row: u8,
+		col: u8,
+		value: CellValue,
+	) -> Result<(), PlacementError> {
+		if value == CellValue::Empty {
+			return Ok(());
+		}
+		let mut board = *self;
+		board.set_cell_value(row, col, CellValue::Empty);
+
+		if let Some(at) = board
+			.peers(row, col)
+			.find(|(i, j)| board.get_cell(*i, *j).value == value)
+		{
+			return Err(PlacementError::Duplicate {
+				value,
+				cell: (row, col),
+				at,
+			});
+		}
+		let cell = board.get_cell(row, col);
+		if cell.color == CellColor::Black {
+			return Ok(());
+		}
+		if let Some(constraint) = cell.constraint.filter(|c| !c.allows(value)) {
+			return Err(PlacementError::Constraint { value, constraint });
+		}
+		let (row_compartment, col_compartment) = board.compartment_cells(row, col);
+		for (compartment, orientation) in [
+			(row_compartment, Orientation::Row),
+			(col_compartment, Orientation::Column),
+		] {
+			if !board.fits_compartment(&compartment, value) {
+				return Err(PlacementError::OutOfRange { value, orientation });
+			}
+		}
+		Ok(())
+	}
+
+	/// Summarizes the values of every row and column, see `LineSummary`.
+	///
+	/// A line without empty white cells (e.g. a fully black one) has every value it lacks among the impossible ones.
+	pub(crate) fn line_summaries(&self) -> LineSummaries {
+		LineSummaries {
+			rows: std::array::from_fn(|row| {
+				self.line_summary((0..SIZE as u8).map(|col| (row as u8, col)))
+			}),
+			cols: std::array::from_fn(|col| {
+				self.line_summary((0..SIZE as u8).map(|row| (row, col as u8)))
+			}),
+		}
+	}
+
+	/// Summarizes the values of the given cells of a line.
+	fn line_summary(&self, line: impl Iterator<Item = Position> + Clone) -> LineSummary {
+		let placed: Vec<CellValue> = line
+			.clone()
+			.map(|(row, col)| self.get_cell(row, col).value)
+			.filter(|value| *value != CellValue::Empty)
+			.collect();
+		let candidates: Vec<CellValue> = line
+			.flat_map(|(row, col)| self.candidates(row, col))
+			.collect();
+		let mut summary = LineSummary::default();
+		for value in CellValue::into_iter(false) {
+			if placed.contains(&value) {
+				summary.placed.push(value);
+			} else if candidates.contains(&value) {
+				summary.missing.push(value);
+			} else {
+				summary.impossible.push(value);
+			}
+		}
+		summary
+	}
+
+	/// Returns the straights (windows of consecutive values) the given compartment can still be filled with.
+	///
+	/// A window is possible if it contains all values placed in the compartment, and each of its missing values is still
+	/// a candidate of some empty cell of the compartment. An empty list means the compartment cannot be completed.
+	pub(crate) fn possible_windows(&self, compartment: &[Position]) -> Vec<RangeInclusive<u8>> {
+		let length = compartment.len() as u8;
+		if length == 0 {
+			return Vec::new();
+		}
+		let placed: Vec<u8> = compartment
+			.iter()
+			.map(|(row, col)| u8::from(self.get_cell(*row, *col).value))
+			.filter(|value| *value != 0)
+			.collect();
+		let candidates: Vec<Vec<u8>> = compartment
+			.iter()
+			.map(|(row, col)| {
+				self.candidates(*row, *col)
+					.into_iter()
+					.map(u8::from)
+					.collect()
+			})
+			.collect();
+
+		(1..=(10 - length))
+			.map(|first| first..=(first + length - 1))
+			.filter(|window| {
+				placed.iter().all(|value| window.contains(value))
+					&& window.clone().all(|value| {
+						placed.contains(&value) || candidates.iter().any(|c| c.contains(&value))
+					})
+			})
+			.collect()
+	}
+
+	/// Returns the values that are not yet placed in the compartment, but are part of every straight the compartment
+	/// can still be filled with (its sure candidates).
+	///
+	/// A sure candidate must be placed in the compartment, so it can be ruled out in the rest of the compartment's row
+	/// or column.
+	pub(crate) fn sure_candidates(&self, compartment: &[Position]) -> Vec<CellValue> {
+		let windows = self.possible_windows(compartment);
+		if windows.is_empty() {
+			return Vec::new();
+		}
+		let placed: Vec<CellValue> = compartment
+			.iter()
+			.map(|(row, col)| self.get_cell(*row, *col).value)
+			.collect();
+
+		CellValue::into_iter(false)
+			.filter(|value| {
+				let number_value: u8 = (*value).into();
+				windows.iter().all(|window| window.contains(&number_value))
+					&& !placed.contains(value)
+			})
+			.collect()
+	}
+
+	/// Returns the candidates of the compartment's empty cells that are part of no straight the compartment can still be
+	/// filled with (its stranded candidates), together with their cells.
+	///
+	/// Stranded candidates can be ruled out, as placing them would leave the compartment without a straight.
+	pub(crate) fn stranded_candidates(
+		&self,
+		compartment: &[Position],
+	) -> Vec<(Position, CellValue)> {
+		let windows = self.possible_windows(compartment);
+		compartment
+			.iter()
+			.flat_map(|(row, col)| {
+				self.candidates(*row, *col)
+					.into_iter()
+					.map(move |value| ((*row, *col), value))
+			})
+			.filter(|(_, value)| {
+				let number_value: u8 = (*value).into();
+				!windows.iter().any(|window| window.contains(&number_value))
+			})
+			.collect()
+	}
+
+	/// Returns the smallest and largest value of any straight the given compartment can still be filled with, or None if
+	/// it cannot be completed.
+	pub(crate) fn tightest_bounds(&self, compartment: &[Position]) -> Option<RangeInclusive<u8>> {
+		let windows = self.possible_windows(compartment);
+		Some(*windows.first()?.start()..=*windows.last()?.end())
+	}
+
+	/// Returns the cells that must not hold the same value as the given cell: its row, its column, and the other cells of
+	/// its variant rules' groups. The cell itself is included.
+	fn peers(&self, row: u8, col: u8) -> impl Iterator<Item = Position> {
+		let groups = self
+			.rules
+			.distinct_groups()
+			.into_iter()
+			.filter(move |group| group.cells.contains(&(row, col)))
+			.flat_map(|group| group.cells);
+		(0..SIZE as u8)
+			.flat_map(move |i| [(row, i), (i, col)])
+			.chain(groups)
+	}
+
+	/// Checks whether the given value, together with the values already placed in the compartment,
+	/// still fits into a straight of the compartment's length.
+	fn fits_compartment(&self, compartment: &[Position], value: CellValue) -> bool {
+		let value: u8 = value.into();
+		let (min, max) = compartment
+			.iter()
+			.map(|(row, col)| u8::from(self.get_cell(*row, *col).value))
+			.filter(|v| *v != 0)
+			.fold((value, value), |(min, max), v| (min.min(v), max.max(v)));
+		usize::from(max - min) < compartment.len()
+	}
+}
+
+/// A compartment R1C1-R1C3 whose clues below leave R1C1 with 3 and 5, R1C2 with 1-3 and R1C3 with 1 and 2, so its only
+/// straight is 1-3 and the 5 of R1C1 is stranded.
+#[cfg(test)]
+pub(crate) fn stranded_board() -> Str8ts {
+	str8ts![
+		"...######",
+		"adc######",
+		"bed######",
+		"dfe######",
+		"fgh######",
+		"ghi######",
+		"hif######",
+		"i#g######",
+		"#########",
+	]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts_tutorial::rules_board;
+
+	#[test]
+	fn can_place_accepts_a_consistent_value() {
+		assert!(rules_board().can_place(0, 2, CellValue::Three));
+	}
+
+	#[test]
+	fn can_place_rejects_a_duplicate() {
+		let str8ts = rules_board();
+		assert!(!str8ts.can_place(0, 2, CellValue::One));
+		assert_eq!(
+			str8ts.place_check(2, 2, CellValue::Four),
+			Err(PlacementError::Duplicate {
+				value: CellValue::Four,
+				cell: (2, 2),
+				at: (2, 3),
+			})
+		);
+	}
+
+	#[test]
+	fn windows_contain_the_placed_values() {
+		let str8ts = rules_board();
+		let top_row = [(0, 0), (0, 1), (0, 2)];
+		assert_eq!(str8ts.possible_windows(&top_row), vec![1..=3]);
+		assert_eq!(str8ts.tightest_bounds(&top_row), Some(1..=3));
+	}
+
+	#[test]
+	fn windows_avoid_values_no_cell_can_hold() {
+		// The clue 4 next to the bottom row rules out every straight through 4.
+		let str8ts = rules_board();
+		let bottom_row = [(2, 0), (2, 1), (2, 2)];
+		assert_eq!(
+			str8ts.possible_windows(&bottom_row),
+			vec![1..=3, 5..=7, 6..=8, 7..=9]
+		);
+		assert_eq!(str8ts.tightest_bounds(&bottom_row), Some(1..=9));
+		assert_eq!(str8ts.tightest_bounds(&[]), None);
+	}
+
+	#[test]
+	fn full_length_compartment_has_a_single_window() {
+		let str8ts = Str8ts::new();
+		let row: Vec<Position> = (0..SIZE as u8).map(|col| (0, col)).collect();
+		assert_eq!(str8ts.possible_windows(&row), vec![1..=9]);
+		assert_eq!(str8ts.tightest_bounds(&row), Some(1..=9));
+	}
+
+	#[test]
+	fn compartment_without_a_straight_has_no_windows() {
+		// 1 and 5 are too far apart for a straight of three.
+		let str8ts = str8ts![
+			"1.5######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let row = [(0, 0), (0, 1), (0, 2)];
+		assert!(str8ts.possible_windows(&row).is_empty());
+		assert_eq!(str8ts.tightest_bounds(&row), None);
+	}
+
+	#[test]
+	fn place_check_explains_the_broken_rule() {
+		let mut str8ts = rules_board();
+		let error = str8ts.place_check(0, 2, CellValue::Nine).unwrap_err();
+		assert_eq!(
+			error,
+			PlacementError::OutOfRange {
+				value: CellValue::Nine,
+				orientation: Orientation::Row,
+			}
+		);
+		assert_eq!(
+			error.to_string(),
+			"9 does not form a straight with the other values of this row-compartment"
+		);
+		assert_eq!(
+			str8ts
+				.place_check(2, 2, CellValue::Four)
+				.unwrap_err()
+				.to_string(),
+			"4 already appears in this row"
+		);
+
+		str8ts.set_cell_constraint(2, 2, Some(ValueConstraint::AtMost(CellValue::Five)));
+		let error = str8ts.place_check(2, 2, CellValue::Six).unwrap_err();
+		assert_eq!(
+			error,
+			PlacementError::Constraint {
+				value: CellValue::Six,
+				constraint: ValueConstraint::AtMost(CellValue::Five),
+			}
+		);
+		assert_eq!(error.to_string(), "6 is larger than this cell's bound of 5");
+		assert_eq!(str8ts.place_check(2, 2, CellValue::Empty), Ok(()));
+	}
+
+	#[test]
+	fn line_summaries_split_the_values() {
+		let summaries = rules_board().line_summaries();
+		let top_row = &summaries.rows[0];
+		assert_eq!(top_row.placed, vec![CellValue::One, CellValue::Two]);
+		assert_eq!(top_row.missing, vec![CellValue::Three]);
+		assert_eq!(top_row.impossible.len(), 6);
+		// The black clue counts as placed.
+		assert_eq!(summaries.rows[2].placed, vec![CellValue::Four]);
+		let black_column = &summaries.cols[8];
+		assert!(black_column.placed.is_empty() && black_column.missing.is_empty());
+		assert_eq!(black_column.impossible.len(), SIZE);
+	}
+
+	#[test]
+	fn sure_candidates_are_in_every_window() {
+		// Every straight of six cells contains 4, 5 and 6.
+		let str8ts = Str8ts::new();
+		let compartment: Vec<Position> = (3..SIZE as u8).map(|col| (0, col)).collect();
+		assert_eq!(
+			str8ts.sure_candidates(&compartment),
+			vec![CellValue::Four, CellValue::Five, CellValue::Six]
+		);
+		assert!(str8ts.stranded_candidates(&compartment).is_empty());
+	}
+
+	#[test]
+	fn stranded_candidates_fit_no_window() {
+		let str8ts = stranded_board();
+		let compartment = [(0, 0), (0, 1), (0, 2)];
+		assert_eq!(str8ts.possible_windows(&compartment), vec![1..=3]);
+		assert_eq!(
+			str8ts.stranded_candidates(&compartment),
+			vec![((0, 0), CellValue::Five)]
+		);
+	}
+}
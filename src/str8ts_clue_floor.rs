@@ -0,0 +1,166 @@
+use std::time::{Duration, Instant};
+
+use crate::str8ts::{CellColor, CellValue, Str8ts};
+use crate::str8ts_solver::{SolveError, Uniqueness};
+
+/// The smallest set of clues `Str8ts::clue_floor` found that keeps the solution unique.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ClueFloorReport {
+	/// The clues, as cell indices with their values in the solution, in reading order.
+	pub(crate) clues: Vec<(u8, CellValue)>,
+	/// Whether the search finished within its budget, which proves that no smaller clue set keeps the solution unique.
+	/// Otherwise, the clues are merely the fewest found in time.
+	pub(crate) proven_minimal: bool,
+	/// The number of clue sets checked for uniqueness.
+	pub(crate) checks: usize,
+}
+
+/// The state of the branch-and-bound search of `Str8ts::clue_floor`.
+struct ClueSearch {
+	/// The board with all white cells empty, to which the clue sets are applied.
+	layout: Str8ts,
+	deadline: Instant,
+	best: Vec<(u8, CellValue)>,
+	checks: usize,
+	timed_out: bool,
+}
+
+impl ClueSearch {
+	/// Tries removing each clue from `start` on (removing earlier ones is covered by other branches), and recurses into
+	/// the clue sets that keep the solution unique.
+	fn search(&mut self, clues: &mut Vec<(u8, CellValue)>, start: usize) {
+		if clues.len() < self.best.len() {
+			self.best = clues.clone();
+		}
+		for position in start..clues.len() {
+			// Only the clues from here on can still be removed, which leaves at least `position` clues.
+			if position >= self.best.len() {
+				return;
+			}
+			if Instant::now() >= self.deadline {
+				self.timed_out = true;
+				return;
+			}
+			let removed = clues.remove(position);
+			self.checks += 1;
+			if matches!(
+				self.layout.apply_clue_set(clues).uniqueness(),
+				Uniqueness::Unique(_)
+			) {
+				self.search(clues, position);
+			}
+			clues.insert(position, removed);
+			if self.timed_out {
+				return;
+			}
+		}
+	}
+}
+
+impl Str8ts {
+	/// Searches for the smallest set of white clues that gives the layout of the str8ts game (its black cells and
+	/// their clues) a unique solution: its current solution.
+	///
+	/// The search starts from every white cell of the solution as a clue and removes clues as long as the solution stays
+	/// unique. Its first branch is the greedy removal in reading order, the remaining branches backtrack from there. Every
+	/// clue set is checked with `uniqueness`, which solves the str8ts game twice, so the search is limited to the given
+	/// budget. Use `apply_clue_set` to turn the reported clues into a puzzle.
+	pub(crate) fn clue_floor(&self, budget: Duration) -> Result<ClueFloorReport, SolveError> {
+		let solution = self.solve().ok_or(SolveError::Infeasible)?;
+		let mut clues: Vec<(u8, CellValue)> = solution
+			.white_cells()
+			.map(|((row, col), cell)| (Str8ts::index(row, col), cell.value))
+			.collect();
+		let mut search = ClueSearch {
+			layout: self.apply_clue_set(&[]),
+			deadline: Instant::now() + budget,
+			best: clues.clone(),
+			checks: 0,
+			timed_out: false,
+		};
+		search.search(&mut clues, 0);
+		Ok(ClueFloorReport {
+			clues: search.best,
+			proven_minimal: !search.timed_out,
+			checks: search.checks,
+		})
+	}
+
+	/// Returns the str8ts game with its white cells holding exactly the given clues, by cell index.
+	///
+	/// All other white cells are emptied. The clues are marked as given; clues for black cells are ignored.
+	pub(crate) fn apply_clue_set(&self, clues: &[(u8, CellValue)]) -> Str8ts {
+		let mut board = *self;
+		for ((row, col), _) in self.white_cells() {
+			board.set_cell_value(row, col, CellValue::Empty);
+			board.set_cell_given(row, col, false);
+		}
+		for (index, value) in clues {
+			let (row, col) = Str8ts::coords(*index);
+			if board.get_cell(row, col).color == CellColor::White {
+				board.set_cell_value(row, col, *value);
+				board.set_cell_given(row, col, *value != CellValue::Empty);
+			}
+		}
+		board
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A 2x2 block of white cells, which any two consecutive values fill crosswise.
+	fn corner_block() -> Str8ts {
+		str8ts![
+			"..#######",
+			"..#######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		]
+	}
+
+	#[test]
+	fn clue_floor_keeps_the_solution_unique() {
+		let str8ts = corner_block();
+		let report = str8ts.clue_floor(Duration::from_secs(10)).unwrap();
+		assert!(report.proven_minimal);
+		assert!(report.clues.len() < 4);
+		let puzzle = str8ts.apply_clue_set(&report.clues);
+		assert!(matches!(puzzle.uniqueness(), Uniqueness::Unique(_)));
+	}
+
+	#[test]
+	fn clue_floor_without_budget_keeps_all_clues() {
+		let report = corner_block().clue_floor(Duration::ZERO).unwrap();
+		assert!(!report.proven_minimal);
+		assert_eq!(report.clues.len(), 4);
+	}
+
+	#[test]
+	fn apply_clue_set_replaces_the_white_values() {
+		let str8ts = str8ts![
+			"12#######",
+			"..#######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let puzzle = str8ts.apply_clue_set(&[(9, CellValue::Two), (2, CellValue::Five)]);
+		assert_eq!(puzzle.get_cell(0, 0).value, CellValue::Empty);
+		assert_eq!(puzzle.get_cell(0, 1).value, CellValue::Empty);
+		assert!(puzzle.get_cell(1, 0).given);
+		assert_eq!(puzzle.get_cell(1, 0).value, CellValue::Two);
+		// Black cells keep their (empty) clue.
+		assert_eq!(puzzle.get_cell(0, 2).value, CellValue::Empty);
+	}
+}
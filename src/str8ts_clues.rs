@@ -0,0 +1,84 @@
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use crate::str8ts::{CellColor, LabelScheme, Position, Str8ts};
+
+/// How long `--clues` searches for the fewest clues the layout needs.
+const CLUE_FLOOR_BUDGET: Duration = Duration::from_secs(10);
+
+/// Describes the clues of the puzzle for `--clues`: which of its white givens can each be removed on its own, the
+/// puzzle left after removing them one after another, keeping the pinned ones, and the fewest clues its layout needs.
+///
+/// Removing a given never removes solutions, so no clue of a puzzle without a unique solution is redundant.
+pub(crate) fn clue_report(str8ts: &Str8ts, pinned: &[Position]) -> String {
+	let mut report = redundant_clue_report(str8ts, pinned);
+	match str8ts.clue_floor(CLUE_FLOOR_BUDGET) {
+		Ok(floor) => {
+			let _ = write!(
+				report,
+				"The fewest clues {} for this solution: {}\n{}",
+				if floor.proven_minimal {
+					"possible"
+				} else {
+					"found in time"
+				},
+				floor.clues.len(),
+				str8ts.apply_clue_set(&floor.clues).to_terminal()
+			);
+		}
+		Err(error) => {
+			let _ = writeln!(report, "No clue set can be searched for: {}", error);
+		}
+	}
+	report
+}
+
+/// Describes which white givens can each be removed on its own, and the puzzle left after removing them one after
+/// another, keeping the pinned ones.
+fn redundant_clue_report(str8ts: &Str8ts, pinned: &[Position]) -> String {
+	let labels = LabelScheme::default();
+	let mut report = String::new();
+	let redundant: Vec<String> = str8ts
+		.redundant_clues()
+		.into_iter()
+		.map(|index| {
+			let (row, col) = Str8ts::coords(index);
+			labels.cell_reference(row, col)
+		})
+		.collect();
+	if redundant.is_empty() {
+		report.push_str("No clue can be removed on its own.\n");
+		return report;
+	}
+	let _ = writeln!(
+		report,
+		"Each of these clues can be removed on its own: {}",
+		redundant.join(", ")
+	);
+
+	let pinned_indices: Vec<u8> = pinned
+		.iter()
+		.map(|(row, col)| Str8ts::index(*row, *col))
+		.collect();
+	let minimized = str8ts.minimize_clues(&pinned_indices);
+	let clues = minimized
+		.cells()
+		.filter(|(_, cell)| cell.color == CellColor::White && cell.given)
+		.count();
+	let kept: Vec<String> = pinned
+		.iter()
+		.map(|(row, col)| labels.cell_reference(*row, *col))
+		.collect();
+	let _ = write!(
+		report,
+		"Removing them one after another{} leaves {} clues:\n{}",
+		if kept.is_empty() {
+			String::new()
+		} else {
+			format!(", keeping {},", kept.join(", "))
+		},
+		clues,
+		minimized.to_terminal()
+	);
+	report
+}
@@ -0,0 +1,153 @@
+use crate::str8ts::{LabelScheme, Position};
+use crate::str8ts_logic::{LogicalStep, Technique};
+
+/// Explains a step of the logical solver in a full sentence, for players learning the techniques.
+///
+/// The sentences of all techniques are kept here, so every place showing them (like the tooltips of the difficulty
+/// overlay) words them the same.
+pub(crate) fn explain(step: &LogicalStep) -> String {
+	let labels = LabelScheme::default();
+	let cell = labels.cell_reference(step.row, step.col);
+	let compartment = step
+		.compartment
+		.map(|(first, last)| describe_compartment(&labels, first, last))
+		.unwrap_or_else(|| String::from("its compartment"));
+	match step.technique {
+		Technique::StraightCompletion => format!(
+			"{} must form a straight and has a single gap left, which only {} fills, so {} must be {}.",
+			capitalize(&compartment),
+			step.value,
+			cell,
+			step.value
+		),
+		Technique::NakedSingle => format!(
+			"Every value but {} already appears in the row or column of {}, or breaks the straight of one of its \
+			 compartments, so {} must be {}.",
+			step.value, cell, cell, step.value
+		),
+		Technique::HiddenSingle => format!(
+			"Every straight {} can still form contains {}, and {} is the only cell of it where {} fits, so {} must be {}.",
+			compartment, step.value, cell, step.value, cell, step.value
+		),
+		Technique::StrandedElimination => format!(
+			"Ruling out the values that fit no straight their compartment can still form leaves only {} for {}, so {} \
+			 must be {}.",
+			step.value, cell, cell, step.value
+		),
+		Technique::SureCandidateElimination => format!(
+			"Ruling out the values that fit no straight of their compartment, and the values a compartment surely \
+			 contains from the rest of its row or column, leaves only {} for {}, so {} must be {}.",
+			step.value, cell, cell, step.value
+		),
+	}
+}
+
+/// Describes a compartment by its first and last cell, e.g. "the compartment in row 4 spanning columns 2-5".
+fn describe_compartment(labels: &LabelScheme, first: Position, last: Position) -> String {
+	let ((first_row, first_col), (last_row, last_col)) = (first, last);
+	if first == last {
+		format!(
+			"the compartment of the single cell {}",
+			labels.cell_reference(first_row, first_col)
+		)
+	} else if first_row == last_row {
+		format!(
+			"the compartment in row {} spanning columns {}-{}",
+			labels.row_label(first_row),
+			labels.col_label(first_col),
+			labels.col_label(last_col)
+		)
+	} else {
+		format!(
+			"the compartment in column {} spanning rows {}-{}",
+			labels.col_label(first_col),
+			labels.row_label(first_row),
+			labels.row_label(last_row)
+		)
+	}
+}
+
+/// Returns the text with its first letter in upper case, to start a sentence with it.
+fn capitalize(text: &str) -> String {
+	let mut chars = text.chars();
+	match chars.next() {
+		Some(first) => first.to_uppercase().chain(chars).collect(),
+		None => String::new(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts::CellValue;
+
+	/// A step filling R4C3 with 5.
+	fn step(technique: Technique, compartment: Option<(Position, Position)>) -> LogicalStep {
+		LogicalStep {
+			technique,
+			row: 3,
+			col: 2,
+			value: CellValue::Five,
+			compartment,
+		}
+	}
+
+	#[test]
+	fn straight_completion_names_its_row_compartment() {
+		assert_eq!(
+			explain(&step(Technique::StraightCompletion, Some(((3, 1), (3, 4))))),
+			"The compartment in row 4 spanning columns 2-5 must form a straight and has a single gap left, which only 5 \
+			 fills, so R4C3 must be 5."
+		);
+	}
+
+	#[test]
+	fn straight_completion_names_a_single_cell_compartment() {
+		assert_eq!(
+			explain(&step(Technique::StraightCompletion, Some(((3, 2), (3, 2))))),
+			"The compartment of the single cell R4C3 must form a straight and has a single gap left, which only 5 fills, \
+			 so R4C3 must be 5."
+		);
+	}
+
+	#[test]
+	fn naked_single_is_explained() {
+		assert_eq!(
+			explain(&step(Technique::NakedSingle, None)),
+			"Every value but 5 already appears in the row or column of R4C3, or breaks the straight of one of its \
+			 compartments, so R4C3 must be 5."
+		);
+	}
+
+	#[test]
+	fn hidden_single_names_its_column_compartment() {
+		assert_eq!(
+			explain(&step(Technique::HiddenSingle, Some(((1, 2), (5, 2))))),
+			"Every straight the compartment in column 3 spanning rows 2-6 can still form contains 5, and R4C3 is the only \
+			 cell of it where 5 fits, so R4C3 must be 5."
+		);
+		assert_eq!(
+			explain(&step(Technique::HiddenSingle, None)),
+			"Every straight its compartment can still form contains 5, and R4C3 is the only cell of it where 5 fits, so \
+			 R4C3 must be 5."
+		);
+	}
+
+	#[test]
+	fn stranded_elimination_is_explained() {
+		assert_eq!(
+			explain(&step(Technique::StrandedElimination, None)),
+			"Ruling out the values that fit no straight their compartment can still form leaves only 5 for R4C3, so R4C3 \
+			 must be 5."
+		);
+	}
+
+	#[test]
+	fn sure_candidate_elimination_is_explained() {
+		assert_eq!(
+			explain(&step(Technique::SureCandidateElimination, None)),
+			"Ruling out the values that fit no straight of their compartment, and the values a compartment surely \
+			 contains from the rest of its row or column, leaves only 5 for R4C3, so R4C3 must be 5."
+		);
+	}
+}
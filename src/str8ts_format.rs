@@ -0,0 +1,577 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::str8ts::{
+	Cell, CellColor, CellValue, Orientation, Position, Str8ts, ValueConstraint, CELLS, SIZE,
+};
+use crate::str8ts_rules::RuleSet;
+
+/// A position in a text, with lines and columns (in characters) counted from 1 as in text editors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TextPosition {
+	pub(crate) line: usize,
+	pub(crate) column: usize,
+}
+
+impl Display for TextPosition {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "line {}, column {}", self.line, self.column)
+	}
+}
+
+/// The reason a str8ts game could not be read from text.
+///
+/// Each error but `RowCount` knows the line of the text it occurred on, see `position`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ParseError {
+	/// The text does not contain exactly 9 rows.
+	RowCount(usize),
+	/// The board is given on a single line, but that line does not contain exactly 81 cells.
+	WrongLength {
+		expected: usize,
+		got: usize,
+		line: usize,
+	},
+	/// The row does not contain exactly 9 cells.
+	RowLength {
+		row: usize,
+		length: usize,
+		line: usize,
+	},
+	/// The character does not describe a cell.
+	InvalidCell {
+		row: usize,
+		col: usize,
+		c: char,
+		at: TextPosition,
+	},
+	/// The line looks like a value constraint, but is not of the form `R4C7<=5` or `R4C7>=5`.
+	InvalidConstraint { constraint: String, line: usize },
+}
+
+impl ParseError {
+	/// Returns where in the text the error occurred: the offending character, or the start of the offending line.
+	///
+	/// Returns None if the error concerns the text as a whole.
+	pub(crate) fn position(&self) -> Option<TextPosition> {
+		match self {
+			ParseError::RowCount(_) => None,
+			ParseError::WrongLength { line, .. }
+			| ParseError::RowLength { line, .. }
+			| ParseError::InvalidConstraint { line, .. } => Some(TextPosition {
+				line: *line,
+				column: 1,
+			}),
+			ParseError::InvalidCell { at, .. } => Some(*at),
+		}
+	}
+
+	/// Moves an error of `set_row_from_str`, whose position is relative to the row, to the row's start in the text.
+	fn relocated(self, start: TextPosition) -> ParseError {
+		match self {
+			ParseError::RowLength { row, length, .. } => ParseError::RowLength {
+				row,
+				length,
+				line: start.line,
+			},
+			ParseError::InvalidCell { row, col, c, at } => ParseError::InvalidCell {
+				row,
+				col,
+				c,
+				at: TextPosition {
+					line: start.line,
+					column: start.column + at.column - 1,
+				},
+			},
+			error => error,
+		}
+	}
+}
+
+impl Display for ParseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ParseError::RowCount(count) => write!(f, "expected 9 rows, found {}", count),
+			ParseError::WrongLength {
+				expected,
+				got,
+				line,
+			} => {
+				write!(
+					f,
+					"expected {} cells, found {} on line {}",
+					expected, got, line
+				)
+			}
+			ParseError::RowLength { row, length, line } => {
+				write!(
+					f,
+					"expected 9 cells in row {}, found {} on line {}",
+					row + 1,
+					length,
+					line
+				)
+			}
+			ParseError::InvalidCell { row, col, c, at } => {
+				write!(
+					f,
+					"invalid cell '{}' at row {}, column {} ({})",
+					c,
+					row + 1,
+					col + 1,
+					at
+				)
+			}
+			ParseError::InvalidConstraint { constraint, line } => {
+				write!(f, "invalid constraint '{}' on line {}", constraint, line)
+			}
+		}
+	}
+}
+
+impl std::error::Error for ParseError {}
+
+/// The ways the cells of a str8ts game can be written in the `.str8ts` text format, see `detect_format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TextFormat {
+	/// One line of 9 cells per row.
+	Grid,
+	/// All 81 cells on a single line, in reading order.
+	SingleLine,
+}
+
+impl Display for TextFormat {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			TextFormat::Grid => write!(f, "9-line grid"),
+			TextFormat::SingleLine => write!(f, "single-line board"),
+		}
+	}
+}
+
+/// Detects in which format the cells of a str8ts game are written, as `from_str` reads them, or None if the number of
+/// lines of cells fits neither format.
+///
+/// Only the number of lines of cells is considered, so this does not check that the text can actually be read.
+pub(crate) fn detect_format(text: &str) -> Option<TextFormat> {
+	match split_lines(text).1.len() {
+		1 => Some(TextFormat::SingleLine),
+		SIZE => Some(TextFormat::Grid),
+		_ => None,
+	}
+}
+
+/// A trimmed line of text, together with the position it starts at.
+type Line<'a> = (TextPosition, &'a str);
+
+/// Splits the non-blank lines of the `.str8ts` text format into the lines of constraints, rules and sum clues, and the
+/// lines of cells.
+///
+/// The lines are trimmed, and each is paired with the position its trimmed text starts at.
+fn split_lines(text: &str) -> (Vec<Line<'_>>, Vec<Line<'_>>) {
+	text.lines()
+		.enumerate()
+		.map(|(index, line)| {
+			let trimmed = line.trim();
+			let start = TextPosition {
+				line: index + 1,
+				column: line.chars().take_while(|c| c.is_whitespace()).count() + 1,
+			};
+			(start, trimmed)
+		})
+		.filter(|(_, line)| !line.is_empty())
+		.partition(|(_, line)| {
+			line.contains(['<', '>']) || line.starts_with("sum ") || RuleSet::default().enable(line)
+		})
+}
+
+/// Reads a str8ts game from the `.str8ts` text format.
+///
+/// The format has one line per row and one character per cell: `.` for an empty white cell, `1`-`9` for a white cell
+/// with that value, `#` for an empty black cell, and `a`-`i` for a black cell with the value 1-9. Blank lines are
+/// ignored. Values of white cells are read as givens. The rows may also be written on a single line of 81 cells, as
+/// puzzles are often shared.
+///
+/// Optionally, value constraints follow the rows, one per line, such as `R4C7<=5` (the cell in row 4 and column 7 holds
+/// at most 5) or `R4C7>=5` (at least 5), as well as the keywords of variant rules (see `RuleSet::keywords`), such as
+/// `diagonals` for X-Str8ts, and sum clues of compartments, such as `sum R4C2 row 12` (the row-compartment starting
+/// at row 4 and column 2 adds up to 12) or `sum R4C2 column 7`.
+impl FromStr for Str8ts {
+	type Err = ParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (constraints, rows) = split_lines(s);
+		let mut str8ts = Str8ts::new();
+		if let [(start, line)] = rows[..] {
+			let cells: Vec<char> = line.chars().collect();
+			if cells.len() != CELLS {
+				return Err(ParseError::WrongLength {
+					expected: CELLS,
+					got: cells.len(),
+					line: start.line,
+				});
+			}
+			for (row, cells) in cells.chunks(SIZE).enumerate() {
+				let row_start = TextPosition {
+					column: start.column + row * SIZE,
+					..start
+				};
+				str8ts
+					.set_row_from_str(row as u8, &cells.iter().collect::<String>())
+					.map_err(|error| error.relocated(row_start))?;
+			}
+		} else if rows.len() != SIZE {
+			return Err(ParseError::RowCount(rows.len()));
+		} else {
+			for (row, (start, line)) in rows.iter().enumerate() {
+				str8ts
+					.set_row_from_str(row as u8, line)
+					.map_err(|error| error.relocated(*start))?;
+			}
+		}
+		for (start, line) in constraints {
+			let invalid = || ParseError::InvalidConstraint {
+				constraint: line.to_string(),
+				line: start.line,
+			};
+			if str8ts.rules.enable(line) {
+				continue;
+			}
+			if line.starts_with("sum ") {
+				let (first, orientation, sum) = parse_sum(line).ok_or_else(invalid)?;
+				str8ts.set_compartment_sum(first, orientation, Some(sum));
+				continue;
+			}
+			let (row, col, constraint) = parse_constraint(line).ok_or_else(invalid)?;
+			str8ts.set_cell_constraint(row, col, Some(constraint));
+		}
+		Ok(str8ts)
+	}
+}
+
+/// Parses a value constraint line like `R4C7<=5` into the (zero-based) cell and its constraint.
+fn parse_constraint(line: &str) -> Option<(u8, u8, ValueConstraint)> {
+	let (cell, bound, is_at_most) = match line.split_once("<=") {
+		Some((cell, bound)) => (cell, bound, true),
+		None => {
+			let (cell, bound) = line.split_once(">=")?;
+			(cell, bound, false)
+		}
+	};
+	let (row, col) = parse_cell_reference(cell)?;
+	let bound: u8 = bound.trim().parse().ok()?;
+	if !(1..=9).contains(&bound) {
+		return None;
+	}
+	let bound = CellValue::from(bound);
+	let constraint = if is_at_most {
+		ValueConstraint::AtMost(bound)
+	} else {
+		ValueConstraint::AtLeast(bound)
+	};
+	Some((row, col, constraint))
+}
+
+/// Parses a sum clue line like `sum R4C2 row 12` into the compartment's (zero-based) first cell, orientation and sum.
+fn parse_sum(line: &str) -> Option<(Position, Orientation, u8)> {
+	let mut parts = line.split_whitespace().skip(1);
+	let first = parse_cell_reference(parts.next()?)?;
+	let orientation = match parts.next()? {
+		"row" => Orientation::Row,
+		"column" => Orientation::Column,
+		_ => return None,
+	};
+	let sum: u8 = parts.next()?.parse().ok()?;
+	if parts.next().is_some() || !(1..=45).contains(&sum) {
+		return None;
+	}
+	Some((first, orientation, sum))
+}
+
+/// Parses a cell reference like `R4C7` into the (zero-based) cell.
+pub(crate) fn parse_cell_reference(reference: &str) -> Option<Position> {
+	let (row, col) = reference.trim().strip_prefix('R')?.split_once('C')?;
+	let row: u8 = row.parse().ok()?;
+	let col: u8 = col.parse().ok()?;
+	if !(1..=9).contains(&row) || !(1..=9).contains(&col) {
+		return None;
+	}
+	Some((row - 1, col - 1))
+}
+
+impl Str8ts {
+	/// Sets the cells of the given row from one line of the `.str8ts` text format.
+	///
+	/// The line must consist of exactly 9 cell characters: `.` for an empty white cell, `1`-`9` for a white cell with
+	/// that (given) value, `#` for an empty black cell, and `a`-`i` for a black cell with the value 1-9.
+	/// On error, the row is left unchanged. Positions in the error count the row as line `row + 1` of a grid.
+	pub(crate) fn set_row_from_str(&mut self, row: u8, line: &str) -> Result<(), ParseError> {
+		let length = line.chars().count();
+		if length != SIZE {
+			return Err(ParseError::RowLength {
+				row: row as usize,
+				length,
+				line: row as usize + 1,
+			});
+		}
+		let mut cells = [Cell::default(); SIZE];
+		for (col, c) in line.chars().enumerate() {
+			cells[col] = match c {
+				'.' => Cell::new(CellColor::White, CellValue::Empty),
+				'1'..='9' => Cell {
+					given: true,
+					..Cell::new(CellColor::White, CellValue::from(c))
+				},
+				'#' => Cell::new(CellColor::Black, CellValue::Empty),
+				'a'..='i' => Cell::new(CellColor::Black, CellValue::from(c as u8 - b'a' + 1)),
+				_ => {
+					return Err(ParseError::InvalidCell {
+						row: row as usize,
+						col,
+						c,
+						at: TextPosition {
+							line: row as usize + 1,
+							column: col + 1,
+						},
+					})
+				}
+			};
+		}
+		for (col, cell) in cells.into_iter().enumerate() {
+			self.set_cell(row, col as u8, cell);
+		}
+		Ok(())
+	}
+
+	/// Writes the str8ts game in the `.str8ts` text format, which `from_str` reads back.
+	pub(crate) fn file_contents(&self) -> String {
+		let mut result = String::new();
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				let cell = self.get_cell(row, col);
+				result.push(match (cell.color, cell.value) {
+					(CellColor::White, CellValue::Empty) => '.',
+					(CellColor::White, value) => value.into(),
+					(CellColor::Black, CellValue::Empty) => '#',
+					(CellColor::Black, value) => (b'a' + u8::from(value) - 1) as char,
+				});
+			}
+			result.push('\n');
+		}
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				let (operator, bound) = match self.get_cell(row, col).constraint {
+					Some(ValueConstraint::AtMost(bound)) => ("<=", bound),
+					Some(ValueConstraint::AtLeast(bound)) => (">=", bound),
+					None => continue,
+				};
+				result.push_str(&format!("R{}C{}{}{}\n", row + 1, col + 1, operator, bound));
+			}
+		}
+		for keyword in self.rules.keywords() {
+			result.push_str(keyword);
+			result.push('\n');
+		}
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				for (orientation, name) in
+					[(Orientation::Row, "row"), (Orientation::Column, "column")]
+				{
+					if let Some(sum) = self.compartment_sum((row, col), orientation) {
+						result.push_str(&format!(
+							"sum R{}C{} {} {}\n",
+							row + 1,
+							col + 1,
+							name,
+							sum
+						));
+					}
+				}
+			}
+		}
+		result
+	}
+
+	/// Writes only the layout of the str8ts game, i.e. the colors of its cells, ignoring all values and clues.
+	///
+	/// The layout is a single line of 81 cells in reading order, `.` for a white and `#` for a black cell, which
+	/// `from_layout_string` reads back.
+	pub(crate) fn layout_string(&self) -> String {
+		self.into_iter()
+			.map(|cell| match cell.color {
+				CellColor::White => '.',
+				CellColor::Black => '#',
+			})
+			.collect()
+	}
+
+	/// Creates an empty str8ts game with the given layout, as written by `layout_string`.
+	pub(crate) fn from_layout_string(layout: &str) -> Result<Str8ts, ParseError> {
+		let trimmed = layout.trim_start();
+		let indent = &layout[..layout.len() - trimmed.len()];
+		let start = TextPosition {
+			line: indent.matches('\n').count() + 1,
+			column: indent
+				.rsplit('\n')
+				.next()
+				.unwrap_or_default()
+				.chars()
+				.count() + 1,
+		};
+		let cells: Vec<char> = trimmed.trim_end().chars().collect();
+		if cells.len() != CELLS {
+			return Err(ParseError::WrongLength {
+				expected: CELLS,
+				got: cells.len(),
+				line: start.line,
+			});
+		}
+		let mut str8ts = Str8ts::new();
+		for (index, c) in cells.into_iter().enumerate() {
+			let (row, col) = Str8ts::coords(index as u8);
+			let color = match c {
+				'.' => CellColor::White,
+				'#' => CellColor::Black,
+				_ => {
+					return Err(ParseError::InvalidCell {
+						row: row as usize,
+						col: col as usize,
+						c,
+						at: TextPosition {
+							column: start.column + index,
+							..start
+						},
+					})
+				}
+			};
+			str8ts.set_cell_color(row, col, color);
+		}
+		Ok(str8ts)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn too_short_board_is_rejected() {
+		assert_eq!(
+			".".repeat(CELLS - 1).parse::<Str8ts>(),
+			Err(ParseError::WrongLength {
+				expected: CELLS,
+				got: CELLS - 1,
+				line: 1,
+			})
+		);
+		assert_eq!(
+			"........#\n".repeat(SIZE - 1).parse::<Str8ts>(),
+			Err(ParseError::RowCount(SIZE - 1))
+		);
+	}
+
+	#[test]
+	fn too_long_board_is_rejected() {
+		assert_eq!(
+			".".repeat(CELLS + 1).parse::<Str8ts>(),
+			Err(ParseError::WrongLength {
+				expected: CELLS,
+				got: CELLS + 1,
+				line: 1,
+			})
+		);
+		assert_eq!(
+			"........#.\n".repeat(SIZE).parse::<Str8ts>(),
+			Err(ParseError::RowLength {
+				row: 0,
+				length: 10,
+				line: 1,
+			})
+		);
+	}
+
+	#[test]
+	fn layout_string_round_trips_the_colors_only() {
+		let str8ts = str8ts![
+			"#.a..#...",
+			"..#6..#.3",
+			".........",
+			"####.####",
+			"i........",
+			".........",
+			"5.......#",
+			".........",
+			"........#",
+		];
+		let layout = str8ts.layout_string();
+		assert_eq!(layout.len(), CELLS);
+		assert!(layout.starts_with("#.#..#.....#...#.."));
+		let blank = Str8ts::from_layout_string(&layout).unwrap();
+		for ((position, cell), (_, blank_cell)) in str8ts.cells().zip(blank.cells()) {
+			assert_eq!(blank_cell.color, cell.color, "color of {:?}", position);
+			assert_eq!(blank_cell.value, CellValue::Empty);
+		}
+		assert_eq!(blank.layout_string(), layout);
+	}
+
+	#[test]
+	fn errors_point_into_the_text() {
+		// A blank line and an indented row, with an invalid cell in the fourth row.
+		let mut rows = ["........#"; SIZE];
+		rows[1] = "  ........#";
+		rows[3] = "...x....#";
+		let text = format!("\n{}", rows.join("\n"));
+		let error = text.parse::<Str8ts>().unwrap_err();
+		assert_eq!(
+			error,
+			ParseError::InvalidCell {
+				row: 3,
+				col: 3,
+				c: 'x',
+				at: TextPosition { line: 5, column: 4 },
+			}
+		);
+		assert_eq!(
+			error.to_string(),
+			"invalid cell 'x' at row 4, column 4 (line 5, column 4)"
+		);
+
+		let mut single_line = ".".repeat(CELLS);
+		single_line.replace_range(SIZE + 2..SIZE + 3, "x");
+		assert_eq!(
+			format!(" {}", single_line)
+				.parse::<Str8ts>()
+				.unwrap_err()
+				.position(),
+			Some(TextPosition {
+				line: 1,
+				column: SIZE + 4,
+			})
+		);
+
+		let text = format!("{}\nR1C1<=0", ".".repeat(CELLS));
+		assert_eq!(
+			text.parse::<Str8ts>().unwrap_err(),
+			ParseError::InvalidConstraint {
+				constraint: "R1C1<=0".to_string(),
+				line: 2,
+			}
+		);
+	}
+
+	#[test]
+	fn format_is_detected_by_the_number_of_rows() {
+		let grid = "........#\n".repeat(SIZE);
+		assert_eq!(detect_format(&grid), Some(TextFormat::Grid));
+		assert_eq!(
+			detect_format(&format!("{}diagonals\n", grid)),
+			Some(TextFormat::Grid)
+		);
+		assert_eq!(
+			detect_format(&".".repeat(CELLS)),
+			Some(TextFormat::SingleLine)
+		);
+		assert_eq!(detect_format(""), None);
+		assert_eq!(detect_format(&"........#\n".repeat(2)), None);
+	}
+}
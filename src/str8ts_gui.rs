@@ -1,17 +1,206 @@
-use iced::widget::{Button, Column, Container, Row, Text, TextInput};
-use iced::{theme, Background, BorderRadius, Color, Element, Length, Sandbox, Settings};
-use iced_style::{text_input, Theme};
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::Display;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::str8ts::{CellColor, CellValue, Str8ts};
+use iced::futures::channel::oneshot;
+use iced::keyboard::{self, KeyCode};
+use iced::widget::{
+	focus_next, focus_previous, text_input, tooltip, Button, Column, Container, PickList, Row,
+	Text, TextInput, Tooltip,
+};
+use iced::{
+	alignment, clipboard, event, executor, subscription, theme, Application, Background,
+	BorderRadius, Color, Command, Element, Event, Font, Length, Settings, Subscription,
+};
+use iced_style::{container, Theme};
+
+use crate::str8ts::{
+	CellColor, CellValue, LabelScheme, Orientation, Position, Str8ts, CELLS, SIZE,
+};
+use crate::str8ts_analysis::{analyze, AnalyzedBoard, BoardAnalysis};
+use crate::str8ts_cache::SolutionCache;
+use crate::str8ts_candidates::PlacementError;
+use crate::str8ts_explain::explain;
+use crate::str8ts_format::{detect_format, ParseError};
+use crate::str8ts_logic::LogicalSolution;
+use crate::str8ts_moves::{diff_move, CellContent, Move, MoveHistory};
+use crate::str8ts_replay::Replay;
+use crate::str8ts_rules::Diagonal;
+use crate::str8ts_snapshots::Snapshots;
+use crate::str8ts_solver::{FillSelection, SolvedBoard, SplitMix64, Uniqueness};
+use crate::str8ts_tutorial::{Tutorial, TutorialAction};
+use crate::str8ts_validation::{Conflict, Violation};
+
+/// Width of a cell's text input at a zoom level of 1.
+const CELL_WIDTH: f32 = 35.0;
+/// Width of a cell's color toggle button at a zoom level of 1.
+const TOGGLE_WIDTH: f32 = 15.0;
+/// Spacing between the widgets of the board.
+const SPACING: f32 = 10.0;
+/// Text size of the row and column labels at a zoom level of 1.
+const LABEL_SIZE: f32 = 16.0;
+/// Text size of the compartment sum clues at a zoom level of 1.
+const SUM_LABEL_SIZE: f32 = 10.0;
+/// Below this zoom level, the row and column labels are hidden.
+const LABEL_MIN_ZOOM: f32 = 0.75;
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 2.0;
+const ZOOM_STEP: f32 = 0.25;
+/// Width and height of a cell of the solution view at a zoom level of 1.
+const SOLUTION_CELL_SIZE: f32 = 25.0;
+/// How long the board has to stay unchanged before it is solved in the background.
+const AUTO_SOLVE_DELAY: Duration = Duration::from_millis(500);
+/// How long each solved value is shown before the next one is revealed.
+const REVEAL_DELAY: Duration = Duration::from_millis(40);
+/// How long each move of a playback is shown before the next one is played.
+const PLAYBACK_DELAY: Duration = Duration::from_millis(500);
+/// Border color of cells involved in a conflict.
+const CONFLICT_COLOR: Color = Color::from_rgb(0.9, 0.1, 0.1);
+/// How long the full feasibility probe may run SCIP before giving up.
+const FEASIBILITY_TIME_LIMIT: Duration = Duration::from_secs(2);
+/// The file `Save Replay` writes to, in the working directory.
+const REPLAY_FILE: &str = "replay.txt";
+/// Colors of the values entered in the first, second, ... nested trial, repeating for deeper trials.
+const TRIAL_COLORS: [Color; 3] = [
+	Color::from_rgb(0.1, 0.3, 0.9),
+	Color::from_rgb(0.1, 0.6, 0.2),
+	Color::from_rgb(0.6, 0.2, 0.7),
+];
 
 pub(crate) fn run() -> iced::Result {
 	Str8tsEditor::run(Settings::default())
 }
 
+/// Whether the editor is used to author a puzzle or to solve it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Mode {
+	/// Colors can be toggled, and entered values become givens.
+	#[default]
+	Edit,
+	/// Colors and givens are locked, and entered values are the player's answers.
+	Solve,
+}
+
+impl Display for Mode {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Mode::Edit => write!(f, "Edit"),
+			Mode::Solve => write!(f, "Solve"),
+		}
+	}
+}
+
+/// How hard the board is probed for having become unsolvable after each edit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum FeasibilityProbe {
+	#[default]
+	Off,
+	/// Only the logical solver is run, which catches the obvious cases cheaply.
+	Propagation,
+	/// If the logical solver finds nothing, SCIP is run as well, up to `FEASIBILITY_TIME_LIMIT`.
+	Full,
+}
+
+impl Display for FeasibilityProbe {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			FeasibilityProbe::Off => write!(f, "Off"),
+			FeasibilityProbe::Propagation => write!(f, "Propagation"),
+			FeasibilityProbe::Full => write!(f, "Full"),
+		}
+	}
+}
+
+/// A playback of the moves made on the board, shown below it.
+struct Playback {
+	replay: Replay,
+	/// The board before the first move and after each move.
+	boards: Vec<Str8ts>,
+	/// The number of moves played so far.
+	played: usize,
+	/// Whether the next move is played automatically after `PLAYBACK_DELAY`.
+	is_playing: bool,
+}
+
 struct Str8tsEditor {
 	str8ts: Str8ts,
+	mode: Mode,
+	/// The cell that was interacted with last.
+	selected: Option<(u8, u8)>,
+	show_labels: bool,
+	label_scheme: LabelScheme,
+	zoom: f32,
+	/// Whether empty white cells are shaded by their number of candidates.
+	show_heatmap: bool,
+	/// Whether empty white cells are shaded by the step of the logical solver that determined them.
+	show_difficulty_overlay: bool,
+	/// The logical solution the difficulty overlay is based on, if it has been computed for the current board.
+	logical_solution: Option<LogicalSolution>,
+	/// Whether the board is solved in the background after each edit, and its solution shown next to it.
+	auto_solve: bool,
+	/// Incremented on every change of the board, to detect stale background results.
+	revision: u64,
+	/// The result of the last background solve, together with the revision of the board it was computed for.
+	solution_view: Option<(u64, Uniqueness)>,
+	/// Whether the board has a solution, together with the revision of the board it was checked for.
+	solvable: Option<(u64, bool)>,
+	/// The cached solution used for checking, invalidated whenever the structure of the board changes.
+	solved: Option<SolvedBoard>,
+	/// The results of solving the puzzles of this session, consulted before solving again.
+	cache: SolutionCache,
+	/// The filled cells found to be incorrect by the last check.
+	incorrect_cells: Vec<Position>,
+	/// The outcome of the last check, until the board changes.
+	check_message: Option<String>,
+	/// How the last solve went: whether it found a solution, and the statistics of the solver or the cache. Shown until
+	/// the board changes.
+	solve_statistics: Option<String>,
+	/// The cell a value breaking the rules was just entered into, and why it breaks them, until the board changes.
+	placement_error: Option<(Position, PlacementError)>,
+	/// The latest candidates and conflicts computed in the background, together with the revision of the board they
+	/// were computed for. Briefly lags behind the board after an edit.
+	analysis: Option<(u64, BoardAnalysis)>,
+	/// The board together with its compartments, brought up to date whenever the board is analyzed.
+	analyzed: AnalyzedBoard,
+	/// The changes of values and colors made to the board, so they can be undone.
+	history: MoveHistory,
+	/// Generates the random boards, seeded once per session.
+	rng: SplitMix64,
+	/// The named checkpoints of the board.
+	snapshots: Snapshots,
+	/// The name the next checkpoint is saved under.
+	snapshot_name: String,
+	/// The checkpoint chosen in the dropdown, to be restored or deleted.
+	selected_snapshot: Option<String>,
+	/// The board as it was when each running trial began, outermost trial first.
+	///
+	/// Values entered during a trial are shown in the trial's color, until it is committed into the enclosing trial (or
+	/// the board) or discarded.
+	trials: Vec<Str8ts>,
+	/// Whether solved values are revealed one by one instead of all at once.
+	animate_solve: bool,
+	/// Whether the row and column of the selected cell are shaded.
+	highlight_lines: bool,
+	/// Whether the values still missing from each row and column are listed along the board.
+	show_line_summaries: bool,
+	/// The solved values still to be revealed, in reading order.
+	reveal_queue: VecDeque<(Position, CellValue)>,
+	/// The text entered into the import panel, if it is open.
+	import_text: Option<String>,
+	/// How the board is probed for feasibility after each edit.
+	feasibility_probe: FeasibilityProbe,
+	/// The cell whose value or color was changed by the last edit, if the last edit changed a single cell.
+	last_edited: Option<Position>,
+	/// Whether the feasibility probe proved the board unsolvable, together with the revision of the board it probed.
+	refuted: Option<(u64, bool)>,
+	/// The playback of the moves made on the board, if it is open.
+	playback: Option<Playback>,
+	/// The running tutorial, if any. It is shown until it is ended, even once all its steps are completed.
+	tutorial: Option<Tutorial>,
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 enum Message {
 	CellInputChanged(u8, u8, String),
@@ -19,23 +208,132 @@ enum Message {
 	SolveRequested,
 	ClearAll,
 	ClearValues,
+	ToggleLabels,
+	ToggleLabelScheme,
+	ZoomIn,
+	ZoomOut,
+	ToggleHeatmap,
+	ToggleDifficultyOverlay,
+	LogicalSolutionComputed(LogicalSolution),
+	ToggleAutoSolve,
+	EditsSettled(u64),
+	UniquenessComputed(u64, Uniqueness),
+	SolvabilityComputed(u64, bool),
+	NextConflict,
+	PreviousConflict,
+	CheckRequested,
+	/// Checks whether the puzzle can be solved with another value in the selected cell.
+	ForcedCheckRequested,
+	SetMode(Mode),
+	ToggleDiagonals,
+	ToggleAntiKnight,
+	CompartmentSumChanged(Orientation, String),
+	PasteRequested,
+	/// Pastes a layout written by `Str8ts::layout_string`, as a new empty puzzle.
+	PasteLayoutRequested,
+	CopyLayout,
+	Undo,
+	FillRandom,
+	BoardPasted(Result<Str8ts, ParseError>),
+	AnalysisComputed(u64, BoardAnalysis),
+	SnapshotNameChanged(String),
+	SaveSnapshot,
+	SnapshotSelected(String),
+	RestoreSnapshot,
+	DeleteSnapshot,
+	ToggleSolveAnimation,
+	ToggleLineHighlight,
+	ToggleLineSummaries,
+	RevealTick,
+	ToggleImport,
+	ImportTextChanged(String),
+	ImportConfirmed,
+	ExportWalkthrough,
+	FillRequested(FillSelection),
+	StartTrial,
+	CommitTrial,
+	DiscardTrial,
+	StartTutorial,
+	TutorialContinue,
+	EndTutorial,
+	/// Moves the selection by the given number of cells in reading order, wrapping around the board.
+	MoveSelection(i8),
+	/// Focuses the text input of the selected cell again.
+	FocusSelected,
+	/// Moves the keyboard focus to the next focusable widget, or with `false` to the previous one.
+	FocusNext(bool),
+	ToggleSelectedColor,
+	ToggleMode,
+	CycleFeasibilityProbe,
+	FeasibilityProbed(u64, bool),
+	CopyReplay,
+	SaveReplay,
+	StartPlayback,
+	TogglePlayback,
+	StepPlayback,
+	PlaybackTick,
+	ClosePlayback,
 }
 
 struct CustomCellStyle {
 	is_black: bool,
+	/// The number of candidates of the cell, if it is to be shaded as part of the heatmap.
+	candidate_count: Option<usize>,
+	/// How late the cell was determined by the logical solver (0 = first step, 1 = last step or not at all),
+	/// if it is to be shaded as part of the difficulty overlay.
+	difficulty: Option<f32>,
+	/// Whether the cell is involved in a conflict.
+	is_conflicting: bool,
+	/// Whether the cell lies on a diagonal that is constrained by the X-Str8ts rule.
+	is_diagonal: bool,
+	/// Whether the cell shares its row or column with the selected cell.
+	is_highlighted: bool,
+	/// The number of the (nested) trial the cell's value was entered in, starting at 1, or 0 if it was entered outside
+	/// any trial.
+	trial: usize,
+}
+
+impl CustomCellStyle {
+	/// Returns the background color of a white cell.
+	///
+	/// Cells of the difficulty overlay are shaded from blue (determined early) to red (determined late or not at all).
+	/// Otherwise, cells of the heatmap are shaded towards the theme's primary color, the darker the fewer candidates they have.
+	/// Remaining cells in the row or column of the selected cell are marked with a light blue, and remaining cells on a
+	/// constrained diagonal with a light gray. Black cells and the borders of conflicting cells are never shaded.
+	fn white_background(&self, style: &Theme) -> Color {
+		if let Some(difficulty) = self.difficulty {
+			return Color::from_rgb(0.6 + 0.4 * difficulty, 0.6, 1.0 - 0.4 * difficulty);
+		}
+		match self.candidate_count {
+			Some(count) => {
+				let primary = style.palette().primary;
+				let intensity = 1.0 - count.min(9) as f32 / 9.0;
+				Color::from_rgb(
+					1.0 + (primary.r - 1.0) * intensity,
+					1.0 + (primary.g - 1.0) * intensity,
+					1.0 + (primary.b - 1.0) * intensity,
+				)
+			}
+			None if self.is_highlighted => Color::from_rgb(0.88, 0.93, 1.0),
+			None if self.is_diagonal => Color::from_rgb(0.93, 0.93, 0.93),
+			None => Color::WHITE,
+		}
+	}
 }
 
 impl text_input::StyleSheet for CustomCellStyle {
 	type Style = Theme;
 
-	fn active(&self, _: &Self::Style) -> text_input::Appearance {
+	fn active(&self, style: &Self::Style) -> text_input::Appearance {
 		text_input::Appearance {
 			background: if self.is_black {
 				Background::Color(Color::BLACK)
 			} else {
-				Background::Color(Color::WHITE)
+				Background::Color(self.white_background(style))
 			},
-			border_color: if self.is_black {
+			border_color: if self.is_conflicting {
+				CONFLICT_COLOR
+			} else if self.is_black {
 				Color::WHITE
 			} else {
 				Color::BLACK
@@ -46,7 +344,7 @@ impl text_input::StyleSheet for CustomCellStyle {
 				Color::BLACK
 			},
 			border_radius: BorderRadius::default(),
-			border_width: 1.0,
+			border_width: if self.is_conflicting { 2.0 } else { 1.0 },
 		}
 	}
 
@@ -65,6 +363,8 @@ impl text_input::StyleSheet for CustomCellStyle {
 	fn value_color(&self, _: &Self::Style) -> Color {
 		if self.is_black {
 			Color::WHITE
+		} else if self.trial > 0 {
+			TRIAL_COLORS[(self.trial - 1) % TRIAL_COLORS.len()]
 		} else {
 			Color::BLACK
 		}
@@ -91,108 +391,1963 @@ impl text_input::StyleSheet for CustomCellStyle {
 	}
 }
 
-impl Sandbox for Str8tsEditor {
+/// The style of a cell of the (read-only) solution view.
+struct SolutionCellStyle {
+	is_black: bool,
+	/// Whether the solution was computed for an older version of the board.
+	is_stale: bool,
+}
+
+impl container::StyleSheet for SolutionCellStyle {
+	type Style = Theme;
+
+	fn appearance(&self, _: &Self::Style) -> container::Appearance {
+		let foreground = if self.is_stale {
+			Color::from_rgb(0.6, 0.6, 0.6)
+		} else if self.is_black {
+			Color::WHITE
+		} else {
+			Color::BLACK
+		};
+		container::Appearance {
+			text_color: Some(foreground),
+			background: Some(Background::Color(if self.is_black {
+				Color::BLACK
+			} else {
+				Color::WHITE
+			})),
+			border_radius: BorderRadius::default(),
+			border_width: 1.0,
+			border_color: foreground,
+		}
+	}
+}
+
+impl Application for Str8tsEditor {
+	type Executor = executor::Default;
 	type Message = Message;
+	type Theme = Theme;
+	type Flags = ();
 
-	fn new() -> Self {
-		Self {
+	fn new(_flags: ()) -> (Self, Command<Message>) {
+		let mut editor = Self {
 			str8ts: Str8ts::new(),
-		}
+			mode: Mode::default(),
+			selected: None,
+			show_labels: true,
+			label_scheme: LabelScheme::default(),
+			zoom: 1.0,
+			show_heatmap: false,
+			show_difficulty_overlay: false,
+			logical_solution: None,
+			auto_solve: true,
+			revision: 0,
+			solution_view: None,
+			solvable: None,
+			solved: None,
+			cache: SolutionCache::default(),
+			incorrect_cells: Vec::new(),
+			check_message: None,
+			solve_statistics: None,
+			placement_error: None,
+			analysis: None,
+			analyzed: AnalyzedBoard::new(Str8ts::new()),
+			history: MoveHistory::default(),
+			rng: SplitMix64(
+				SystemTime::now()
+					.duration_since(UNIX_EPOCH)
+					.map_or(0, |duration| duration.as_nanos() as u64),
+			),
+			snapshots: Snapshots::default(),
+			snapshot_name: String::new(),
+			selected_snapshot: None,
+			trials: Vec::new(),
+			animate_solve: true,
+			highlight_lines: true,
+			show_line_summaries: false,
+			reveal_queue: VecDeque::new(),
+			import_text: None,
+			feasibility_probe: FeasibilityProbe::default(),
+			last_edited: None,
+			refuted: None,
+			playback: None,
+			tutorial: None,
+		};
+		let command = Command::batch([editor.schedule_analysis(), editor.schedule_auto_solve()]);
+		(editor, command)
 	}
 
 	fn title(&self) -> String {
 		String::from("Str8ts Editor")
 	}
 
-	fn update(&mut self, message: Message) {
+	fn update(&mut self, message: Message) -> Command<Message> {
+		let is_ignored = match &message {
+			Message::CellInputChanged(row, col, value) => {
+				parse_cell_input(value).is_none() || self.is_locked(*row, *col)
+			}
+			Message::CellColorToggled(..)
+			| Message::ToggleDiagonals
+			| Message::ToggleAntiKnight
+			| Message::PasteLayoutRequested
+			| Message::FillRandom
+			| Message::ToggleImport => self.mode == Mode::Solve,
+			Message::ImportConfirmed => {
+				self.mode == Mode::Solve || self.imported_board().is_none_or(|board| board.is_err())
+			}
+			// The Solve button is disabled then, but its shortcut is not.
+			Message::SolveRequested => self.solvable == Some((self.revision, false)),
+			Message::StartTrial | Message::FillRequested(_) => self.mode == Mode::Edit,
+			Message::ForcedCheckRequested => {
+				self.mode == Mode::Edit
+					|| self.selected.is_none_or(|(row, col)| {
+						let cell = self.str8ts.get_cell(row, col);
+						cell.color == CellColor::Black
+							|| cell.given || cell.value == CellValue::Empty
+					})
+			}
+			Message::CommitTrial | Message::DiscardTrial => self.trials.is_empty(),
+			Message::Undo => !self.history.can_undo(),
+			Message::RestoreSnapshot => self
+				.selected_snapshot
+				.as_ref()
+				.is_none_or(|name| self.snapshots.restore(name).is_none()),
+			Message::CompartmentSumChanged(orientation, value) => {
+				self.mode == Mode::Solve
+					|| self.compartment_start(*orientation).is_none()
+					|| (!value.trim().is_empty() && parse_sum_input(value).is_none())
+			}
+			_ => false,
+		};
+		if is_ignored {
+			// Ignored input leaves the board unchanged.
+			return Command::none();
+		}
+		if let (Some(tutorial), Some(action)) = (&mut self.tutorial, tutorial_action(&message)) {
+			tutorial.observe(action);
+		}
+
+		let changes_board = matches!(
+			message,
+			Message::CellInputChanged(..)
+				| Message::CellColorToggled(..)
+				| Message::SolveRequested
+				| Message::ClearAll
+				| Message::ClearValues
+				| Message::ToggleDiagonals
+				| Message::ToggleAntiKnight
+				| Message::CompartmentSumChanged(..)
+				| Message::BoardPasted(Ok(_))
+				| Message::Undo
+				| Message::FillRandom
+				| Message::RestoreSnapshot
+				| Message::ImportConfirmed
+				| Message::DiscardTrial
+				| Message::FillRequested(_)
+				| Message::StartTutorial
+		);
+		if changes_board {
+			// Any change of the board ends a running reveal, so it applies to the complete solution.
+			self.finish_reveal();
+			// The logical solution no longer matches the board, it is only recomputed on demand.
+			self.logical_solution = None;
+			self.show_difficulty_overlay = false;
+			self.revision += 1;
+			self.incorrect_cells.clear();
+			self.check_message = None;
+			self.solve_statistics = None;
+			self.placement_error = None;
+			self.last_edited = None;
+		}
+		if matches!(
+			message,
+			Message::CellColorToggled(..)
+				| Message::SolveRequested
+				| Message::ClearAll
+				| Message::ToggleDiagonals
+				| Message::ToggleAntiKnight
+				| Message::CompartmentSumChanged(..)
+				| Message::BoardPasted(Ok(_))
+				| Message::Undo
+				| Message::FillRandom
+				| Message::RestoreSnapshot
+				| Message::ImportConfirmed
+				| Message::StartTutorial
+		) {
+			// The cached solution only survives edits of values.
+			self.solved = None;
+		}
+		if matches!(
+			message,
+			Message::CellColorToggled(..)
+				| Message::ClearAll
+				| Message::ToggleDiagonals
+				| Message::ToggleAntiKnight
+				| Message::CompartmentSumChanged(..)
+				| Message::BoardPasted(Ok(_))
+				| Message::FillRandom
+				| Message::RestoreSnapshot
+				| Message::ImportConfirmed
+				| Message::StartTutorial
+		) {
+			// Trials are guesses about this puzzle, they end with it.
+			self.trials.clear();
+		}
+
 		match message {
 			Message::CellInputChanged(row, col, value) => {
 				// Update logic for changing cell input
-				// Get new value
-				// if not empty or in [1, 9] -> do nothing
-				let value = match value.trim().parse::<u8>() {
-					Ok(value) => match value {
-						1 => CellValue::One,
-						2 => CellValue::Two,
-						3 => CellValue::Three,
-						4 => CellValue::Four,
-						5 => CellValue::Five,
-						6 => CellValue::Six,
-						7 => CellValue::Seven,
-						8 => CellValue::Eight,
-						9 => CellValue::Nine,
-						_ => CellValue::Empty,
-					},
-					Err(_) => CellValue::Empty,
-				};
-				// Update cell
-				self.str8ts.set_cell_value(row, col, value)
+				// Only accept the last typed digit (or clearing the cell), anything else is ignored above
+				if let Some(value) = parse_cell_input(&value) {
+					let previous = self.str8ts.get_cell(row, col);
+					if previous.value != value {
+						let previous = CellContent::of(previous);
+						self.history.record(match value {
+							CellValue::Empty => Move::ClearValue {
+								position: (row, col),
+								previous,
+							},
+							_ => Move::SetValue {
+								position: (row, col),
+								from: previous,
+								to: CellContent {
+									value,
+									given: self.mode == Mode::Edit,
+								},
+							},
+						});
+					}
+					// Values breaking the rules are still entered, but the player is told why they break them
+					if let Err(error) = self.str8ts.place_check(row, col, value) {
+						self.check_message = Some(format!(
+							"{}: {}",
+							self.label_scheme.cell_reference(row, col),
+							error
+						));
+						self.placement_error = Some(((row, col), error));
+					}
+					self.last_edited = Some((row, col));
+					// Update cell, values entered while editing are part of the puzzle
+					self.str8ts.set_cell_value(row, col, value);
+					self.str8ts.set_cell_given(
+						row,
+						col,
+						self.mode == Mode::Edit && value != CellValue::Empty,
+					);
+					if !self.trials.is_empty() && !self.str8ts.conflicts().is_empty() {
+						let hint = "The trial led to a conflict, consider discarding it.";
+						self.check_message = Some(match self.check_message.take() {
+							Some(reason) => format!("{} {}", reason, hint),
+							None => String::from(hint),
+						});
+					}
+				}
+				self.selected = Some((row, col));
 			}
 			Message::CellColorToggled(row, col) => {
 				// Update logic for toggling cell color
 				self.str8ts.toggle_cell_color(row, col);
+				self.history.record(Move::ToggleColor {
+					position: (row, col),
+				});
+				self.selected = Some((row, col));
+				self.last_edited = Some((row, col));
 			}
 			Message::SolveRequested => {
-				// Update logic for solving the str8ts game
-				// Print str8ts game
-				println!("{}", self.str8ts);
-				// Solve str8ts game
-				println!("Solving...");
-				let solved_str8ts = self.str8ts.solve();
-				println!("Solved!");
+				// Update logic for solving the str8ts game, unless the cache knows its solution
+				let (solution, statistics) = match self.cache.cached_solution(&self.str8ts) {
+					Some(solution) => (solution, String::from("from the cache")),
+					None => {
+						let report = self.str8ts.solve_with_report();
+						self.cache.store_solution(&self.str8ts, report.solution);
+						let (variables, constraints) = self.str8ts.model_size();
+						let statistics = format!(
+							"{:?} after {:.3}s, {} variables, {} constraints, {} nodes, {} LP iterations, {}, {}",
+							report.status,
+							report.solving_time,
+							variables,
+							constraints,
+							report.nodes,
+							report.lp_iterations,
+							if report.solved_at_root() {
+								"solved at the root"
+							} else {
+								"required branching"
+							},
+							if self.str8ts.solves_by_propagation() {
+								"solvable by logic alone"
+							} else {
+								"not solvable by logic alone"
+							}
+						);
+						(report.solution, statistics)
+					}
+				};
+				self.solve_statistics = Some(format!(
+					"{} ({}, {})",
+					if solution.is_some() {
+						"Solved"
+					} else {
+						"No solution found"
+					},
+					statistics,
+					self.cache.stats()
+				));
 				// Update str8ts game
-				if let Some(solved_str8ts) = solved_str8ts {
-					println!("Solution found!");
-					println!("{}", solved_str8ts);
-					self.str8ts.copy_from(&solved_str8ts);
-				} else {
-					println!("No solution found!");
+				if let Some(solved_str8ts) = solution {
+					self.history.record(diff_move(&self.str8ts, &solved_str8ts));
+					if self.animate_solve {
+						self.reveal_queue = self
+							.str8ts
+							.into_iter()
+							.zip(solved_str8ts)
+							.enumerate()
+							.filter(|(_, (cell, solved))| {
+								cell.color == CellColor::White && cell.value != solved.value
+							})
+							.map(|(index, (_, solved))| (Str8ts::coords(index as u8), solved.value))
+							.collect();
+					} else {
+						self.str8ts.copy_from(&solved_str8ts);
+					}
 				}
 			}
 			Message::ClearAll => {
 				// Update logic for clearing the str8ts game
 				self.str8ts.clear_all();
+				self.history.clear();
 			}
 			Message::ClearValues => {
-				// Update logic for clearing the str8ts game
-				self.str8ts.clear_values();
+				// Update logic for clearing the str8ts game, keeping its givens
+				let before = self.str8ts;
+				self.str8ts.clear_solution();
+				self.history.record(diff_move(&before, &self.str8ts));
+			}
+			Message::ToggleLabels => {
+				self.show_labels = !self.show_labels;
+			}
+			Message::ToggleLabelScheme => {
+				self.label_scheme = match self.label_scheme {
+					LabelScheme::Numeric => LabelScheme::Letters,
+					LabelScheme::Letters => LabelScheme::Numeric,
+				};
+			}
+			Message::ZoomIn => {
+				self.zoom = (self.zoom + ZOOM_STEP).min(MAX_ZOOM);
+			}
+			Message::ZoomOut => {
+				self.zoom = (self.zoom - ZOOM_STEP).max(MIN_ZOOM);
+			}
+			Message::ToggleHeatmap => {
+				self.show_heatmap = !self.show_heatmap;
+			}
+			Message::ToggleDifficultyOverlay => {
+				self.show_difficulty_overlay = !self.show_difficulty_overlay;
+				if self.show_difficulty_overlay && self.logical_solution.is_none() {
+					// Solving logically is not free, so do it in the background.
+					let str8ts = self.str8ts;
+					return Command::perform(
+						async move { str8ts.solve_logically() },
+						Message::LogicalSolutionComputed,
+					);
+				}
+			}
+			Message::LogicalSolutionComputed(logical_solution) => {
+				self.logical_solution = Some(logical_solution);
+			}
+			Message::ToggleAutoSolve => {
+				self.auto_solve = !self.auto_solve;
+				if self.auto_solve {
+					return self.schedule_auto_solve();
+				}
+			}
+			Message::EditsSettled(revision) => {
+				if revision == self.revision {
+					let str8ts = self.str8ts;
+					let probe = self.schedule_feasibility_probe();
+					// Puzzles solved before need not be solved again.
+					if self.auto_solve {
+						if let Some(uniqueness) = self.cache.cached_uniqueness(&str8ts) {
+							self.solvable =
+								Some((revision, !matches!(uniqueness, Uniqueness::Infeasible)));
+							self.solution_view = Some((revision, uniqueness));
+							return probe;
+						}
+					} else if let Some(solvable) = self.cache.cached_solvability(&str8ts) {
+						self.solvable = Some((revision, solvable));
+						return probe;
+					}
+					// Without auto solve, only the cheaper check for a solution is needed.
+					let solve = if self.auto_solve {
+						Command::perform(async move { str8ts.uniqueness() }, move |uniqueness| {
+							Message::UniquenessComputed(revision, uniqueness)
+						})
+					} else {
+						Command::perform(async move { str8ts.is_solvable() }, move |solvable| {
+							Message::SolvabilityComputed(revision, solvable)
+						})
+					};
+					return Command::batch([solve, probe]);
+				}
+			}
+			Message::CycleFeasibilityProbe => {
+				self.feasibility_probe = match self.feasibility_probe {
+					FeasibilityProbe::Off => FeasibilityProbe::Propagation,
+					FeasibilityProbe::Propagation => FeasibilityProbe::Full,
+					FeasibilityProbe::Full => FeasibilityProbe::Off,
+				};
+				self.refuted = None;
+				return self.schedule_feasibility_probe();
+			}
+			Message::FeasibilityProbed(revision, refuted) => {
+				// Results for older boards (or from before the probe was turned off) are dropped.
+				if revision == self.revision && self.feasibility_probe != FeasibilityProbe::Off {
+					self.refuted = Some((revision, refuted));
+					if let Some((row, col)) = self.last_edited.filter(|_| refuted) {
+						self.check_message = Some(format!(
+							"{}: this edit left the puzzle without a solution, undo it to go back",
+							self.label_scheme.cell_reference(row, col)
+						));
+					}
+				}
+			}
+			Message::UniquenessComputed(revision, uniqueness) => {
+				if revision == self.revision {
+					self.cache.store_uniqueness(&self.str8ts, &uniqueness);
+				}
+				self.solvable = Some((revision, !matches!(uniqueness, Uniqueness::Infeasible)));
+				self.solution_view = Some((revision, uniqueness));
+			}
+			Message::SolvabilityComputed(revision, solvable) => {
+				if revision == self.revision {
+					self.cache.store_solvability(&self.str8ts, solvable);
+				}
+				self.solvable = Some((revision, solvable));
+			}
+			Message::CheckRequested => {
+				if self.solved.is_none() {
+					self.solved = self.cache.solved(&self.str8ts);
+				}
+				match &self.solved {
+					Some(solved) => {
+						self.incorrect_cells = solved
+							.diff_from(&self.str8ts)
+							.into_iter()
+							.map(|(position, _)| position)
+							.filter(|(row, col)| {
+								self.str8ts.get_cell(*row, *col).value != CellValue::Empty
+							})
+							.collect();
+						let message = match self.incorrect_cells.len() {
+							0 => String::from("All values are correct so far"),
+							1 => String::from("1 value is incorrect"),
+							count => format!("{} values are incorrect", count),
+						};
+						// Name the rule that is broken, if the incorrect values already break one.
+						self.check_message = Some(match self.str8ts.first_violation() {
+							None => String::from("The board is solved"),
+							Some(Violation::Conflict(conflict)) => {
+								format!("{}: {}", message, conflict)
+							}
+							Some(Violation::EmptyCell(_)) => message,
+						});
+					}
+					None => {
+						self.check_message = Some(String::from("The board has no solution"));
+					}
+				}
+			}
+			Message::ForcedCheckRequested => {
+				if let Some((row, col)) = self.selected {
+					let value = self.str8ts.get_cell(row, col).value;
+					let mut puzzle = self.str8ts;
+					puzzle.clear_solution();
+					// The candidates of the puzzle's empty cells, except for the value to check.
+					let mut candidates: [[BTreeSet<CellValue>; SIZE]; SIZE] = Default::default();
+					for ((i, j), cell) in puzzle.white_cells() {
+						candidates[i as usize][j as usize] = if cell.value == CellValue::Empty {
+							puzzle.candidates(i, j).into_iter().collect()
+						} else {
+							BTreeSet::from([cell.value])
+						};
+					}
+					candidates[row as usize][col as usize].remove(&value);
+					let cell = self.label_scheme.cell_reference(row, col);
+					self.check_message = Some(match puzzle.solve_with_candidates(&candidates) {
+						Some(other) => format!(
+							"{} is not forced: the puzzle can also be solved with a {} there",
+							cell,
+							other.get_cell(row, col).value
+						),
+						None => format!("{} must be a {}", cell, value),
+					});
+				}
+			}
+			Message::SetMode(mode) => {
+				self.mode = mode;
+			}
+			Message::ToggleDiagonals => {
+				self.str8ts.rules.diagonals = !self.str8ts.rules.diagonals;
+			}
+			Message::ToggleAntiKnight => {
+				self.str8ts.rules.anti_knight = !self.str8ts.rules.anti_knight;
+			}
+			Message::CompartmentSumChanged(orientation, value) => {
+				if let Some(first) = self.compartment_start(orientation) {
+					self.str8ts
+						.set_compartment_sum(first, orientation, parse_sum_input(&value));
+				}
+			}
+			Message::PasteRequested => {
+				return clipboard::read(|text| {
+					Message::BoardPasted(text.unwrap_or_default().parse())
+				});
+			}
+			Message::PasteLayoutRequested => {
+				return clipboard::read(|text| {
+					Message::BoardPasted(Str8ts::from_layout_string(&text.unwrap_or_default()))
+				});
+			}
+			Message::CopyLayout => {
+				self.check_message = Some(String::from("Copied the layout to the clipboard."));
+				return clipboard::write(self.str8ts.layout_string());
+			}
+			Message::BoardPasted(result) => match result {
+				// While solving, only the values of the pasted board are taken over, onto the puzzle being solved
+				Ok(pasted) if self.mode == Mode::Solve => {
+					match Str8ts::overlay(&self.str8ts, &pasted) {
+						Ok(board) => {
+							if board != self.str8ts {
+								self.history.record(diff_move(&self.str8ts, &board));
+							}
+							self.str8ts = board;
+						}
+						Err(error) => {
+							self.check_message =
+								Some(format!("Could not paste the values: {}", error));
+						}
+					}
+				}
+				Ok(str8ts) => {
+					self.str8ts = str8ts;
+					self.selected = None;
+					self.history.clear();
+				}
+				Err(error) => {
+					self.check_message = Some(format!("Could not paste the board: {}", error));
+				}
+			},
+			Message::ToggleImport => {
+				self.import_text = match self.import_text {
+					Some(_) => None,
+					None => Some(String::new()),
+				};
+			}
+			Message::ImportTextChanged(text) => {
+				self.import_text = Some(text);
+			}
+			Message::ImportConfirmed => {
+				if let Some(Ok(str8ts)) = self.imported_board() {
+					self.str8ts = str8ts;
+					self.selected = None;
+					self.history.clear();
+					self.import_text = None;
+				}
+			}
+			Message::ExportWalkthrough => match self.str8ts.solution_walkthrough() {
+				Ok(walkthrough) => {
+					self.check_message =
+						Some(String::from("Copied the walkthrough to the clipboard."));
+					return clipboard::write(walkthrough.to_markdown());
+				}
+				Err(error) => {
+					self.check_message =
+						Some(format!("Could not export the walkthrough: {}", error));
+				}
+			},
+			Message::Undo => {
+				self.history.undo(&mut self.str8ts);
+			}
+			Message::CopyReplay => match Replay::from_history(&self.str8ts, &self.history) {
+				Some(replay) => {
+					self.check_message = Some(String::from("Copied the replay to the clipboard."));
+					return clipboard::write(replay.file_contents());
+				}
+				None => {
+					self.check_message = Some(String::from("The moves do not fit the board."));
+				}
+			},
+			Message::SaveReplay => {
+				self.check_message =
+					Some(match Replay::from_history(&self.str8ts, &self.history) {
+						Some(replay) => match replay.save(Path::new(REPLAY_FILE)) {
+							Ok(()) => format!("Saved the replay to {}.", REPLAY_FILE),
+							Err(error) => format!("Could not save the replay: {}", error),
+						},
+						None => String::from("The moves do not fit the board."),
+					});
+			}
+			Message::StartPlayback => {
+				self.playback =
+					Replay::from_history(&self.str8ts, &self.history).and_then(|replay| {
+						Some(Playback {
+							boards: replay.boards().ok()?,
+							replay,
+							played: 0,
+							is_playing: true,
+						})
+					});
+			}
+			Message::TogglePlayback => {
+				if let Some(playback) = &mut self.playback {
+					if playback.played == playback.replay.moves.len() {
+						// Playing a finished playback starts it over.
+						playback.played = 0;
+					}
+					playback.is_playing = !playback.is_playing;
+				}
+			}
+			Message::StepPlayback | Message::PlaybackTick => {
+				if let Some(playback) = &mut self.playback {
+					if matches!(message, Message::StepPlayback) {
+						playback.is_playing = false;
+					}
+					playback.played = (playback.played + 1).min(playback.replay.moves.len());
+					if playback.played == playback.replay.moves.len() {
+						playback.is_playing = false;
+					}
+				}
+			}
+			Message::ClosePlayback => {
+				self.playback = None;
+			}
+			Message::FillRandom => {
+				self.str8ts = Str8ts::random_full_solution(&mut self.rng);
+				self.selected = None;
+				self.history.clear();
+			}
+			Message::SnapshotNameChanged(name) => {
+				self.snapshot_name = name;
+			}
+			Message::SaveSnapshot => {
+				let name = match self.snapshot_name.trim() {
+					"" => format!("Checkpoint {}", self.snapshots.list().len() + 1),
+					name => name.to_string(),
+				};
+				self.snapshots.save(&name, &self.str8ts);
+				self.selected_snapshot = Some(name);
+				self.snapshot_name.clear();
+			}
+			Message::SnapshotSelected(name) => {
+				self.selected_snapshot = Some(name);
+			}
+			Message::RestoreSnapshot => {
+				if let Some(restored) = self
+					.selected_snapshot
+					.as_ref()
+					.and_then(|name| self.snapshots.restore(name))
+				{
+					// Only changes of values can be undone, a checkpoint of a different puzzle is like pasting it.
+					if restored.layout_string() == self.str8ts.layout_string()
+						&& restored.rules == self.str8ts.rules
+						&& restored.sums == self.str8ts.sums
+					{
+						self.history.record(diff_move(&self.str8ts, &restored));
+					} else {
+						self.history.clear();
+					}
+					self.str8ts = restored;
+				}
+			}
+			Message::DeleteSnapshot => {
+				if let Some(name) = self.selected_snapshot.take() {
+					self.snapshots.delete(&name);
+				}
+			}
+			Message::FillRequested(selection) => {
+				let before = self.str8ts;
+				match self
+					.cache
+					.fill_from_solution(&mut self.str8ts, selection, &mut self.rng)
+				{
+					Ok(filled) if !filled.is_empty() => {
+						self.history.record(diff_move(&before, &self.str8ts));
+					}
+					Ok(_) => {}
+					Err(error) => {
+						self.check_message = Some(format!("Could not fill the cells: {}", error));
+					}
+				}
+			}
+			Message::StartTrial => {
+				self.trials.push(self.str8ts);
+			}
+			Message::CommitTrial => {
+				// The values stay on the board, and now belong to the enclosing trial (if any).
+				self.trials.pop();
+			}
+			Message::DiscardTrial => {
+				if let Some(started) = self.trials.pop() {
+					self.history.record(diff_move(&self.str8ts, &started));
+					self.str8ts = started;
+				}
+			}
+			Message::StartTutorial => {
+				let tutorial = Tutorial::rules();
+				self.str8ts = tutorial.board();
+				self.mode = Mode::Edit;
+				self.selected = None;
+				self.history.clear();
+				self.tutorial = Some(tutorial);
+			}
+			// The step was already completed above.
+			Message::TutorialContinue => {}
+			Message::EndTutorial => {
+				self.tutorial = None;
+			}
+			Message::ToggleSolveAnimation => {
+				self.animate_solve = !self.animate_solve;
+				if !self.animate_solve && !self.reveal_queue.is_empty() {
+					self.finish_reveal();
+					self.revision += 1;
+					return Command::batch([self.schedule_analysis(), self.schedule_auto_solve()]);
+				}
+			}
+			Message::ToggleLineHighlight => {
+				self.highlight_lines = !self.highlight_lines;
+			}
+			Message::ToggleLineSummaries => {
+				self.show_line_summaries = !self.show_line_summaries;
+			}
+			Message::RevealTick => {
+				if let Some(((row, col), value)) = self.reveal_queue.pop_front() {
+					self.str8ts.set_cell_value(row, col, value);
+					if self.reveal_queue.is_empty() {
+						// The board only settles once all values are revealed.
+						self.revision += 1;
+						return Command::batch([
+							self.schedule_analysis(),
+							self.schedule_auto_solve(),
+						]);
+					}
+				}
+			}
+			Message::AnalysisComputed(revision, analysis) => {
+				if self
+					.analysis
+					.as_ref()
+					.is_none_or(|(current, _)| *current < revision)
+				{
+					self.analysis = Some((revision, analysis));
+				}
+			}
+			Message::MoveSelection(offset) => {
+				let index = match self.selected {
+					Some((row, col)) => (Str8ts::index(row, col) as i16 + offset as i16)
+						.rem_euclid(CELLS as i16) as u8,
+					None => 0,
+				};
+				let (row, col) = Str8ts::coords(index);
+				return self.select_cell(row, col);
+			}
+			Message::FocusSelected => {
+				if let Some((row, col)) = self.selected {
+					return self.select_cell(row, col);
+				}
+			}
+			Message::FocusNext(true) => return focus_next(),
+			Message::FocusNext(false) => return focus_previous(),
+			// The shortcuts stand in for the messages of the buttons they replace.
+			Message::ToggleSelectedColor => {
+				if let Some((row, col)) = self.selected {
+					return self.update(Message::CellColorToggled(row, col));
+				}
+			}
+			Message::ToggleMode => {
+				return self.update(Message::SetMode(match self.mode {
+					Mode::Edit => Mode::Solve,
+					Mode::Solve => Mode::Edit,
+				}));
+			}
+			Message::NextConflict => {
+				let conflict_cells = self.conflict_cells();
+				let next = conflict_cells
+					.iter()
+					.find(|cell| Some(**cell) > self.selected)
+					.or(conflict_cells.first());
+				if let Some((row, col)) = next {
+					return self.select_cell(*row, *col);
+				}
+			}
+			Message::PreviousConflict => {
+				let conflict_cells = self.conflict_cells();
+				let previous = conflict_cells
+					.iter()
+					.rev()
+					.find(|cell| self.selected.is_none_or(|selected| **cell < selected))
+					.or(conflict_cells.last());
+				if let Some((row, col)) = previous {
+					return self.select_cell(*row, *col);
+				}
 			}
 		}
+
+		if changes_board {
+			return Command::batch([self.schedule_analysis(), self.schedule_auto_solve()]);
+		}
+		Command::none()
+	}
+
+	fn subscription(&self) -> Subscription<Message> {
+		let keys = subscription::events_with(|event, status| match event {
+			Event::Keyboard(keyboard::Event::KeyPressed {
+				key_code,
+				modifiers,
+			}) => shortcut(key_code, modifiers, status),
+			_ => None,
+		});
+		let mut subscriptions = vec![keys];
+		if !self.reveal_queue.is_empty() {
+			// Reveals the next solved value after every delay, until the queue is empty and the subscription is dropped.
+			subscriptions.push(every("reveal", REVEAL_DELAY, Message::RevealTick));
+		}
+		if self
+			.playback
+			.as_ref()
+			.is_some_and(|playback| playback.is_playing)
+		{
+			// Likewise, plays the next move after every delay, until the playback is paused or finished.
+			subscriptions.push(every("playback", PLAYBACK_DELAY, Message::PlaybackTick));
+		}
+		Subscription::batch(subscriptions)
 	}
 
-	fn view(&self) -> Element<Message> {
-		let mut board = Column::new().spacing(10);
+	fn view(&self) -> Element<'_, Message> {
+		let conflict_cells = self.conflict_cells();
+		let mut grid = Column::new().spacing(SPACING);
+		let show_labels = self.show_labels && self.zoom >= LABEL_MIN_ZOOM;
+		// Sum clues are shown above the first cell of their compartment, if the game has any.
+		let has_sums = self.str8ts.sums.iter().flatten().any(Option::is_some);
+		let line_summaries = self
+			.show_line_summaries
+			.then(|| self.str8ts.line_summaries());
 
-		for row in 0..9 {
-			let mut row_cells = Row::new().spacing(10);
-			for col in 0..9 {
+		if show_labels {
+			// Column labels, each centered above the input and toggle button of its column.
+			let mut label_row = Row::new().spacing(SPACING);
+			label_row = label_row.push(self.label(String::new()));
+			for col in 0..SIZE as u8 {
+				let label = self.label(self.label_scheme.col_label(col));
+				label_row = label_row.push(
+					Container::new(label)
+						.width(Length::Fixed(
+							(CELL_WIDTH + TOGGLE_WIDTH) * self.zoom + SPACING,
+						))
+						.center_x(),
+				);
+			}
+			grid = grid.push(label_row);
+		}
+
+		for row in 0..SIZE as u8 {
+			let mut row_cells = Row::new().spacing(SPACING);
+			if show_labels {
+				row_cells = row_cells.push(self.label(self.label_scheme.row_label(row)));
+			}
+			for col in 0..SIZE as u8 {
 				let cell = self.str8ts.get_cell(row, col);
-				let input = TextInput::new("", cell.value.to_string().as_str())
-					.on_input(move |v| Message::CellInputChanged(row, col, v))
-					.width(Length::Fixed(35.0))
-					.style(theme::TextInput::Custom(Box::new(CustomCellStyle {
+				let candidate_count = self
+					.analysis()
+					.filter(|_| {
+						self.show_heatmap
+							&& cell.color == CellColor::White
+							&& cell.value == CellValue::Empty
+					})
+					.map(|analysis| analysis.candidates(row, col).len());
+				let difficulty = self.difficulty(row, col);
+				let placement_error = self
+					.placement_error
+					.filter(|(position, _)| *position == (row, col))
+					.map(|(_, error)| error);
+				let mut input =
+					TextInput::new("", cell.value.to_string().as_str()).id(cell_input_id(row, col));
+				if !self.is_locked(row, col) {
+					input = input.on_input(move |v| Message::CellInputChanged(row, col, v));
+				}
+				let input = input.width(Length::Fixed(CELL_WIDTH * self.zoom)).style(
+					theme::TextInput::Custom(Box::new(CustomCellStyle {
 						is_black: cell.color == CellColor::Black,
-					})));
+						candidate_count,
+						difficulty: difficulty.as_ref().map(|(difficulty, _)| *difficulty),
+						is_conflicting: conflict_cells.contains(&(row, col))
+							|| self.incorrect_cells.contains(&(row, col))
+							|| placement_error.is_some()
+							|| self.is_refuting_edit(row, col),
+						is_diagonal: self.str8ts.rules.diagonals
+							&& !Diagonal::of(row, col).is_empty(),
+						is_highlighted: self.is_highlighted(row, col),
+						trial: self.trial_of(row, col),
+					})),
+				);
+				let input: Element<Message> = match (placement_error, difficulty) {
+					(Some(error), _) => {
+						Tooltip::new(input, error.to_string(), tooltip::Position::Top).into()
+					}
+					(None, Some((_, description))) => {
+						Tooltip::new(input, description, tooltip::Position::Top).into()
+					}
+					(None, None) => input.into(),
+				};
 
-				let button = Button::new("").on_press(Message::CellColorToggled(row, col));
+				let mut button = Button::new("").width(Length::Fixed(TOGGLE_WIDTH * self.zoom));
+				if self.mode == Mode::Edit {
+					button = button.on_press(Message::CellColorToggled(row, col));
+				}
 
+				let input: Element<Message> = if has_sums {
+					let (row_compartment, col_compartment) =
+						self.str8ts.compartment_cells(row, col);
+					let mut label = String::new();
+					if row_compartment.first() == Some(&(row, col)) {
+						if let Some(sum) = self.str8ts.compartment_sum((row, col), Orientation::Row)
+						{
+							label.push_str(&format!("→{}", sum));
+						}
+					}
+					if col_compartment.first() == Some(&(row, col)) {
+						if let Some(sum) =
+							self.str8ts.compartment_sum((row, col), Orientation::Column)
+						{
+							if !label.is_empty() {
+								label.push(' ');
+							}
+							label.push_str(&format!("↓{}", sum));
+						}
+					}
+					Column::new()
+						.push(Text::new(label).size(SUM_LABEL_SIZE * self.zoom))
+						.push(input)
+						.into()
+				} else {
+					input
+				};
 				row_cells = row_cells.push(Container::new(input).width(Length::Shrink));
 				row_cells = row_cells.push(Container::new(button).width(Length::Shrink));
 			}
-			board = board.push(row_cells);
+			if let Some(line_summaries) = &line_summaries {
+				// The values still missing from the row, to the right of it.
+				let missing: String = line_summaries.rows[row as usize]
+					.missing
+					.iter()
+					.map(ToString::to_string)
+					.collect();
+				row_cells = row_cells.push(Text::new(missing).size(SUM_LABEL_SIZE * self.zoom));
+			}
+			grid = grid.push(row_cells);
+		}
+		if let Some(line_summaries) = &line_summaries {
+			// The values still missing from each column, stacked below it.
+			let mut rail = Row::new().spacing(SPACING);
+			if show_labels {
+				rail = rail.push(self.label(String::new()));
+			}
+			for summary in line_summaries.cols.iter() {
+				let missing: Vec<String> =
+					summary.missing.iter().map(ToString::to_string).collect();
+				rail = rail.push(
+					Container::new(Text::new(missing.join("\n")).size(SUM_LABEL_SIZE * self.zoom))
+						.width(Length::Fixed(
+							(CELL_WIDTH + TOGGLE_WIDTH) * self.zoom + SPACING,
+						))
+						.center_x(),
+				);
+			}
+			grid = grid.push(rail);
+		}
+
+		let mut board = Column::new().spacing(SPACING);
+		if self.auto_solve {
+			board = board.push(
+				Row::new()
+					.spacing(SPACING * 2.0)
+					.push(grid)
+					.push(self.solution_pane()),
+			);
+		} else {
+			board = board.push(grid);
 		}
 
 		let mut button_row = Row::new().spacing(10);
-		let solve_button = Button::new(Text::new("Solve")).on_press(Message::SolveRequested);
+		for mode in [Mode::Edit, Mode::Solve] {
+			let mut mode_button = Button::new(Text::new(format!("{} Mode", mode)));
+			if self.mode != mode {
+				mode_button = mode_button.on_press(Message::SetMode(mode));
+			}
+			button_row = button_row.push(Container::new(mode_button).width(Length::Shrink));
+		}
+		// Solving and checking are pointless once the board is known to have no solution.
+		let is_unsolvable = self.solvable == Some((self.revision, false));
+		let mut solve_button = Button::new(Text::new("Solve"));
+		let mut check_button = Button::new(Text::new("Check"));
+		if !is_unsolvable {
+			solve_button = solve_button.on_press(Message::SolveRequested);
+			check_button = check_button.on_press(Message::CheckRequested);
+		}
 		let clear_all_button = Button::new(Text::new("Clear All")).on_press(Message::ClearAll);
 		let clear_values_button =
 			Button::new(Text::new("Clear Values")).on_press(Message::ClearValues);
 		button_row = button_row.push(Container::new(solve_button).width(Length::Shrink));
+		button_row = button_row.push(Container::new(check_button).width(Length::Shrink));
+		if self.mode == Mode::Solve && !is_unsolvable {
+			let mut forced_button = Button::new(Text::new("Is It Forced?"));
+			let mut fill_cell_button = Button::new(Text::new("Fill Cell"));
+			let mut fill_compartment_button = Button::new(Text::new("Fill Compartment"));
+			let mut fill_row_button = Button::new(Text::new("Fill Row"));
+			let mut fill_column_button = Button::new(Text::new("Fill Column"));
+			if let Some((row, col)) = self.selected {
+				let cell = self.str8ts.get_cell(row, col);
+				if cell.color == CellColor::White && !cell.given && cell.value != CellValue::Empty {
+					forced_button = forced_button.on_press(Message::ForcedCheckRequested);
+				}
+				fill_cell_button =
+					fill_cell_button.on_press(Message::FillRequested(FillSelection::Cells(vec![
+						(row, col),
+					])));
+				// The row compartment of the cell, or its column compartment if it has none.
+				let (row_compartment, col_compartment) = self.analyzed.compartments_of((row, col));
+				if let Some(id) = row_compartment.or(col_compartment) {
+					fill_compartment_button = fill_compartment_button
+						.on_press(Message::FillRequested(FillSelection::Compartment(id)));
+				}
+				fill_row_button =
+					fill_row_button.on_press(Message::FillRequested(FillSelection::Row(row)));
+				fill_column_button =
+					fill_column_button.on_press(Message::FillRequested(FillSelection::Column(col)));
+			}
+			let fill_random_cells_button = Button::new(Text::new("Fill 5 Cells"))
+				.on_press(Message::FillRequested(FillSelection::Random(5)));
+			button_row = button_row.push(Container::new(forced_button).width(Length::Shrink));
+			button_row = button_row.push(Container::new(fill_cell_button).width(Length::Shrink));
+			button_row =
+				button_row.push(Container::new(fill_compartment_button).width(Length::Shrink));
+			button_row = button_row.push(Container::new(fill_row_button).width(Length::Shrink));
+			button_row = button_row.push(Container::new(fill_column_button).width(Length::Shrink));
+			button_row =
+				button_row.push(Container::new(fill_random_cells_button).width(Length::Shrink));
+		}
 		button_row = button_row.push(Container::new(clear_all_button).width(Length::Shrink));
 		button_row = button_row.push(Container::new(clear_values_button).width(Length::Shrink));
+		let mut undo_button = Button::new(Text::new("Undo"));
+		if self.history.can_undo() {
+			undo_button = undo_button.on_press(Message::Undo);
+		}
+		button_row = button_row.push(Container::new(undo_button).width(Length::Shrink));
+		let mut copy_replay_button = Button::new(Text::new("Copy Replay"));
+		let mut save_replay_button = Button::new(Text::new("Save Replay"));
+		let mut playback_button = Button::new(Text::new("Play Moves"));
+		if self.history.can_undo() {
+			copy_replay_button = copy_replay_button.on_press(Message::CopyReplay);
+			save_replay_button = save_replay_button.on_press(Message::SaveReplay);
+			playback_button = playback_button.on_press(Message::StartPlayback);
+		}
+		button_row = button_row.push(Container::new(copy_replay_button).width(Length::Shrink));
+		button_row = button_row.push(Container::new(save_replay_button).width(Length::Shrink));
+		button_row = button_row.push(Container::new(playback_button).width(Length::Shrink));
+		let paste_button = Button::new(Text::new("Paste")).on_press(Message::PasteRequested);
+		button_row = button_row.push(Container::new(paste_button).width(Length::Shrink));
+		let copy_layout_button =
+			Button::new(Text::new("Copy Layout")).on_press(Message::CopyLayout);
+		button_row = button_row.push(Container::new(copy_layout_button).width(Length::Shrink));
+		let mut paste_layout_button = Button::new(Text::new("Paste Layout"));
+		if self.mode == Mode::Edit {
+			paste_layout_button = paste_layout_button.on_press(Message::PasteLayoutRequested);
+		}
+		button_row = button_row.push(Container::new(paste_layout_button).width(Length::Shrink));
+		let mut import_button = Button::new(Text::new(if self.import_text.is_some() {
+			"Close Import"
+		} else {
+			"Import..."
+		}));
+		if self.mode == Mode::Edit {
+			import_button = import_button.on_press(Message::ToggleImport);
+		}
+		button_row = button_row.push(Container::new(import_button).width(Length::Shrink));
+		let mut walkthrough_button = Button::new(Text::new("Copy Walkthrough"));
+		if !is_unsolvable {
+			walkthrough_button = walkthrough_button.on_press(Message::ExportWalkthrough);
+		}
+		button_row = button_row.push(Container::new(walkthrough_button).width(Length::Shrink));
+		let mut fill_random_button = Button::new(Text::new("Fill Random"));
+		if self.mode == Mode::Edit {
+			fill_random_button = fill_random_button.on_press(Message::FillRandom);
+		}
+		button_row = button_row.push(Container::new(fill_random_button).width(Length::Shrink));
+
+		let mut snapshot_row = Row::new().spacing(10);
+		let snapshot_name_input = TextInput::new("Checkpoint name", &self.snapshot_name)
+			.on_input(Message::SnapshotNameChanged)
+			.on_submit(Message::SaveSnapshot)
+			.width(Length::Fixed(150.0));
+		let save_snapshot_button =
+			Button::new(Text::new("Save Checkpoint")).on_press(Message::SaveSnapshot);
+		let snapshot_names: Vec<String> = self
+			.snapshots
+			.list()
+			.into_iter()
+			.map(String::from)
+			.collect();
+		let snapshot_list = PickList::new(
+			snapshot_names,
+			self.selected_snapshot.clone(),
+			Message::SnapshotSelected,
+		)
+		.placeholder("Checkpoints");
+		let mut restore_snapshot_button = Button::new(Text::new("Restore"));
+		let mut delete_snapshot_button = Button::new(Text::new("Delete"));
+		if self.selected_snapshot.is_some() {
+			restore_snapshot_button = restore_snapshot_button.on_press(Message::RestoreSnapshot);
+			delete_snapshot_button = delete_snapshot_button.on_press(Message::DeleteSnapshot);
+		}
+		snapshot_row = snapshot_row.push(Container::new(snapshot_name_input).width(Length::Shrink));
+		snapshot_row =
+			snapshot_row.push(Container::new(save_snapshot_button).width(Length::Shrink));
+		snapshot_row = snapshot_row.push(Container::new(snapshot_list).width(Length::Shrink));
+		snapshot_row =
+			snapshot_row.push(Container::new(restore_snapshot_button).width(Length::Shrink));
+		snapshot_row =
+			snapshot_row.push(Container::new(delete_snapshot_button).width(Length::Shrink));
+		if let Some(diff) = self
+			.selected_snapshot
+			.as_ref()
+			.and_then(|name| self.snapshots.diff_against(name, &self.str8ts))
+		{
+			snapshot_row = snapshot_row.push(Text::new(match diff.len() {
+				0 => String::from("Same values as the board"),
+				1 => String::from("1 cell differs from the board"),
+				count => format!("{} cells differ from the board", count),
+			}));
+		}
+		let mut start_trial_button = Button::new(Text::new(match self.trials.len() {
+			0 => String::from("Start Trial"),
+			depth => format!("Start Trial {}", depth + 1),
+		}));
+		let mut commit_trial_button = Button::new(Text::new("Commit Trial"));
+		let mut discard_trial_button = Button::new(Text::new("Discard Trial"));
+		if self.mode == Mode::Solve {
+			start_trial_button = start_trial_button.on_press(Message::StartTrial);
+		}
+		if !self.trials.is_empty() {
+			commit_trial_button = commit_trial_button.on_press(Message::CommitTrial);
+			discard_trial_button = discard_trial_button.on_press(Message::DiscardTrial);
+		}
+		snapshot_row = snapshot_row.push(Container::new(start_trial_button).width(Length::Shrink));
+		snapshot_row = snapshot_row.push(Container::new(commit_trial_button).width(Length::Shrink));
+		snapshot_row =
+			snapshot_row.push(Container::new(discard_trial_button).width(Length::Shrink));
+
+		let mut diagonals_button = Button::new(Text::new(if self.str8ts.rules.diagonals {
+			"X-Str8ts: On"
+		} else {
+			"X-Str8ts: Off"
+		}));
+		if self.mode == Mode::Edit {
+			diagonals_button = diagonals_button.on_press(Message::ToggleDiagonals);
+		}
+		button_row = button_row.push(Container::new(diagonals_button).width(Length::Shrink));
+		let mut anti_knight_button = Button::new(Text::new(if self.str8ts.rules.anti_knight {
+			"Anti-Knight: On"
+		} else {
+			"Anti-Knight: Off"
+		}));
+		if self.mode == Mode::Edit {
+			anti_knight_button = anti_knight_button.on_press(Message::ToggleAntiKnight);
+		}
+		button_row = button_row.push(Container::new(anti_knight_button).width(Length::Shrink));
+
+		let previous_conflict_button =
+			Button::new(Text::new("Previous Conflict")).on_press(Message::PreviousConflict);
+		let next_conflict_button =
+			Button::new(Text::new("Next Conflict")).on_press(Message::NextConflict);
+		button_row =
+			button_row.push(Container::new(previous_conflict_button).width(Length::Shrink));
+		button_row = button_row.push(Container::new(next_conflict_button).width(Length::Shrink));
+
+		let mut view_row = Row::new().spacing(10);
+		let labels_button = Button::new(Text::new(if self.show_labels {
+			"Hide Labels"
+		} else {
+			"Show Labels"
+		}))
+		.on_press(Message::ToggleLabels);
+		let label_scheme_button = Button::new(Text::new(match self.label_scheme {
+			LabelScheme::Numeric => "Columns A-I",
+			LabelScheme::Letters => "Columns 1-9",
+		}))
+		.on_press(Message::ToggleLabelScheme);
+		let zoom_out_button = Button::new(Text::new("-")).on_press(Message::ZoomOut);
+		let zoom_in_button = Button::new(Text::new("+")).on_press(Message::ZoomIn);
+		let heatmap_button = Button::new(Text::new(if self.show_heatmap {
+			"Hide Heatmap"
+		} else {
+			"Show Heatmap"
+		}))
+		.on_press(Message::ToggleHeatmap);
+		let difficulty_overlay_button = Button::new(Text::new(if self.show_difficulty_overlay {
+			"Hide Difficulty"
+		} else {
+			"Show Difficulty"
+		}))
+		.on_press(Message::ToggleDifficultyOverlay);
+		let auto_solve_button = Button::new(Text::new(if self.auto_solve {
+			"Auto Solve: On"
+		} else {
+			"Auto Solve: Off"
+		}))
+		.on_press(Message::ToggleAutoSolve);
+		let solve_animation_button = Button::new(Text::new(if self.animate_solve {
+			"Animate Solve: On"
+		} else {
+			"Animate Solve: Off"
+		}))
+		.on_press(Message::ToggleSolveAnimation);
+		let line_highlight_button = Button::new(Text::new(if self.highlight_lines {
+			"Highlight Lines: On"
+		} else {
+			"Highlight Lines: Off"
+		}))
+		.on_press(Message::ToggleLineHighlight);
+		let line_summaries_button = Button::new(Text::new(if self.show_line_summaries {
+			"Missing Values: On"
+		} else {
+			"Missing Values: Off"
+		}))
+		.on_press(Message::ToggleLineSummaries);
+		let feasibility_probe_button = Button::new(Text::new(format!(
+			"Live Feasibility: {}",
+			self.feasibility_probe
+		)))
+		.on_press(Message::CycleFeasibilityProbe);
+		let tutorial_button = Button::new(Text::new("Tutorial")).on_press(Message::StartTutorial);
+		view_row = view_row.push(Container::new(labels_button).width(Length::Shrink));
+		view_row = view_row.push(Container::new(label_scheme_button).width(Length::Shrink));
+		view_row = view_row.push(Container::new(zoom_out_button).width(Length::Shrink));
+		view_row = view_row.push(Container::new(zoom_in_button).width(Length::Shrink));
+		view_row = view_row.push(Container::new(heatmap_button).width(Length::Shrink));
+		view_row = view_row.push(Container::new(difficulty_overlay_button).width(Length::Shrink));
+		view_row = view_row.push(Container::new(auto_solve_button).width(Length::Shrink));
+		view_row = view_row.push(Container::new(solve_animation_button).width(Length::Shrink));
+		view_row = view_row.push(Container::new(line_highlight_button).width(Length::Shrink));
+		view_row = view_row.push(Container::new(line_summaries_button).width(Length::Shrink));
+		view_row = view_row.push(Container::new(feasibility_probe_button).width(Length::Shrink));
+		view_row = view_row.push(Container::new(tutorial_button).width(Length::Shrink));
 
 		board = board.push(button_row);
+		if self.mode == Mode::Edit {
+			let mut sum_row = Row::new().spacing(10);
+			for (orientation, name) in [
+				(Orientation::Row, "Row sum"),
+				(Orientation::Column, "Column sum"),
+			] {
+				if let Some(first) = self.compartment_start(orientation) {
+					let sum = self
+						.str8ts
+						.compartment_sum(first, orientation)
+						.map_or_else(String::new, |sum| sum.to_string());
+					let sum_input = TextInput::new(name, &sum)
+						.on_input(move |v| Message::CompartmentSumChanged(orientation, v))
+						.width(Length::Fixed(100.0));
+					sum_row = sum_row.push(Text::new(name));
+					sum_row = sum_row.push(Container::new(sum_input).width(Length::Shrink));
+				}
+			}
+			board = board.push(sum_row);
+		}
+		board = board.push(view_row);
+		board = board.push(snapshot_row);
+		if let Some(import_panel) = self.import_panel() {
+			board = board.push(import_panel);
+		}
+		if let Some(playback_panel) = self.playback_panel() {
+			board = board.push(playback_panel);
+		}
+		if let Some(tutorial_panel) = self.tutorial_panel() {
+			board = board.push(tutorial_panel);
+		}
+		board = board.push(Text::new(self.status()));
+		if let Some(solve_statistics) = &self.solve_statistics {
+			board = board.push(Text::new(solve_statistics));
+		}
+		if let Some(check_message) = &self.check_message {
+			board = board.push(Text::new(check_message));
+		}
 
 		Container::new(board).into()
 	}
 }
+
+/// Returns the id of the text input of the given cell.
+fn cell_input_id(row: u8, col: u8) -> text_input::Id {
+	text_input::Id::new(format!("cell-{}-{}", row, col))
+}
+
+/// Interprets the content of a cell's text input after the user edited it.
+///
+/// As the input still contains the previous value, only the last character is considered: `"12"` becomes `2`.
+/// Returns `CellValue::Empty` if the input was cleared, and `None` if the edit is to be ignored
+/// (e.g. because a `0` or a letter was typed).
+fn parse_cell_input(input: &str) -> Option<CellValue> {
+	match input.trim().chars().last() {
+		None => Some(CellValue::Empty),
+		Some(c @ '1'..='9') => Some(CellValue::from(c)),
+		Some(_) => None,
+	}
+}
+
+/// Returns the message of a keyboard shortcut, if the key is one.
+///
+/// Focused text inputs only pass on the arrow keys, which move the selection between the cells: Left and Right in
+/// reading order, Up and Down within the column. Tab and Shift+Tab move the keyboard focus between all widgets, as in
+/// other applications. Escape leaves a text input, after which the remaining keys are shortcuts: C toggles the color of
+/// the selected cell, M switches the mode, K checks the board, Ctrl+Z undoes, Ctrl+Enter solves, and Enter returns to
+/// the selected cell. F8 and Shift+F8 jump between conflicts anywhere.
+fn shortcut(
+	key_code: KeyCode,
+	modifiers: keyboard::Modifiers,
+	status: event::Status,
+) -> Option<Message> {
+	let is_unfocused = status == event::Status::Ignored;
+	match key_code {
+		KeyCode::F8 if modifiers.shift() => Some(Message::PreviousConflict),
+		KeyCode::F8 => Some(Message::NextConflict),
+		KeyCode::Tab => Some(Message::FocusNext(!modifiers.shift())),
+		KeyCode::Left => Some(Message::MoveSelection(-1)),
+		KeyCode::Right => Some(Message::MoveSelection(1)),
+		KeyCode::Up => Some(Message::MoveSelection(-(SIZE as i8))),
+		KeyCode::Down => Some(Message::MoveSelection(SIZE as i8)),
+		_ if !is_unfocused => None,
+		KeyCode::Enter | KeyCode::NumpadEnter if modifiers.command() => {
+			Some(Message::SolveRequested)
+		}
+		KeyCode::Enter | KeyCode::NumpadEnter => Some(Message::FocusSelected),
+		KeyCode::Z if modifiers.command() => Some(Message::Undo),
+		_ if modifiers.command() || modifiers.alt() => None,
+		KeyCode::C => Some(Message::ToggleSelectedColor),
+		KeyCode::M => Some(Message::ToggleMode),
+		KeyCode::K => Some(Message::CheckRequested),
+		_ => None,
+	}
+}
+
+/// Describes the selected cell in words: its reference, color, value, the lengths of its compartments and the values
+/// their straights can still span (see `Str8ts::tightest_bounds`), whether turning a black cell white would merge
+/// compartments, and its first conflict, if any.
+///
+/// This is the part of the status line that changes as the selection moves, so it tells keyboard users where they are.
+fn describe_selection(
+	str8ts: &Str8ts,
+	(row, col): Position,
+	labels: LabelScheme,
+	conflicts: &[Conflict],
+) -> String {
+	let cell = str8ts.get_cell(row, col);
+	let mut description = format!("{} — ", labels.cell_reference(row, col));
+	match (cell.color, cell.value) {
+		(CellColor::White, CellValue::Empty) => description.push_str("white cell, empty"),
+		(CellColor::White, value) if cell.given => {
+			description.push_str(&format!("white cell, given {}", value))
+		}
+		(CellColor::White, value) => description.push_str(&format!("white cell, {}", value)),
+		(CellColor::Black, CellValue::Empty) => description.push_str("black cell"),
+		(CellColor::Black, value) => description.push_str(&format!("black cell, clue {}", value)),
+	}
+	if cell.color == CellColor::White {
+		let (row_length, col_length) = str8ts.compartment_length_at(row, col);
+		let (row_compartment, col_compartment) = str8ts.compartment_cells(row, col);
+		let bounds = |compartment: &[Position]| match str8ts.tightest_bounds(compartment) {
+			Some(bounds) => format!("within {}-{}", bounds.start(), bounds.end()),
+			None => String::from("without any straight left"),
+		};
+		description.push_str(&format!(
+			" — row run {} {}, col run {} {}",
+			row_length,
+			bounds(&row_compartment),
+			col_length,
+			bounds(&col_compartment)
+		));
+	} else if str8ts.would_merge_compartments(Str8ts::index(row, col)) {
+		description.push_str(" — turning it white merges compartments");
+	}
+	if let Some(conflict) = conflicts
+		.iter()
+		.find(|conflict| conflict.cells().contains(&(row, col)))
+	{
+		description.push_str(&format!(" — {}", conflict));
+	}
+	description
+}
+
+/// Returns what the message means to a running tutorial, if anything.
+fn tutorial_action(message: &Message) -> Option<TutorialAction> {
+	match message {
+		Message::TutorialContinue => Some(TutorialAction::Continue),
+		Message::CellInputChanged(row, col, value) => {
+			parse_cell_input(value).map(|value| TutorialAction::EnterValue((*row, *col), value))
+		}
+		Message::CellColorToggled(row, col) => Some(TutorialAction::ToggleColor((*row, *col))),
+		Message::FillRequested(_) => Some(TutorialAction::Fill),
+		Message::SolveRequested => Some(TutorialAction::Solve),
+		_ => None,
+	}
+}
+
+/// Parses the text of a compartment sum input. Returns `None` if it is empty or not a sum of 1 to 45.
+fn parse_sum_input(input: &str) -> Option<u8> {
+	input
+		.trim()
+		.parse()
+		.ok()
+		.filter(|sum| (1..=45).contains(sum))
+}
+
+/// Waits for the duration without blocking the executor, which runs the other commands and subscriptions meanwhile.
+///
+/// The timers of `iced::time` need one of iced's async runtimes, so this sleeps on a thread of its own instead.
+async fn delay(duration: Duration) {
+	let (sender, receiver) = oneshot::channel();
+	std::thread::spawn(move || {
+		std::thread::sleep(duration);
+		let _ = sender.send(());
+	});
+	let _ = receiver.await;
+}
+
+/// Produces the message after every period, like `iced::time::every`, until the subscription is dropped.
+fn every(id: &'static str, period: Duration, message: Message) -> Subscription<Message> {
+	subscription::unfold(id, (), move |()| {
+		let message = message.clone();
+		async move {
+			delay(period).await;
+			(message, ())
+		}
+	})
+}
+
+impl Str8tsEditor {
+	/// Probes the board for feasibility in the background, as far as the chosen probe goes.
+	///
+	/// The probe cannot be aborted, but its result is dropped if the board has changed by the time it arrives. The full
+	/// probe is limited by `FEASIBILITY_TIME_LIMIT`, and an undecided result counts as feasible.
+	fn schedule_feasibility_probe(&self) -> Command<Message> {
+		let revision = self.revision;
+		let str8ts = self.str8ts;
+		let probe = self.feasibility_probe;
+		if probe == FeasibilityProbe::Off {
+			return Command::none();
+		}
+		Command::perform(
+			async move {
+				str8ts.refuted_by_propagation()
+					|| probe == FeasibilityProbe::Full
+						&& str8ts.is_solvable_within(FEASIBILITY_TIME_LIMIT) == Some(false)
+			},
+			move |refuted| Message::FeasibilityProbed(revision, refuted),
+		)
+	}
+
+	/// Whether the cell is marked as the edit that left the board without a solution.
+	fn is_refuting_edit(&self, row: u8, col: u8) -> bool {
+		self.refuted == Some((self.revision, true)) && self.last_edited == Some((row, col))
+	}
+
+	/// Selects the given cell and focuses its text input.
+	fn select_cell(&mut self, row: u8, col: u8) -> Command<Message> {
+		self.selected = Some((row, col));
+		text_input::focus(cell_input_id(row, col))
+	}
+
+	/// Whether the cell is shaded for sharing its row or column with the selected cell (or being the selected cell).
+	fn is_highlighted(&self, row: u8, col: u8) -> bool {
+		let is_tutorial_cell = self
+			.tutorial
+			.as_ref()
+			.and_then(Tutorial::current_step)
+			.is_some_and(|step| step.highlighted.contains(&(row, col)));
+		is_tutorial_cell
+			|| self.highlight_lines
+				&& self.selected.is_some_and(|(selected_row, selected_col)| {
+					selected_row == row || selected_col == col
+				})
+	}
+
+	/// Returns the latest analysis of the board, if any has been computed yet.
+	fn analysis(&self) -> Option<&BoardAnalysis> {
+		self.analysis.as_ref().map(|(_, analysis)| analysis)
+	}
+
+	/// Fills in all solved values that are still waiting to be revealed.
+	fn finish_reveal(&mut self) {
+		for ((row, col), value) in self.reveal_queue.drain(..) {
+			self.str8ts.set_cell_value(row, col, value);
+		}
+	}
+
+	/// Analyzes the board in the background, so that the UI does not stutter on slower machines.
+	fn schedule_analysis(&mut self) -> Command<Message> {
+		let revision = self.revision;
+		self.analyzed.update_to(&self.str8ts);
+		let analyzed = self.analyzed.clone();
+		Command::perform(async move { analyze(&analyzed) }, move |analysis| {
+			Message::AnalysisComputed(revision, analysis)
+		})
+	}
+
+	/// Returns all cells involved in a conflict, in reading order.
+	fn conflict_cells(&self) -> Vec<Position> {
+		let Some(analysis) = self.analysis() else {
+			return Vec::new();
+		};
+		let mut cells: Vec<Position> = analysis
+			.conflicts
+			.iter()
+			.flat_map(|conflict| conflict.cells().to_vec())
+			.collect();
+		cells.sort();
+		cells.dedup();
+		cells
+	}
+
+	/// Solves the board in the background once it stayed unchanged for a while.
+	///
+	/// Without auto solve, the board is only checked for having a solution.
+	fn schedule_auto_solve(&self) -> Command<Message> {
+		let revision = self.revision;
+		Command::perform(
+			async move {
+				delay(AUTO_SOLVE_DELAY).await;
+				revision
+			},
+			Message::EditsSettled,
+		)
+	}
+
+	/// Creates the read-only view of the board's solution, or a placeholder if there is no unique solution.
+	fn solution_pane(&self) -> Element<'_, Message> {
+		let (revision, solution) = match &self.solution_view {
+			Some((revision, Uniqueness::Unique(solution))) => (*revision, solution),
+			Some((_, Uniqueness::Infeasible)) => return Text::new("No solution").into(),
+			Some((_, uniqueness @ Uniqueness::Multiple(..))) => {
+				let cells: Vec<String> = uniqueness
+					.differing_cells()
+					.into_iter()
+					.map(|index| {
+						let (row, col) = Str8ts::coords(index);
+						self.label_scheme.cell_reference(row, col)
+					})
+					.collect();
+				return Text::new(format!(
+					"Multiple solutions, differing in {}",
+					cells.join(", ")
+				))
+				.into();
+			}
+			None => return Text::new("Solving...").into(),
+		};
+		self.read_only_board(solution, revision != self.revision)
+	}
+
+	/// Creates a read-only view of the given board, grayed out if it is stale.
+	fn read_only_board(&self, board: &Str8ts, is_stale: bool) -> Element<'_, Message> {
+		let size = SOLUTION_CELL_SIZE * self.zoom;
+
+		let mut pane = Column::new();
+		for row in 0..SIZE as u8 {
+			let mut row_cells = Row::new();
+			for col in 0..SIZE as u8 {
+				let cell = board.get_cell(row, col);
+				row_cells = row_cells.push(
+					Container::new(Text::new(cell.value.to_string()).size(size * 0.6))
+						.width(Length::Fixed(size))
+						.height(Length::Fixed(size))
+						.center_x()
+						.center_y()
+						.style(theme::Container::Custom(Box::new(SolutionCellStyle {
+							is_black: cell.color == CellColor::Black,
+							is_stale,
+						}))),
+				);
+			}
+			pane = pane.push(row_cells);
+		}
+		pane.into()
+	}
+
+	/// Returns the board entered into the import panel, or the reason it cannot be read, if the panel is open.
+	fn imported_board(&self) -> Option<Result<Str8ts, ParseError>> {
+		self.import_text.as_ref().map(|text| text.parse())
+	}
+
+	/// Creates the import panel, with a preview of the entered board or the reason it cannot be read, if it is open.
+	fn import_panel(&self) -> Option<Element<'_, Message>> {
+		let text = self.import_text.as_ref()?;
+		let input = TextInput::new("Paste a board in the .str8ts format", text)
+			.on_input(Message::ImportTextChanged)
+			.on_submit(Message::ImportConfirmed)
+			.width(Length::Fixed(300.0));
+		let mut load_button = Button::new(Text::new("Load"));
+		let detected = match detect_format(text) {
+			Some(format) => format.to_string(),
+			None => "no known format".to_string(),
+		};
+		let mut details = Column::new()
+			.spacing(10)
+			.push(Text::new(format!("Detected: {}", detected)));
+		match self.imported_board() {
+			Some(Ok(board)) => {
+				load_button = load_button.on_press(Message::ImportConfirmed);
+				details = details.push(self.read_only_board(&board, false));
+			}
+			Some(Err(error)) => {
+				details = details.push(Text::new(error.to_string()).style(CONFLICT_COLOR));
+				// Shows the offending line, with a caret below the offending character.
+				if let Some(position) = error.position() {
+					let line = text.lines().nth(position.line - 1).unwrap_or_default();
+					let caret = format!("{}^", " ".repeat(position.column - 1));
+					details = details
+						.push(Text::new(line).font(Font::MONOSPACE))
+						.push(Text::new(caret).font(Font::MONOSPACE).style(CONFLICT_COLOR));
+				}
+			}
+			None => {}
+		}
+		let controls = Column::new().spacing(10).push(input).push(load_button);
+		Some(Row::new().spacing(20).push(controls).push(details).into())
+	}
+
+	/// Creates the playback panel, showing the board after the moves played so far, if it is open.
+	fn playback_panel(&self) -> Option<Element<'_, Message>> {
+		let playback = self.playback.as_ref()?;
+		let count = playback.replay.moves.len();
+		let status = match playback.played {
+			0 => format!("Start, {} moves to play", count),
+			played => {
+				let (time, last) = &playback.replay.moves[played - 1];
+				format!(
+					"Move {} of {} at {:.1}s: {}",
+					played,
+					count,
+					time.as_secs_f64(),
+					last
+				)
+			}
+		};
+		let play_button = Button::new(Text::new(if playback.is_playing {
+			"Pause"
+		} else {
+			"Play"
+		}))
+		.on_press(Message::TogglePlayback);
+		let mut step_button = Button::new(Text::new("Step"));
+		if playback.played < count {
+			step_button = step_button.on_press(Message::StepPlayback);
+		}
+		let close_button =
+			Button::new(Text::new("Close Playback")).on_press(Message::ClosePlayback);
+		let controls = Column::new().spacing(10).push(Text::new(status)).push(
+			Row::new()
+				.spacing(10)
+				.push(play_button)
+				.push(step_button)
+				.push(close_button),
+		);
+		Some(
+			Row::new()
+				.spacing(20)
+				.push(controls)
+				.push(self.read_only_board(&playback.boards[playback.played], false))
+				.into(),
+		)
+	}
+
+	/// Creates the panel showing the current step of the running tutorial, if any.
+	fn tutorial_panel(&self) -> Option<Element<'_, Message>> {
+		let tutorial = self.tutorial.as_ref()?;
+		let mut panel = Row::new().spacing(10);
+		if let Some(step) = tutorial.current_step() {
+			let (number, count) = tutorial.progress();
+			panel = panel.push(
+				Text::new(format!("Tutorial {}/{}: {}", number, count, step.text))
+					.width(Length::Fixed(600.0)),
+			);
+			// Other steps are completed by doing what they say.
+			if step.expected == TutorialAction::Continue {
+				panel =
+					panel.push(Button::new(Text::new("Next")).on_press(Message::TutorialContinue));
+			}
+		} else {
+			panel = panel.push(Text::new(
+				"Tutorial complete! Clear the board or paste a puzzle to get started.",
+			));
+		}
+		let end_button = Button::new(Text::new(if tutorial.is_finished() {
+			"Close Tutorial"
+		} else {
+			"End Tutorial"
+		}))
+		.on_press(Message::EndTutorial);
+		Some(panel.push(end_button).into())
+	}
+
+	/// Creates a row or column label, scaled to the current zoom level.
+	fn label(&self, label: String) -> Element<'_, Message> {
+		Text::new(label)
+			.size(LABEL_SIZE * self.zoom)
+			.width(Length::Fixed(LABEL_SIZE * self.zoom))
+			.horizontal_alignment(alignment::Horizontal::Center)
+			.into()
+	}
+
+	/// Returns how late the given cell was determined by the logical solver, together with a description of how,
+	/// if the difficulty overlay is shown and the cell was originally empty.
+	fn difficulty(&self, row: u8, col: u8) -> Option<(f32, String)> {
+		let logical_solution = self.logical_solution.as_ref()?;
+		let cell = self.str8ts.get_cell(row, col);
+		if !self.show_difficulty_overlay
+			|| cell.color == CellColor::Black
+			|| cell.value != CellValue::Empty
+		{
+			return None;
+		}
+		Some(match logical_solution.step_of(row, col) {
+			Some(index) => {
+				let step = &logical_solution.steps[index];
+				let last_index = logical_solution.steps.len().max(2) - 1;
+				(
+					index as f32 / last_index as f32,
+					format!("Step {}: {}", index + 1, explain(step)),
+				)
+			}
+			None => (1.0, String::from("Requires guessing")),
+		})
+	}
+
+	/// Returns the number of the (nested) trial the value of the cell was entered in, starting at 1, or 0 if it was
+	/// entered outside any trial.
+	///
+	/// A value entered in the n-th trial differs from the board as it was when each of the first n trials began.
+	fn trial_of(&self, row: u8, col: u8) -> usize {
+		let value = self.str8ts.get_cell(row, col).value;
+		self.trials
+			.iter()
+			.filter(|started| started.get_cell(row, col).value != value)
+			.count()
+	}
+
+	/// Whether the given cell is part of the puzzle, and thus cannot be changed in solve mode.
+	fn is_locked(&self, row: u8, col: u8) -> bool {
+		let cell = self.str8ts.get_cell(row, col);
+		self.mode == Mode::Solve && (cell.color == CellColor::Black || cell.given)
+	}
+
+	/// Returns the first cell of the selected cell's compartment in the given orientation, where its sum clue is stored.
+	///
+	/// Returns `None` if no cell or a black cell is selected.
+	fn compartment_start(&self, orientation: Orientation) -> Option<Position> {
+		let (row_compartment, col_compartment) = self.analyzed.compartments_of(self.selected?);
+		match orientation {
+			Orientation::Row => row_compartment,
+			Orientation::Column => col_compartment,
+		}
+		.map(|id| id.first)
+	}
+
+	/// Returns the text of the status bar, describing the active mode and the selected cell.
+	fn status(&self) -> String {
+		let mode = format!("{} mode", self.mode);
+		let Some((row, col)) = self.selected else {
+			// Without a selected cell, authors get an overview of the board instead.
+			return match self.mode {
+				Mode::Edit => format!("{} — {}", mode, self.str8ts.summary()),
+				Mode::Solve => mode,
+			};
+		};
+		let conflicts = self
+			.analysis()
+			.map_or(&[][..], |analysis| &analysis.conflicts);
+		format!(
+			"{} — {}",
+			mode,
+			describe_selection(&self.str8ts, (row, col), self.label_scheme, conflicts)
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts_tutorial::rules_board;
+
+	#[test]
+	fn selection_is_described_with_its_compartments() {
+		let mut str8ts = rules_board();
+		let describe = |str8ts: &Str8ts, position| {
+			describe_selection(str8ts, position, LabelScheme::Numeric, &[])
+		};
+		assert_eq!(
+			describe(&str8ts, (0, 1)),
+			"R1C2 — white cell, given 2 — row run 3 within 1-3, col run 3 within 1-3"
+		);
+		assert_eq!(
+			describe(&str8ts, (0, 2)),
+			"R1C3 — white cell, empty — row run 3 within 1-3, col run 3 within 1-9"
+		);
+		assert_eq!(describe(&str8ts, (2, 3)), "R3C4 — black cell, clue 4");
+		assert_eq!(describe(&str8ts, (3, 3)), "R4C4 — black cell");
+		str8ts.set_cell_color(1, 1, CellColor::Black);
+		assert_eq!(
+			describe(&str8ts, (1, 1)),
+			"R2C2 — black cell, clue 1 — turning it white merges compartments"
+		);
+		str8ts.set_cell_color(1, 1, CellColor::White);
+		str8ts.set_cell_value(0, 2, CellValue::Three);
+		assert_eq!(
+			describe_selection(&str8ts, (0, 2), LabelScheme::Letters, &[]),
+			"C1 — white cell, 3 — row run 3 within 1-3, col run 3 within 1-3"
+		);
+	}
+
+	#[test]
+	fn selection_description_names_its_first_conflict() {
+		let mut str8ts = rules_board();
+		str8ts.set_cell_value(0, 2, CellValue::One);
+		let conflicts = str8ts.conflicts();
+		assert_eq!(
+			describe_selection(&str8ts, (0, 2), LabelScheme::Numeric, &conflicts),
+			format!(
+				"R1C3 — white cell, 1 — row run 3 without any straight left, col run 3 within 1-3 — {}",
+				conflicts[0]
+			)
+		);
+		assert!(
+			!describe_selection(&str8ts, (2, 0), LabelScheme::Numeric, &conflicts)
+				.contains(&conflicts[0].to_string())
+		);
+	}
+
+	#[test]
+	fn arrow_keys_move_the_selection_and_tab_the_focus() {
+		let none = keyboard::Modifiers::default();
+		let focused = event::Status::Captured;
+		let message = |key_code, modifiers| shortcut(key_code, modifiers, focused);
+		assert!(matches!(
+			message(KeyCode::Left, none),
+			Some(Message::MoveSelection(-1))
+		));
+		assert!(matches!(
+			message(KeyCode::Right, none),
+			Some(Message::MoveSelection(1))
+		));
+		assert!(matches!(
+			message(KeyCode::Down, none),
+			Some(Message::MoveSelection(9))
+		));
+		assert!(matches!(
+			message(KeyCode::Tab, none),
+			Some(Message::FocusNext(true))
+		));
+		assert!(matches!(
+			message(KeyCode::Tab, keyboard::Modifiers::SHIFT),
+			Some(Message::FocusNext(false))
+		));
+		// Other keys only act as shortcuts outside of text inputs.
+		assert!(message(KeyCode::C, none).is_none());
+	}
+
+	#[test]
+	fn cell_input_keeps_only_the_last_digit() {
+		assert_eq!(parse_cell_input("12"), Some(CellValue::Two));
+		assert_eq!(parse_cell_input(""), Some(CellValue::Empty));
+		assert_eq!(parse_cell_input("0"), None);
+		assert_eq!(parse_cell_input("a"), None);
+
+		let (mut editor, _) = Str8tsEditor::new(());
+		let _ = editor.update(Message::CellInputChanged(0, 0, "12".into()));
+		assert_eq!(editor.str8ts.get_cell(0, 0).value, CellValue::Two);
+		// Ignored input leaves the cell as it is.
+		let _ = editor.update(Message::CellInputChanged(0, 0, "2a".into()));
+		assert_eq!(editor.str8ts.get_cell(0, 0).value, CellValue::Two);
+	}
+
+	#[test]
+	fn selected_lines_are_highlighted_until_toggled_off() {
+		let (mut editor, _) = Str8tsEditor::new(());
+		let _ = editor.update(Message::MoveSelection(0));
+		let _ = editor.update(Message::MoveSelection(42));
+		assert_eq!(editor.selected, Some((4, 6)));
+		let highlighted: Vec<Position> = editor
+			.str8ts
+			.cells()
+			.map(|(position, _)| position)
+			.filter(|(row, col)| editor.is_highlighted(*row, *col))
+			.collect();
+		let expected: Vec<Position> = editor
+			.str8ts
+			.cells()
+			.map(|(position, _)| position)
+			.filter(|(row, col)| *row == 4 || *col == 6)
+			.collect();
+		assert_eq!(highlighted, expected);
+
+		let _ = editor.update(Message::ToggleLineHighlight);
+		assert!(!editor.is_highlighted(4, 6));
+		assert!(!editor.is_highlighted(4, 0));
+	}
+
+	#[test]
+	fn black_and_conflicting_cells_keep_their_style_when_highlighted() {
+		let style = |is_black, is_conflicting| {
+			let style = CustomCellStyle {
+				is_black,
+				candidate_count: None,
+				difficulty: None,
+				is_conflicting,
+				is_diagonal: false,
+				is_highlighted: true,
+				trial: 0,
+			};
+			text_input::StyleSheet::active(&style, &Theme::Light)
+		};
+		let highlighted = style(false, false);
+		assert_eq!(
+			highlighted.background,
+			Background::Color(Color::from_rgb(0.88, 0.93, 1.0))
+		);
+		assert_eq!(highlighted.border_color, Color::BLACK);
+		assert_eq!(
+			style(true, false).background,
+			Background::Color(Color::BLACK)
+		);
+		assert_eq!(style(false, true).border_color, CONFLICT_COLOR);
+	}
+
+	#[test]
+	fn command_enter_solves_unless_a_text_input_has_the_key() {
+		let command = keyboard::Modifiers::COMMAND;
+		assert!(matches!(
+			shortcut(KeyCode::Enter, command, event::Status::Ignored),
+			Some(Message::SolveRequested)
+		));
+		assert!(matches!(
+			shortcut(KeyCode::NumpadEnter, command, event::Status::Ignored),
+			Some(Message::SolveRequested)
+		));
+		assert!(shortcut(KeyCode::Enter, command, event::Status::Captured).is_none());
+		assert!(matches!(
+			shortcut(
+				KeyCode::Enter,
+				keyboard::Modifiers::default(),
+				event::Status::Ignored
+			),
+			Some(Message::FocusSelected)
+		));
+	}
+}
@@ -0,0 +1,984 @@
+use std::fmt::Display;
+
+use crate::str8ts::{
+	CellColor, CellValue, LabelScheme, Orientation, Position, Str8ts, CELLS, SIZE,
+};
+use crate::str8ts_format::parse_cell_reference;
+use crate::str8ts_solver::{find_compartments, find_compartments_cols, find_compartments_rows};
+
+/// A technique the logical solver uses to determine the value of a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Technique {
+	/// The cell is the only empty one of its compartment, and its value fills the single gap in the straight.
+	StraightCompletion,
+	/// The cell has exactly one candidate left.
+	NakedSingle,
+	/// The value must be part of the cell's compartment, and the cell is the only one in the compartment that can hold it.
+	HiddenSingle,
+	/// The cell has exactly one candidate left once the candidates that fit no straight of their compartment (stranded
+	/// candidates) are ruled out.
+	StrandedElimination,
+	/// The cell has exactly one candidate left once the values a compartment surely contains (sure candidates) are ruled
+	/// out in the rest of its row or column, in addition to the stranded candidates if that technique is enabled.
+	SureCandidateElimination,
+}
+
+impl Technique {
+	/// All techniques, from the simplest to the hardest.
+	pub(crate) const ALL: [Technique; 5] = [
+		Technique::StraightCompletion,
+		Technique::NakedSingle,
+		Technique::HiddenSingle,
+		Technique::StrandedElimination,
+		Technique::SureCandidateElimination,
+	];
+
+	/// Returns the name of the technique in solution scripts, e.g. `naked-single`.
+	pub(crate) fn keyword(&self) -> &'static str {
+		match self {
+			Technique::StraightCompletion => "straight-completion",
+			Technique::NakedSingle => "naked-single",
+			Technique::HiddenSingle => "hidden-single",
+			Technique::StrandedElimination => "stranded-elimination",
+			Technique::SureCandidateElimination => "sure-candidate-elimination",
+		}
+	}
+
+	/// Returns how hard the technique is to spot for a human.
+	pub(crate) fn difficulty(&self) -> Difficulty {
+		match self {
+			Technique::StraightCompletion | Technique::NakedSingle => Difficulty::Easy,
+			Technique::HiddenSingle
+			| Technique::StrandedElimination
+			| Technique::SureCandidateElimination => Difficulty::Medium,
+		}
+	}
+}
+
+impl Display for Technique {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Technique::StraightCompletion => write!(f, "Straight completion"),
+			Technique::NakedSingle => write!(f, "Naked single"),
+			Technique::HiddenSingle => write!(f, "Hidden single"),
+			Technique::StrandedElimination => write!(f, "Stranded elimination"),
+			Technique::SureCandidateElimination => write!(f, "Sure candidate elimination"),
+		}
+	}
+}
+
+/// A selection of the techniques the logical solver may use.
+///
+/// Puzzle sources differ in the techniques they expect from players, so a puzzle rated with all techniques may be
+/// harder for a smaller selection. The default selection contains all techniques.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct TechniqueSet(u8);
+
+impl TechniqueSet {
+	/// The selection of all techniques.
+	pub(crate) const ALL: TechniqueSet = TechniqueSet((1 << Technique::ALL.len()) - 1);
+	/// The empty selection, with which the logical solver makes no step.
+	pub(crate) const NONE: TechniqueSet = TechniqueSet(0);
+
+	/// Returns the selection with the given technique added.
+	pub(crate) fn with(self, technique: Technique) -> TechniqueSet {
+		TechniqueSet(self.0 | 1 << technique as u8)
+	}
+
+	/// Returns the selection with the given technique removed.
+	pub(crate) fn without(self, technique: Technique) -> TechniqueSet {
+		TechniqueSet(self.0 & !(1 << technique as u8))
+	}
+
+	/// Whether the selection contains the given technique.
+	pub(crate) fn contains(&self, technique: Technique) -> bool {
+		self.0 & 1 << technique as u8 != 0
+	}
+
+	/// Returns the techniques of the selection, from the simplest to the hardest.
+	pub(crate) fn techniques(&self) -> impl Iterator<Item = Technique> + '_ {
+		Technique::ALL
+			.into_iter()
+			.filter(|technique| self.contains(*technique))
+	}
+}
+
+impl Default for TechniqueSet {
+	fn default() -> Self {
+		TechniqueSet::ALL
+	}
+}
+
+impl Display for TechniqueSet {
+	/// Lists the names of the techniques, e.g. "Straight completion, Naked single".
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if *self == TechniqueSet::NONE {
+			return write!(f, "None");
+		}
+		let names: Vec<String> = self
+			.techniques()
+			.map(|technique| technique.to_string())
+			.collect();
+		write!(f, "{}", names.join(", "))
+	}
+}
+
+/// A single deduction of the logical solver: the cell at `(row, col)` must contain `value`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LogicalStep {
+	pub(crate) technique: Technique,
+	pub(crate) row: u8,
+	pub(crate) col: u8,
+	pub(crate) value: CellValue,
+	/// The first and last cell of the compartment the deduction was made in, if it was made in a compartment.
+	pub(crate) compartment: Option<(Position, Position)>,
+}
+
+impl Display for LogicalStep {
+	/// Explains the step, e.g. "Straight completion: R4C2 must be 5 to complete compartment R4C1-R4C3".
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let labels = LabelScheme::default();
+		write!(
+			f,
+			"{}: {} must be {}",
+			self.technique,
+			labels.cell_reference(self.row, self.col),
+			self.value
+		)?;
+		if let Some(((first_row, first_col), (last_row, last_col))) = self.compartment {
+			let purpose = match self.technique {
+				Technique::StraightCompletion => "to complete",
+				_ => "in",
+			};
+			write!(
+				f,
+				" {} compartment {}-{}",
+				purpose,
+				labels.cell_reference(first_row, first_col),
+				labels.cell_reference(last_row, last_col)
+			)?;
+		}
+		Ok(())
+	}
+}
+
+/// The result of solving a str8ts game logically.
+#[derive(Debug, Clone)]
+pub(crate) struct LogicalSolution {
+	/// The board after applying all steps.
+	pub(crate) board: Str8ts,
+	/// The deductions made, in the order they were found.
+	pub(crate) steps: Vec<LogicalStep>,
+	/// The techniques the solver was allowed to use.
+	pub(crate) techniques: TechniqueSet,
+}
+
+/// How hard a str8ts game is for a human, judged by the techniques its logical solution requires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Difficulty {
+	/// Straight completions and naked singles suffice.
+	Easy,
+	/// Hidden singles, or ruling out stranded or sure candidates, are required.
+	Medium,
+	/// The logical solver gets stuck, so guessing is required.
+	Hard,
+}
+
+impl Display for Difficulty {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Difficulty::Easy => write!(f, "Easy"),
+			Difficulty::Medium => write!(f, "Medium"),
+			Difficulty::Hard => write!(f, "Hard"),
+		}
+	}
+}
+
+/// An estimate of how many ways the empty cells of a str8ts game can be filled, as the product of their numbers of
+/// candidates after the logical solver got stuck.
+///
+/// All sizes are given as log10 of the product. A solved board thus has a size of 0.0, while `f64::NEG_INFINITY`
+/// marks an empty cell without any candidate left (and thus a board without solution).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SearchSpace {
+	/// The size of the whole search space.
+	pub(crate) total: f64,
+	/// The size of the search space of each row.
+	pub(crate) rows: [f64; SIZE],
+	/// The size of the search space of each compartment, rows first and columns second.
+	pub(crate) compartments: Vec<(Vec<Position>, f64)>,
+}
+
+impl LogicalSolution {
+	/// Returns the index of the step that determined the given cell, if any.
+	pub(crate) fn step_of(&self, row: u8, col: u8) -> Option<usize> {
+		self.steps
+			.iter()
+			.position(|step| step.row == row && step.col == col)
+	}
+
+	/// Whether the steps filled every white cell, i.e. no guessing is required.
+	pub(crate) fn is_complete(&self) -> bool {
+		self.board
+			.into_iter()
+			.all(|cell| cell.color == CellColor::Black || cell.value != CellValue::Empty)
+	}
+
+	/// Rates the str8ts game by the hardest technique needed to solve it, see `GradingReport::difficulty`.
+	pub(crate) fn difficulty(&self) -> Difficulty {
+		self.grading_report().difficulty()
+	}
+
+	/// Summarizes which techniques the steps used, and how often.
+	pub(crate) fn grading_report(&self) -> GradingReport {
+		let technique_counts = Technique::ALL.map(|technique| {
+			let count = self
+				.steps
+				.iter()
+				.filter(|step| step.technique == technique)
+				.count();
+			(technique, count)
+		});
+		let mut longest_chain = 0;
+		let mut chain = 0;
+		for step in self.steps.iter() {
+			if step.technique.difficulty() > Difficulty::Easy {
+				chain += 1;
+				longest_chain = longest_chain.max(chain);
+			} else {
+				chain = 0;
+			}
+		}
+		GradingReport {
+			technique_counts,
+			longest_chain,
+			requires_guessing: !self.is_complete(),
+			techniques: self.techniques,
+		}
+	}
+}
+
+/// How a str8ts game was solved logically: how often each technique was used, and whether guessing was required.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GradingReport {
+	/// The number of steps made with each technique, in the order of `Technique::ALL`.
+	pub(crate) technique_counts: [(Technique, usize); 5],
+	/// The longest run of consecutive steps that needed more than an easy technique.
+	pub(crate) longest_chain: usize,
+	/// Whether the logical solver got stuck before filling the board.
+	pub(crate) requires_guessing: bool,
+	/// The techniques the logical solver was allowed to use, which the rating depends on.
+	pub(crate) techniques: TechniqueSet,
+}
+
+impl GradingReport {
+	/// Rates the str8ts game by the hardest technique used, or as hard if guessing is required.
+	pub(crate) fn difficulty(&self) -> Difficulty {
+		if self.requires_guessing {
+			return Difficulty::Hard;
+		}
+		self.technique_counts
+			.iter()
+			.filter(|(_, count)| *count > 0)
+			.map(|(technique, _)| technique.difficulty())
+			.max()
+			.unwrap_or(Difficulty::Easy)
+	}
+}
+
+impl Display for GradingReport {
+	/// Writes the report as a small table, followed by the rating.
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for (technique, count) in self.technique_counts.iter() {
+			writeln!(f, "{:<26} {:>3}", technique.to_string(), count)?;
+		}
+		writeln!(f, "{:<26} {:>3}", "Longest hard chain", self.longest_chain)?;
+		writeln!(
+			f,
+			"{:<26} {:>3}",
+			"Guessing required",
+			if self.requires_guessing { "yes" } else { "no" }
+		)?;
+		writeln!(
+			f,
+			"{:<26} {:>3}",
+			"Difficulty",
+			self.difficulty().to_string()
+		)?;
+		write!(f, "{:<26} {}", "Techniques", self.techniques)
+	}
+}
+
+impl Str8ts {
+	/// Solve the str8ts game the way a human would, one deduction at a time.
+	///
+	/// The solver repeatedly applies the simplest technique that makes progress, so the order of the steps reflects the
+	/// order in which a human would find them. It stops once no technique applies anymore, which might leave white
+	/// cells empty if the puzzle requires guessing (or has no unique solution).
+	pub(crate) fn solve_logically(&self) -> LogicalSolution {
+		self.solve_logically_with(TechniqueSet::ALL)
+	}
+
+	/// Solve the str8ts game logically like `solve_logically`, using only the given techniques.
+	pub(crate) fn solve_logically_with(&self, techniques: TechniqueSet) -> LogicalSolution {
+		let mut board = *self;
+		let mut steps = Vec::new();
+		while let Some(step) = techniques
+			.techniques()
+			.find_map(|technique| board.find_step(technique, techniques))
+		{
+			board.set_cell_value(step.row, step.col, step.value);
+			steps.push(step);
+		}
+		LogicalSolution {
+			board,
+			steps,
+			techniques,
+		}
+	}
+
+	/// Finds a step made with the given technique, using the other techniques of the selection as far as the technique
+	/// builds on them.
+	fn find_step(&self, technique: Technique, techniques: TechniqueSet) -> Option<LogicalStep> {
+		match technique {
+			Technique::StraightCompletion => self.find_straight_completion(),
+			Technique::NakedSingle => self.find_naked_single(),
+			Technique::HiddenSingle => self.find_hidden_single(),
+			Technique::StrandedElimination | Technique::SureCandidateElimination => {
+				self.find_elimination_single(technique, techniques)
+			}
+		}
+	}
+
+	/// Returns the steps of the logical solution as a script a replay tool can follow, one step per line, such as
+	/// `naked-single R4C7 5`. `parse_solution_script` reads it back.
+	///
+	/// Returns None if the logical solver gets stuck, as the script would not solve the puzzle.
+	pub(crate) fn export_solution_script(&self) -> Option<String> {
+		let solution = self.solve_logically();
+		if !solution.is_complete() {
+			return None;
+		}
+		let labels = LabelScheme::default();
+		Some(
+			solution
+				.steps
+				.iter()
+				.map(|step| {
+					format!(
+						"{} {} {}\n",
+						step.technique.keyword(),
+						labels.cell_reference(step.row, step.col),
+						step.value
+					)
+				})
+				.collect(),
+		)
+	}
+
+	/// Plays the steps of a script written by `export_solution_script` on the str8ts game, and returns the board they
+	/// lead to.
+	///
+	/// Every step has to fill an empty white cell with a value `place_check` accepts, or the script is rejected with
+	/// the number of the offending step. Whether the board is solved in the end is left to the caller.
+	pub(crate) fn play_solution_script(&self, script: &str) -> Result<Str8ts, String> {
+		let mut board = *self;
+		for (number, step) in parse_solution_script(script)?.into_iter().enumerate() {
+			let cell = board.get_cell(step.row, step.col);
+			if cell.color == CellColor::Black || cell.value != CellValue::Empty {
+				return Err(format!(
+					"step {}: {} is not an empty white cell",
+					number + 1,
+					LabelScheme::default().cell_reference(step.row, step.col)
+				));
+			}
+			board
+				.place_check(step.row, step.col, step.value)
+				.map_err(|error| format!("step {}: {}", number + 1, error))?;
+			board.set_cell_value(step.row, step.col, step.value);
+		}
+		Ok(board)
+	}
+
+	/// Solves the str8ts game logically and summarizes the techniques it took, see `GradingReport`.
+	pub(crate) fn grading_report(&self) -> GradingReport {
+		self.solve_logically().grading_report()
+	}
+
+	/// Like `grading_report`, but solves the str8ts game with the given techniques only.
+	pub(crate) fn grading_report_with(&self, techniques: TechniqueSet) -> GradingReport {
+		self.solve_logically_with(techniques).grading_report()
+	}
+
+	/// Whether the logical solver alone fills the whole board, without any search by the MILP solver.
+	pub(crate) fn solves_by_propagation(&self) -> bool {
+		self.solve_logically().is_complete()
+	}
+
+	/// Whether the logical solver proves that the str8ts game has no solution: after it gets stuck, the board has a
+	/// conflict or an empty white cell without candidates.
+	///
+	/// This is much cheaper than solving, but one-sided: if it returns false, the str8ts game may still have no solution.
+	pub(crate) fn refuted_by_propagation(&self) -> bool {
+		let board = self.solve_logically().board;
+		!board.conflicts().is_empty()
+			|| board
+				.empty_white_cells()
+				.any(|((row, col), _)| board.candidates(row, col).is_empty())
+	}
+
+	/// Estimates the size of the search space left after the logical solver, see `SearchSpace`.
+	///
+	/// This is a cheap proxy of how hard the str8ts game is, without rating it by its techniques.
+	pub(crate) fn search_space(&self) -> SearchSpace {
+		let board = self.solve_logically().board;
+		let mut sizes = [[0.0; SIZE]; SIZE];
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				let cell = board.get_cell(row, col);
+				if cell.color == CellColor::White && cell.value == CellValue::Empty {
+					sizes[row as usize][col as usize] =
+						(board.candidates(row, col).len() as f64).log10();
+				}
+			}
+		}
+		let rows = sizes.map(|row| row.iter().sum());
+		let compartments = find_compartments(&board)
+			.into_iter()
+			.map(|compartment| {
+				let cells: Vec<Position> = compartment
+					.iter()
+					.map(|index| Str8ts::coords(*index))
+					.collect();
+				let size = cells
+					.iter()
+					.map(|(row, col)| sizes[*row as usize][*col as usize])
+					.sum();
+				(cells, size)
+			})
+			.collect();
+		SearchSpace {
+			total: rows.iter().sum(),
+			rows,
+			compartments,
+		}
+	}
+
+	/// Returns every empty white cell that has exactly one candidate, as its index together with that candidate.
+	pub(crate) fn naked_singles(&self) -> Vec<(u8, CellValue)> {
+		(0..CELLS as u8)
+			.filter_map(|index| {
+				let (row, col) = Str8ts::coords(index);
+				match self.candidates(row, col)[..] {
+					[value] => Some((index, value)),
+					_ => None,
+				}
+			})
+			.collect()
+	}
+
+	/// Returns every empty white cell, as its index, that the value can still be placed into.
+	///
+	/// A value that must be part of a compartment, but can only be placed into one of its cells, is a hidden single.
+	pub(crate) fn placements_for_value(&self, value: CellValue) -> Vec<u8> {
+		self.empty_white_cells()
+			.filter(|((row, col), _)| self.candidates(*row, *col).contains(&value))
+			.map(|((row, col), _)| Str8ts::index(row, col))
+			.collect()
+	}
+
+	/// Find the first compartment with a single empty cell, whose placed values leave exactly one gap in the straight.
+	///
+	/// For example, a compartment of length 3 holding 4 and 6 forces the 5 into its empty cell.
+	fn find_straight_completion(&self) -> Option<LogicalStep> {
+		for compartment in find_compartments(self) {
+			let compartment: Vec<Position> = compartment
+				.iter()
+				.map(|index| Str8ts::coords(*index))
+				.collect();
+			let mut empty = compartment
+				.iter()
+				.filter(|(row, col)| self.get_cell(*row, *col).value == CellValue::Empty);
+			let (Some((row, col)), None) = (empty.next(), empty.next()) else {
+				continue;
+			};
+			let placed: Vec<u8> = compartment
+				.iter()
+				.map(|(row, col)| u8::from(self.get_cell(*row, *col).value))
+				.filter(|value| *value != 0)
+				.collect();
+			let (Some(min), Some(max)) = (placed.iter().min(), placed.iter().max()) else {
+				continue;
+			};
+			if usize::from(max - min) != compartment.len() - 1 {
+				continue;
+			}
+			let mut gaps = (*min..=*max).filter(|value| !placed.contains(value));
+			if let (Some(value), None) = (gaps.next(), gaps.next()) {
+				let value = CellValue::from(value);
+				if self.candidates(*row, *col).contains(&value) {
+					return Some(LogicalStep {
+						technique: Technique::StraightCompletion,
+						row: *row,
+						col: *col,
+						value,
+						compartment: Some((compartment[0], compartment[compartment.len() - 1])),
+					});
+				}
+			}
+		}
+		None
+	}
+
+	/// Find the first empty white cell (in reading order) that has exactly one candidate.
+	fn find_naked_single(&self) -> Option<LogicalStep> {
+		self.naked_singles().first().map(|(index, value)| {
+			let (row, col) = Str8ts::coords(*index);
+			LogicalStep {
+				technique: Technique::NakedSingle,
+				row,
+				col,
+				value: *value,
+				compartment: None,
+			}
+		})
+	}
+
+	/// Find the first value that must be part of a compartment, but can only be placed into one of its cells.
+	fn find_hidden_single(&self) -> Option<LogicalStep> {
+		// The cells each value can still be placed into, by value, looked up once it is needed.
+		let mut placements: [Option<Vec<u8>>; SIZE] = Default::default();
+		for compartment in find_compartments(self) {
+			let compartment: Vec<Position> = compartment
+				.iter()
+				.map(|index| Str8ts::coords(*index))
+				.collect();
+			for value in self.sure_candidates(&compartment) {
+				let number_value: usize = value.into();
+				let placements = placements[number_value - 1]
+					.get_or_insert_with(|| self.placements_for_value(value));
+				let mut cells = compartment
+					.iter()
+					.filter(|(row, col)| placements.contains(&Str8ts::index(*row, *col)));
+				if let (Some((row, col)), None) = (cells.next(), cells.next()) {
+					return Some(LogicalStep {
+						technique: Technique::HiddenSingle,
+						row: *row,
+						col: *col,
+						value,
+						compartment: Some((compartment[0], compartment[compartment.len() - 1])),
+					});
+				}
+			}
+		}
+		None
+	}
+
+	/// Find the first empty white cell (in reading order) with several candidates, but exactly one after eliminating
+	/// the stranded candidates of every compartment (if `StrandedElimination` is selected), and, for
+	/// `SureCandidateElimination`, the sure candidates of every compartment from the rest of its row or column.
+	fn find_elimination_single(
+		&self,
+		technique: Technique,
+		techniques: TechniqueSet,
+	) -> Option<LogicalStep> {
+		let mut candidates: Vec<Vec<CellValue>> = (0..CELLS as u8)
+			.map(|index| {
+				let (row, col) = Str8ts::coords(index);
+				self.candidates(row, col)
+			})
+			.collect();
+		let before: Vec<usize> = candidates.iter().map(Vec::len).collect();
+		let mut eliminate = |(row, col): Position, value: CellValue| {
+			candidates[Str8ts::index(row, col) as usize].retain(|candidate| *candidate != value);
+		};
+
+		let compartments = find_compartments_rows(self)
+			.into_iter()
+			.map(|compartment| (compartment, Orientation::Row))
+			.chain(
+				find_compartments_cols(self)
+					.into_iter()
+					.map(|compartment| (compartment, Orientation::Column)),
+			);
+		for (compartment, orientation) in compartments {
+			let compartment: Vec<Position> = compartment.into_iter().map(Str8ts::coords).collect();
+			if techniques.contains(Technique::StrandedElimination) {
+				for (position, value) in self.stranded_candidates(&compartment) {
+					eliminate(position, value);
+				}
+			}
+			if technique != Technique::SureCandidateElimination {
+				continue;
+			}
+			let (row, col) = compartment[0];
+			let line = (0..SIZE as u8).map(|i| match orientation {
+				Orientation::Row => (row, i),
+				Orientation::Column => (i, col),
+			});
+			for value in self.sure_candidates(&compartment) {
+				for position in line
+					.clone()
+					.filter(|position| !compartment.contains(position))
+				{
+					eliminate(position, value);
+				}
+			}
+		}
+
+		candidates
+			.iter()
+			.zip(before)
+			.position(|(candidates, before)| candidates.len() == 1 && before > 1)
+			.map(|index| {
+				let (row, col) = Str8ts::coords(index as u8);
+				LogicalStep {
+					technique,
+					row,
+					col,
+					value: candidates[index][0],
+					compartment: None,
+				}
+			})
+	}
+}
+
+/// Reads the steps of a script written by `Str8ts::export_solution_script`. Blank lines are skipped.
+///
+/// The script does not record compartments, so the steps have none.
+pub(crate) fn parse_solution_script(script: &str) -> Result<Vec<LogicalStep>, String> {
+	script
+		.lines()
+		.filter(|line| !line.trim().is_empty())
+		.map(|line| {
+			let invalid = || format!("invalid step '{}'", line.trim());
+			let [keyword, cell, value] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+				return Err(invalid());
+			};
+			let technique = Technique::ALL
+				.into_iter()
+				.find(|technique| technique.keyword() == keyword)
+				.ok_or_else(invalid)?;
+			let (row, col) = parse_cell_reference(cell).ok_or_else(invalid)?;
+			let value = match value.parse::<u8>() {
+				Ok(value @ 1..=9) => CellValue::from(value),
+				_ => return Err(invalid()),
+			};
+			Ok(LogicalStep {
+				technique,
+				row,
+				col,
+				value,
+				compartment: None,
+			})
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts_candidates::stranded_board;
+
+	/// Two white cells, each completing a straight between the givens on either side.
+	fn two_singles_board() -> Str8ts {
+		str8ts![
+			"1.3######",
+			"#########",
+			"7.9######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		]
+	}
+
+	#[test]
+	fn naked_singles_finds_both_forced_cells() {
+		assert_eq!(
+			two_singles_board().naked_singles(),
+			vec![
+				(Str8ts::index(0, 1), CellValue::Two),
+				(Str8ts::index(2, 1), CellValue::Eight),
+			]
+		);
+	}
+
+	#[test]
+	fn forced_cells_are_solved_by_propagation() {
+		assert!(two_singles_board().solves_by_propagation());
+	}
+
+	#[test]
+	fn open_block_requires_search() {
+		let str8ts = str8ts![
+			"..#######",
+			"..#######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert!(!str8ts.solves_by_propagation());
+		assert!(str8ts.solve().is_some());
+	}
+
+	#[test]
+	fn search_space_multiplies_the_candidates_left() {
+		assert_eq!(two_singles_board().search_space().total, 0.0);
+
+		let open_block = str8ts![
+			"..#######",
+			"..#######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let search_space = open_block.search_space();
+		let cell = 9f64.log10();
+		assert!((search_space.total - 4.0 * cell).abs() < 1e-9);
+		assert!((search_space.rows[0] - 2.0 * cell).abs() < 1e-9);
+		assert_eq!(search_space.rows[2], 0.0);
+		assert_eq!(search_space.compartments.len(), 4);
+		assert!(search_space
+			.compartments
+			.iter()
+			.all(|(cells, size)| cells.len() == 2 && (size - 2.0 * cell).abs() < 1e-9));
+	}
+
+	#[test]
+	fn cell_without_candidates_empties_the_search_space() {
+		let str8ts = str8ts![
+			".abcdefgh",
+			"i########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert_eq!(str8ts.search_space().total, f64::NEG_INFINITY);
+	}
+
+	#[test]
+	fn straight_completion_fills_the_gap_between_4_and_6() {
+		let str8ts = str8ts![
+			"#########",
+			"#########",
+			"#########",
+			"4.6######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let step = str8ts.solve_logically().steps[0];
+		assert_eq!(
+			step,
+			LogicalStep {
+				technique: Technique::StraightCompletion,
+				row: 3,
+				col: 1,
+				value: CellValue::Five,
+				compartment: Some(((3, 0), (3, 2))),
+			}
+		);
+		assert_eq!(
+			step.to_string(),
+			"Straight completion: R4C2 must be 5 to complete compartment R4C1-R4C3"
+		);
+	}
+
+	#[test]
+	fn placements_for_value_lists_the_cells_it_fits() {
+		let str8ts = two_singles_board();
+		assert_eq!(
+			str8ts.placements_for_value(CellValue::Two),
+			vec![Str8ts::index(0, 1)]
+		);
+		assert_eq!(
+			str8ts.placements_for_value(CellValue::Eight),
+			vec![Str8ts::index(2, 1)]
+		);
+		assert!(str8ts.placements_for_value(CellValue::Five).is_empty());
+	}
+
+	#[test]
+	fn only_straight_completions_and_naked_singles_are_easy() {
+		let easy: Vec<Technique> = Technique::ALL
+			.into_iter()
+			.filter(|technique| technique.difficulty() == Difficulty::Easy)
+			.collect();
+		assert_eq!(
+			easy,
+			vec![Technique::StraightCompletion, Technique::NakedSingle]
+		);
+		assert!(Technique::ALL
+			.iter()
+			.all(|technique| technique.difficulty() < Difficulty::Hard));
+	}
+
+	#[test]
+	fn getting_stuck_is_hard() {
+		assert_eq!(
+			two_singles_board().grading_report().difficulty(),
+			Difficulty::Easy
+		);
+		let str8ts = str8ts![
+			"..#######",
+			"..#######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let report = str8ts.grading_report();
+		assert!(report.requires_guessing);
+		assert_eq!(report.difficulty(), Difficulty::Hard);
+	}
+
+	#[test]
+	fn solution_script_round_trips() {
+		let str8ts = two_singles_board();
+		let script = str8ts.export_solution_script().unwrap();
+		assert_eq!(
+			script,
+			"straight-completion R1C2 2\nstraight-completion R3C2 8\n"
+		);
+		let steps: Vec<LogicalStep> = str8ts
+			.solve_logically()
+			.steps
+			.into_iter()
+			.map(|step| LogicalStep {
+				compartment: None,
+				..step
+			})
+			.collect();
+		assert_eq!(parse_solution_script(&script), Ok(steps));
+		assert_eq!(
+			parse_solution_script("naked-single R1C2 0"),
+			Err("invalid step 'naked-single R1C2 0'".to_string())
+		);
+	}
+
+	#[test]
+	fn playing_the_exported_script_solves_the_board() {
+		let str8ts = two_singles_board();
+		let script = str8ts.export_solution_script().unwrap();
+		assert_eq!(
+			str8ts.play_solution_script(&script),
+			Ok(str8ts.solve_logically().board)
+		);
+		assert_eq!(
+			str8ts.play_solution_script("naked-single R1C1 2"),
+			Err("step 1: R1C1 is not an empty white cell".to_string())
+		);
+		assert!(str8ts
+			.play_solution_script("naked-single R1C2 2\nnaked-single R3C2 9")
+			.unwrap_err()
+			.starts_with("step 2: "));
+	}
+
+	#[test]
+	fn no_script_is_exported_when_stuck() {
+		let str8ts = str8ts![
+			"..#######",
+			"..#######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert_eq!(str8ts.export_solution_script(), None);
+	}
+
+	#[test]
+	fn technique_sets_toggle_single_techniques() {
+		let set = TechniqueSet::NONE
+			.with(Technique::NakedSingle)
+			.with(Technique::HiddenSingle);
+		assert!(set.contains(Technique::NakedSingle));
+		assert!(!set.contains(Technique::StraightCompletion));
+		assert_eq!(set.to_string(), "Naked single, Hidden single");
+		assert_eq!(
+			set.without(Technique::HiddenSingle)
+				.without(Technique::NakedSingle),
+			TechniqueSet::NONE
+		);
+		assert_eq!(TechniqueSet::NONE.to_string(), "None");
+	}
+
+	#[test]
+	fn solver_only_uses_the_selected_techniques() {
+		let str8ts = two_singles_board();
+		let without_completion =
+			str8ts.solve_logically_with(TechniqueSet::ALL.without(Technique::StraightCompletion));
+		assert!(without_completion.is_complete());
+		assert!(without_completion
+			.steps
+			.iter()
+			.all(|step| step.technique != Technique::StraightCompletion));
+		let report = str8ts.grading_report_with(TechniqueSet::NONE);
+		assert!(report.requires_guessing);
+		assert_eq!(report.techniques, TechniqueSet::NONE);
+	}
+
+	#[test]
+	fn sure_candidates_are_ruled_out_in_the_rest_of_the_row() {
+		// The clues below R1C1 leave it with 5 and 9, and the compartment R1C4-R1C9 surely contains 4, 5 and 6.
+		let str8ts = str8ts![
+			"..#......",
+			"a########",
+			"b########",
+			"c########",
+			"d########",
+			"f########",
+			"g########",
+			"h########",
+			"#########",
+		];
+		assert_eq!(
+			str8ts.solve_logically().steps.first(),
+			Some(&LogicalStep {
+				technique: Technique::SureCandidateElimination,
+				row: 0,
+				col: 0,
+				value: CellValue::Nine,
+				compartment: None,
+			})
+		);
+	}
+
+	#[test]
+	fn stranded_candidates_are_ruled_out() {
+		let str8ts = stranded_board();
+		assert_eq!(
+			str8ts.solve_logically().steps.first(),
+			Some(&LogicalStep {
+				technique: Technique::StrandedElimination,
+				row: 0,
+				col: 0,
+				value: CellValue::Three,
+				compartment: None,
+			})
+		);
+	}
+}
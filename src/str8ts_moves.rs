@@ -0,0 +1,437 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::str8ts::{Cell, CellColor, CellValue, LabelScheme, Position, Str8ts, SIZE};
+use crate::str8ts_format::parse_cell_reference;
+
+/// The value of a cell together with whether it is given, as moves record it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct CellContent {
+	pub(crate) value: CellValue,
+	pub(crate) given: bool,
+}
+
+impl CellContent {
+	/// Returns the value and given flag of the cell.
+	pub(crate) fn of(cell: Cell) -> CellContent {
+		CellContent {
+			value: cell.value,
+			given: cell.given,
+		}
+	}
+}
+
+/// Writes the value as a number (`0` if empty), followed by `*` if it is given.
+impl Display for CellContent {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", u8::from(self.value))?;
+		if self.given {
+			write!(f, "*")?;
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for CellContent {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (value, given) = match s.strip_suffix('*') {
+			Some(value) => (value, true),
+			None => (s, false),
+		};
+		let value: u8 = value.parse().map_err(|_| ())?;
+		if value > 9 {
+			return Err(());
+		}
+		Ok(CellContent {
+			value: CellValue::from(value),
+			given,
+		})
+	}
+}
+
+/// A single change made to a str8ts game, recorded so it can be undone or replayed.
+///
+/// Every move records the values it replaces, along with whether they were given, so it can be inverted, and so
+/// replaying it on a board it was not made on is detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Move {
+	/// The content of the cell changed from `from` to `to`.
+	SetValue {
+		position: Position,
+		from: CellContent,
+		to: CellContent,
+	},
+	/// The content `previous` was removed from the cell.
+	ClearValue {
+		position: Position,
+		previous: CellContent,
+	},
+	/// The color of the cell was toggled.
+	ToggleColor { position: Position },
+	/// The values of several cells were removed at once, such as when clearing the solution.
+	BulkClear {
+		cleared: Vec<(Position, CellContent)>,
+	},
+	/// The values of several cells changed at once, such as when filling in the solution.
+	ApplySolution {
+		diff: Vec<(Position, CellContent, CellContent)>,
+	},
+}
+
+/// The reason a list of moves could not be replayed.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ReplayError {
+	/// The move with the given index does not fit the board it was replayed on.
+	Diverged { index: usize },
+}
+
+impl Display for ReplayError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ReplayError::Diverged { index } => {
+				write!(f, "move {} does not fit the board", index + 1)
+			}
+		}
+	}
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Move {
+	/// Returns the move that undoes this one.
+	pub(crate) fn invert(&self) -> Move {
+		match self {
+			Move::SetValue { position, from, to } => Move::SetValue {
+				position: *position,
+				from: *to,
+				to: *from,
+			},
+			Move::ClearValue { position, previous } => Move::SetValue {
+				position: *position,
+				from: CellContent::default(),
+				to: *previous,
+			},
+			Move::ToggleColor { position } => Move::ToggleColor {
+				position: *position,
+			},
+			Move::BulkClear { cleared } => Move::ApplySolution {
+				diff: cleared
+					.iter()
+					.map(|(position, previous)| (*position, CellContent::default(), *previous))
+					.collect(),
+			},
+			Move::ApplySolution { diff } => Move::ApplySolution {
+				diff: diff
+					.iter()
+					.map(|(position, from, to)| (*position, *to, *from))
+					.collect(),
+			},
+		}
+	}
+
+	/// Applies the move to the board.
+	///
+	/// Returns false, leaving the board unchanged, if the values the move replaces are not those of the board. Only the
+	/// values are compared, but the cells take on the given flags of their new contents.
+	pub(crate) fn apply(&self, board: &mut Str8ts) -> bool {
+		let changes: Vec<(Position, CellContent, CellContent)> = match self {
+			Move::SetValue { position, from, to } => vec![(*position, *from, *to)],
+			Move::ClearValue { position, previous } => {
+				vec![(*position, *previous, CellContent::default())]
+			}
+			Move::ToggleColor { position } => {
+				let (row, col) = *position;
+				board.toggle_cell_color(row, col);
+				return true;
+			}
+			Move::BulkClear { cleared } => cleared
+				.iter()
+				.map(|(position, previous)| (*position, *previous, CellContent::default()))
+				.collect(),
+			Move::ApplySolution { diff } => diff.clone(),
+		};
+		if changes
+			.iter()
+			.any(|((row, col), from, _)| board.get_cell(*row, *col).value != from.value)
+		{
+			return false;
+		}
+		for ((row, col), _, to) in changes {
+			board.set_cell_value(row, col, to.value);
+			board.set_cell_given(row, col, to.given);
+		}
+		true
+	}
+}
+
+/// Writes the move as a single line, such as `set R4C7 0 5`, which `from_str` reads back.
+///
+/// Empty values are written as `0`, and given values are followed by `*`, as in `clear R4C7 5*`. The cells of bulk
+/// moves are listed as `R4C7=5` or `R4C7=0>5`.
+impl Display for Move {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let labels = LabelScheme::default();
+		let reference = |(row, col): Position| labels.cell_reference(row, col);
+		match self {
+			Move::SetValue { position, from, to } => {
+				write!(f, "set {} {} {}", reference(*position), from, to)
+			}
+			Move::ClearValue { position, previous } => {
+				write!(f, "clear {} {}", reference(*position), previous)
+			}
+			Move::ToggleColor { position } => write!(f, "toggle {}", reference(*position)),
+			Move::BulkClear { cleared } => {
+				write!(f, "clear-all")?;
+				for (position, previous) in cleared {
+					write!(f, " {}={}", reference(*position), previous)?;
+				}
+				Ok(())
+			}
+			Move::ApplySolution { diff } => {
+				write!(f, "apply")?;
+				for (position, from, to) in diff {
+					write!(f, " {}={}>{}", reference(*position), from, to)?;
+				}
+				Ok(())
+			}
+		}
+	}
+}
+
+impl FromStr for Move {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || format!("invalid move '{}'", s);
+		let value = |part: &str| part.parse::<CellContent>().ok();
+		let mut parts = s.split_whitespace();
+		let kind = parts.next().ok_or_else(invalid)?;
+		let parts: Vec<&str> = parts.collect();
+		let parsed = match (kind, &parts[..]) {
+			("set", [cell, from, to]) => Some(Move::SetValue {
+				position: parse_cell_reference(cell).ok_or_else(invalid)?,
+				from: value(from).ok_or_else(invalid)?,
+				to: value(to).ok_or_else(invalid)?,
+			}),
+			("clear", [cell, previous]) => Some(Move::ClearValue {
+				position: parse_cell_reference(cell).ok_or_else(invalid)?,
+				previous: value(previous).ok_or_else(invalid)?,
+			}),
+			("toggle", [cell]) => Some(Move::ToggleColor {
+				position: parse_cell_reference(cell).ok_or_else(invalid)?,
+			}),
+			("clear-all", cells) => cells
+				.iter()
+				.map(|part| {
+					let (cell, previous) = part.split_once('=')?;
+					Some((parse_cell_reference(cell)?, value(previous)?))
+				})
+				.collect::<Option<Vec<_>>>()
+				.map(|cleared| Move::BulkClear { cleared }),
+			("apply", cells) => cells
+				.iter()
+				.map(|part| {
+					let (cell, change) = part.split_once('=')?;
+					let (from, to) = change.split_once('>')?;
+					Some((parse_cell_reference(cell)?, value(from)?, value(to)?))
+				})
+				.collect::<Option<Vec<_>>>()
+				.map(|diff| Move::ApplySolution { diff }),
+			_ => None,
+		};
+		parsed.ok_or_else(invalid)
+	}
+}
+
+/// The moves made on a str8ts game, in order, so they can be undone.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MoveHistory {
+	moves: Vec<Move>,
+	/// When each move was made, measured from the first one.
+	times: Vec<Duration>,
+	/// When the first move was made, if any was made since the history was last cleared.
+	started: Option<Instant>,
+}
+
+impl MoveHistory {
+	/// Records a move that was just made.
+	pub(crate) fn record(&mut self, played: Move) {
+		let started = *self.started.get_or_insert_with(Instant::now);
+		self.moves.push(played);
+		self.times.push(started.elapsed());
+	}
+
+	/// Returns the recorded moves, oldest first.
+	pub(crate) fn moves(&self) -> &[Move] {
+		&self.moves
+	}
+
+	/// Returns the recorded moves together with when they were made (measured from the first one), oldest first.
+	pub(crate) fn timed_moves(&self) -> impl Iterator<Item = (Duration, &Move)> + '_ {
+		self.times.iter().copied().zip(self.moves.iter())
+	}
+
+	/// Whether there is a move to undo.
+	pub(crate) fn can_undo(&self) -> bool {
+		!self.moves.is_empty()
+	}
+
+	/// Undoes the last move on the board and forgets it.
+	///
+	/// Returns false if there was no move to undo, or if it no longer fits the board.
+	pub(crate) fn undo(&mut self, board: &mut Str8ts) -> bool {
+		match self.moves.last() {
+			Some(last) if last.invert().apply(board) => {
+				self.moves.pop();
+				self.times.pop();
+				true
+			}
+			_ => false,
+		}
+	}
+
+	/// Forgets all moves, e.g. because the board was replaced.
+	pub(crate) fn clear(&mut self) {
+		self.moves.clear();
+		self.times.clear();
+		self.started = None;
+	}
+}
+
+/// Replays the moves on a copy of the initial board, and returns the resulting board.
+pub(crate) fn replay(initial: &Str8ts, moves: &[Move]) -> Result<Str8ts, ReplayError> {
+	let mut board = *initial;
+	for (index, played) in moves.iter().enumerate() {
+		if !played.apply(&mut board) {
+			return Err(ReplayError::Diverged { index });
+		}
+	}
+	Ok(board)
+}
+
+/// Returns the move that changes the values of the white cells of `before` into those of `after`.
+///
+/// This is a `BulkClear` if values are only removed, and an `ApplySolution` otherwise.
+pub(crate) fn diff_move(before: &Str8ts, after: &Str8ts) -> Move {
+	let mut diff = Vec::new();
+	for row in 0..SIZE as u8 {
+		for col in 0..SIZE as u8 {
+			let (from, to) = (before.get_cell(row, col), after.get_cell(row, col));
+			if from.color == CellColor::White && from.value != to.value {
+				diff.push(((row, col), CellContent::of(from), CellContent::of(to)));
+			}
+		}
+	}
+	if diff.iter().all(|(_, _, to)| to.value == CellValue::Empty) {
+		Move::BulkClear {
+			cleared: diff
+				.into_iter()
+				.map(|(position, from, _)| (position, from))
+				.collect(),
+		}
+	} else {
+		Move::ApplySolution { diff }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts_tutorial::rules_board;
+
+	/// A board with givens, an entered value and a clue, to make moves on.
+	fn initial() -> Str8ts {
+		let mut board = rules_board();
+		board.set_cell_value(0, 2, CellValue::Three);
+		board
+	}
+
+	fn given(value: CellValue) -> CellContent {
+		CellContent { value, given: true }
+	}
+
+	fn entered(value: CellValue) -> CellContent {
+		CellContent {
+			value,
+			given: false,
+		}
+	}
+
+	/// One move of every kind that fits `initial`.
+	fn moves() -> Vec<Move> {
+		vec![
+			Move::SetValue {
+				position: (0, 0),
+				from: given(CellValue::One),
+				to: entered(CellValue::Four),
+			},
+			Move::SetValue {
+				position: (2, 0),
+				from: CellContent::default(),
+				to: given(CellValue::Three),
+			},
+			Move::ClearValue {
+				position: (0, 1),
+				previous: given(CellValue::Two),
+			},
+			Move::ClearValue {
+				position: (0, 2),
+				previous: entered(CellValue::Three),
+			},
+			Move::ToggleColor { position: (1, 0) },
+			Move::BulkClear {
+				cleared: vec![
+					((1, 1), given(CellValue::One)),
+					((0, 2), entered(CellValue::Three)),
+				],
+			},
+			Move::ApplySolution {
+				diff: vec![
+					((0, 0), given(CellValue::One), entered(CellValue::Two)),
+					((2, 2), CellContent::default(), entered(CellValue::Two)),
+				],
+			},
+		]
+	}
+
+	#[test]
+	fn apply_then_invert_restores_the_board() {
+		for played in moves() {
+			let mut board = initial();
+			assert!(played.apply(&mut board), "{} applies", played);
+			assert_ne!(board, initial(), "{} changes the board", played);
+			assert!(played.invert().apply(&mut board), "{} inverts", played);
+			assert_eq!(board, initial(), "{} is undone", played);
+		}
+	}
+
+	#[test]
+	fn moves_round_trip_through_text() {
+		for played in moves() {
+			assert_eq!(played.to_string().parse(), Ok(played.clone()));
+		}
+		assert_eq!(
+			moves()[2].to_string(),
+			"clear R1C2 2*",
+			"given values are marked"
+		);
+	}
+
+	#[test]
+	fn move_not_fitting_the_board_is_rejected() {
+		let mut board = initial();
+		let played = Move::ClearValue {
+			position: (0, 2),
+			previous: entered(CellValue::Four),
+		};
+		assert!(!played.apply(&mut board));
+		assert_eq!(board, initial());
+		assert_eq!(
+			replay(&initial(), &[moves()[0].clone(), played]),
+			Err(ReplayError::Diverged { index: 1 })
+		);
+	}
+}
@@ -0,0 +1,331 @@
+use std::fmt::{Display, Write as _};
+use std::fs;
+use std::io::Write as _;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::str8ts::Str8ts;
+use crate::str8ts_format::ParseError;
+use crate::str8ts_moves::{replay, Move, MoveHistory, ReplayError};
+
+/// The headings of the sections of a replay file, in order.
+const SECTIONS: [&str; 3] = ["initial", "moves", "final"];
+
+/// How a str8ts game was solved (or edited): the board it started from, the moves made on it, and the board they led
+/// to.
+///
+/// Replays are saved as text: the section `initial` holds the starting board in the `.str8ts` format, `moves` one move
+/// per line prefixed by the seconds since the first move (such as `12.500 set R4C7 0 5`), and `final` the resulting
+/// board. Only values and colors are compared between boards, as the `.str8ts` format does not record which values are
+/// given.
+#[derive(Debug, Clone)]
+pub(crate) struct Replay {
+	pub(crate) initial: Str8ts,
+	/// The moves, together with when they were made (measured from the first one), oldest first.
+	pub(crate) moves: Vec<(Duration, Move)>,
+	pub(crate) final_board: Str8ts,
+}
+
+/// The reason a replay file could not be read, or does not replay.
+#[derive(Debug)]
+pub(crate) enum ReplayFileError {
+	/// The file could not be read.
+	Io(std::io::Error),
+	/// The section with the given heading is missing, or not in its place.
+	MissingSection(&'static str),
+	/// The board of the section with the given heading could not be read.
+	Board {
+		section: &'static str,
+		error: ParseError,
+	},
+	/// The line (counted from 1) of the moves section is not a timestamped move.
+	InvalidMove { line: usize, reason: String },
+	/// The moves do not fit the initial board.
+	Diverged(ReplayError),
+	/// Replaying the moves leads to another board than the recorded final one.
+	FinalMismatch,
+}
+
+impl Display for ReplayFileError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ReplayFileError::Io(error) => write!(f, "{}", error),
+			ReplayFileError::MissingSection(section) => {
+				write!(f, "missing section '{}'", section)
+			}
+			ReplayFileError::Board { section, error } => {
+				write!(f, "invalid {} board: {}", section, error)
+			}
+			ReplayFileError::InvalidMove { line, reason } => {
+				write!(f, "line {} of the moves: {}", line, reason)
+			}
+			ReplayFileError::Diverged(error) => write!(f, "the replay diverges: {}", error),
+			ReplayFileError::FinalMismatch => {
+				write!(f, "the moves do not lead to the recorded final board")
+			}
+		}
+	}
+}
+
+impl std::error::Error for ReplayFileError {}
+
+impl From<std::io::Error> for ReplayFileError {
+	fn from(error: std::io::Error) -> Self {
+		ReplayFileError::Io(error)
+	}
+}
+
+impl Replay {
+	/// Returns the replay of the moves recorded in the history, which led to the board.
+	///
+	/// The initial board is found by undoing all moves, so this returns None if the moves were not made on the board.
+	pub(crate) fn from_history(board: &Str8ts, history: &MoveHistory) -> Option<Replay> {
+		let mut initial = *board;
+		for played in history.moves().iter().rev() {
+			if !played.invert().apply(&mut initial) {
+				return None;
+			}
+		}
+		Some(Replay {
+			initial,
+			moves: history
+				.timed_moves()
+				.map(|(time, played)| (time, played.clone()))
+				.collect(),
+			final_board: *board,
+		})
+	}
+
+	/// Returns the board before the first move and after each move, in order.
+	pub(crate) fn boards(&self) -> Result<Vec<Str8ts>, ReplayError> {
+		let mut boards = vec![self.initial];
+		for (index, (_, played)) in self.moves.iter().enumerate() {
+			let mut board = boards[index];
+			if !played.apply(&mut board) {
+				return Err(ReplayError::Diverged { index });
+			}
+			boards.push(board);
+		}
+		Ok(boards)
+	}
+
+	/// Writes the replay in the text format described at `Replay`, which `from_str` reads back.
+	pub(crate) fn file_contents(&self) -> String {
+		let mut result = format!("{}\n{}", SECTIONS[0], self.initial.file_contents());
+		result.push_str(SECTIONS[1]);
+		result.push('\n');
+		for (time, played) in &self.moves {
+			let _ = writeln!(result, "{:.3} {}", time.as_secs_f64(), played);
+		}
+		let _ = write!(
+			result,
+			"{}\n{}",
+			SECTIONS[2],
+			self.final_board.file_contents()
+		);
+		result
+	}
+
+	/// Prints the initial board and the moves. When stepping, the board is printed again after each move, once Enter is
+	/// pressed.
+	pub(crate) fn play_in_terminal(&self, step: bool) -> std::io::Result<()> {
+		print!("{}", self.initial.to_terminal());
+		let boards = self.boards().unwrap_or_default();
+		for (index, (time, played)) in self.moves.iter().enumerate() {
+			if step {
+				print!(
+					"Press Enter for move {} of {}...",
+					index + 1,
+					self.moves.len()
+				);
+				std::io::stdout().flush()?;
+				std::io::stdin().read_line(&mut String::new())?;
+			}
+			println!("{:>8.1}s  {}", time.as_secs_f64(), played);
+			if let Some(board) = boards.get(index + 1).filter(|_| step) {
+				print!("{}", board.to_terminal());
+			}
+		}
+		if !step {
+			print!("{}", self.final_board.to_terminal());
+		}
+		Ok(())
+	}
+
+	/// Saves the replay to the file at the given path.
+	pub(crate) fn save(&self, path: &Path) -> std::io::Result<()> {
+		fs::write(path, self.file_contents())
+	}
+
+	/// Loads the replay from the file at the given path, checking that its moves lead from its initial to its final
+	/// board.
+	pub(crate) fn load(path: &Path) -> Result<Replay, ReplayFileError> {
+		fs::read_to_string(path)?.parse()
+	}
+}
+
+impl FromStr for Replay {
+	type Err = ReplayFileError;
+
+	/// Reads a replay in the text format described at `Replay`, and checks that its moves lead from its initial to its
+	/// final board.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		// The lines of each section, without its heading.
+		let mut sections: [Vec<&str>; 3] = Default::default();
+		let mut current = None;
+		for line in s.lines() {
+			let line = line.trim();
+			let expected = current.map_or(0, |current| current + 1);
+			match SECTIONS.iter().position(|section| *section == line) {
+				Some(index) if index == expected => current = Some(index),
+				Some(_) => {
+					return Err(ReplayFileError::MissingSection(
+						SECTIONS[expected.min(SECTIONS.len() - 1)],
+					))
+				}
+				None if line.is_empty() => {}
+				None => match current {
+					Some(index) => sections[index].push(line),
+					None => return Err(ReplayFileError::MissingSection(SECTIONS[0])),
+				},
+			}
+		}
+		if current != Some(SECTIONS.len() - 1) {
+			let missing = current.map_or(0, |current| current + 1);
+			return Err(ReplayFileError::MissingSection(SECTIONS[missing]));
+		}
+
+		let board = |index: usize| {
+			sections[index]
+				.join("\n")
+				.parse::<Str8ts>()
+				.map_err(|error| ReplayFileError::Board {
+					section: SECTIONS[index],
+					error,
+				})
+		};
+		let initial = board(0)?;
+		let final_board = board(2)?;
+		let moves = sections[1]
+			.iter()
+			.enumerate()
+			.map(|(index, line)| {
+				let invalid = |reason: String| ReplayFileError::InvalidMove {
+					line: index + 1,
+					reason,
+				};
+				let (time, played) = line
+					.split_once(' ')
+					.ok_or_else(|| invalid(format!("invalid move '{}'", line)))?;
+				let time = time
+					.parse()
+					.ok()
+					.and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+					.ok_or_else(|| invalid(format!("invalid time '{}'", time)))?;
+				Ok((time, played.parse().map_err(invalid)?))
+			})
+			.collect::<Result<Vec<(Duration, Move)>, ReplayFileError>>()?;
+
+		let played: Vec<Move> = moves.iter().map(|(_, played)| played.clone()).collect();
+		let replayed = replay(&initial, &played).map_err(ReplayFileError::Diverged)?;
+		if replayed.file_contents() != final_board.file_contents() {
+			return Err(ReplayFileError::FinalMismatch);
+		}
+		Ok(Replay {
+			initial,
+			moves,
+			final_board,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts::CellValue;
+	use crate::str8ts_moves::CellContent;
+
+	/// A replay entering the missing 2 of the first row.
+	fn replay() -> Replay {
+		let initial = str8ts![
+			"1.3######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let mut final_board = initial;
+		final_board.set_cell_value(0, 1, CellValue::Two);
+		Replay {
+			initial,
+			moves: vec![(
+				Duration::from_millis(1500),
+				Move::SetValue {
+					position: (0, 1),
+					from: CellContent::default(),
+					to: CellContent {
+						value: CellValue::Two,
+						given: false,
+					},
+				},
+			)],
+			final_board,
+		}
+	}
+
+	#[test]
+	fn replay_round_trips_through_text() {
+		let replay = replay();
+		let text = replay.file_contents();
+		assert!(text.contains("\nmoves\n1.500 set R1C2 0 2\nfinal\n"));
+		let loaded: Replay = text.parse().unwrap();
+		assert_eq!(loaded.moves, replay.moves);
+		assert_eq!(loaded.file_contents(), text);
+	}
+
+	#[test]
+	fn move_that_does_not_fit_diverges() {
+		let text = replay()
+			.file_contents()
+			.replace("set R1C2 0 2", "set R1C2 5 2");
+		assert!(matches!(
+			text.parse::<Replay>(),
+			Err(ReplayFileError::Diverged(ReplayError::Diverged {
+				index: 0
+			}))
+		));
+	}
+
+	#[test]
+	fn edited_final_board_does_not_match() {
+		let text = replay().file_contents();
+		let (moves, final_board) = text.split_once("final\n").unwrap();
+		let text = format!("{}final\n{}", moves, final_board.replacen("123", "124", 1));
+		assert!(matches!(
+			text.parse::<Replay>(),
+			Err(ReplayFileError::FinalMismatch)
+		));
+	}
+
+	#[test]
+	fn sections_must_come_in_order() {
+		let text = replay().file_contents();
+		let (initial, rest) = text.split_once("moves\n").unwrap();
+		let (moves, final_board) = rest.split_once("final\n").unwrap();
+		let swapped = format!("moves\n{}{}final\n{}", moves, initial, final_board);
+		assert!(matches!(
+			swapped.parse::<Replay>(),
+			Err(ReplayFileError::MissingSection("initial"))
+		));
+		let without_final = format!("{}moves\n{}", initial, moves);
+		assert!(matches!(
+			without_final.parse::<Replay>(),
+			Err(ReplayFileError::MissingSection("final"))
+		));
+	}
+}
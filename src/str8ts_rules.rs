@@ -0,0 +1,142 @@
+use std::fmt::Display;
+
+use crate::str8ts::{Position, SIZE};
+
+/// One of the two main diagonals of the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Diagonal {
+	/// From the top left to the bottom right corner.
+	Main,
+	/// From the top right to the bottom left corner.
+	Anti,
+}
+
+impl Display for Diagonal {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Diagonal::Main => write!(f, "main diagonal"),
+			Diagonal::Anti => write!(f, "anti-diagonal"),
+		}
+	}
+}
+
+impl Diagonal {
+	/// Returns the cells of the diagonal, from top to bottom.
+	pub(crate) fn cells(&self) -> [Position; SIZE] {
+		std::array::from_fn(|i| {
+			let i = i as u8;
+			match self {
+				Diagonal::Main => (i, i),
+				Diagonal::Anti => (i, SIZE as u8 - 1 - i),
+			}
+		})
+	}
+
+	/// Returns the diagonals the given cell lies on (both for the center cell).
+	pub(crate) fn of(row: u8, col: u8) -> Vec<Diagonal> {
+		let mut diagonals = Vec::new();
+		if row == col {
+			diagonals.push(Diagonal::Main);
+		}
+		if row + col == SIZE as u8 - 1 {
+			diagonals.push(Diagonal::Anti);
+		}
+		diagonals
+	}
+}
+
+/// A variant rule, on top of the standard rules of str8ts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Rule {
+	/// X-Str8ts: no value appears twice on the diagonal (black cells included).
+	Diagonal(Diagonal),
+	/// Anti-knight: no two cells a chess knight's move apart hold the same value (black cells included).
+	AntiKnight,
+}
+
+impl Display for Rule {
+	/// Describes where a value must not appear twice, to complete "5 appears 2 times ...".
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Rule::Diagonal(diagonal) => write!(f, "on the {}", diagonal),
+			Rule::AntiKnight => write!(f, "a knight's move apart"),
+		}
+	}
+}
+
+/// A group of cells that must not contain the same value twice, due to a variant rule.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DistinctGroup {
+	pub(crate) rule: Rule,
+	pub(crate) cells: Vec<Position>,
+}
+
+/// The rules a str8ts game is played by, on top of the standard ones.
+///
+/// Every variant contributes its rules as groups of cells that must hold distinct values (just like rows and columns).
+/// The solver, the validation and the candidates all work from these groups, so a new variant only needs to add its
+/// toggle, its groups and its keyword here.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub(crate) struct RuleSet {
+	/// X-Str8ts: no value appears twice on either main diagonal (black cells included).
+	pub(crate) diagonals: bool,
+	/// Anti-knight: no two cells a chess knight's move apart hold the same value (black cells included).
+	pub(crate) anti_knight: bool,
+}
+
+impl RuleSet {
+	/// Returns the groups of cells the active variant rules require to hold distinct values.
+	///
+	/// The standard rule set has none.
+	pub(crate) fn distinct_groups(&self) -> Vec<DistinctGroup> {
+		let mut groups = Vec::new();
+		if self.diagonals {
+			for diagonal in [Diagonal::Main, Diagonal::Anti] {
+				groups.push(DistinctGroup {
+					rule: Rule::Diagonal(diagonal),
+					cells: diagonal.cells().to_vec(),
+				});
+			}
+		}
+		if self.anti_knight {
+			// Each pair of cells a knight's move apart, listed once.
+			for row in 0..SIZE as u8 {
+				for col in 0..SIZE as u8 {
+					for (row_offset, col_offset) in [(1, -2), (1, 2), (2, -1), (2, 1)] {
+						let other_row = row + row_offset;
+						let other_col = col as i8 + col_offset;
+						if other_row < SIZE as u8 && (0..SIZE as i8).contains(&other_col) {
+							groups.push(DistinctGroup {
+								rule: Rule::AntiKnight,
+								cells: vec![(row, col), (other_row, other_col as u8)],
+							});
+						}
+					}
+				}
+			}
+		}
+		groups
+	}
+
+	/// Returns the keywords of the active variant rules, as recorded in the `.str8ts` text format.
+	pub(crate) fn keywords(&self) -> Vec<&'static str> {
+		let mut keywords = Vec::new();
+		if self.diagonals {
+			keywords.push("diagonals");
+		}
+		if self.anti_knight {
+			keywords.push("anti-knight");
+		}
+		keywords
+	}
+
+	/// Activates the variant rule with the given keyword. Returns false if there is no such rule.
+	pub(crate) fn enable(&mut self, keyword: &str) -> bool {
+		match keyword {
+			"diagonals" => self.diagonals = true,
+			"anti-knight" => self.anti_knight = true,
+			_ => return false,
+		}
+		true
+	}
+}
@@ -0,0 +1,127 @@
+use crate::str8ts::{CellValue, Position, Str8ts, SIZE};
+
+/// A copy of a str8ts game saved under a name.
+#[derive(Debug, Clone)]
+pub(crate) struct Snapshot {
+	pub(crate) name: String,
+	pub(crate) board: Str8ts,
+}
+
+/// Named checkpoints of a str8ts game, to jump back to while experimenting.
+///
+/// Unlike the move history, checkpoints are not linear: any of them can be restored, in any order, and restoring one
+/// keeps all others.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Snapshots {
+	snapshots: Vec<Snapshot>,
+}
+
+impl Snapshots {
+	/// Saves a copy of the board under the given name, replacing an earlier checkpoint of the same name.
+	pub(crate) fn save(&mut self, name: &str, board: &Str8ts) {
+		match self
+			.snapshots
+			.iter_mut()
+			.find(|snapshot| snapshot.name == name)
+		{
+			Some(snapshot) => snapshot.board = *board,
+			None => self.snapshots.push(Snapshot {
+				name: name.to_string(),
+				board: *board,
+			}),
+		}
+	}
+
+	/// Returns the board saved under the given name, if there is one.
+	pub(crate) fn restore(&self, name: &str) -> Option<Str8ts> {
+		self.snapshots
+			.iter()
+			.find(|snapshot| snapshot.name == name)
+			.map(|snapshot| snapshot.board)
+	}
+
+	/// Returns the names of all checkpoints, in the order they were first saved.
+	pub(crate) fn list(&self) -> Vec<&str> {
+		self.snapshots
+			.iter()
+			.map(|snapshot| snapshot.name.as_str())
+			.collect()
+	}
+
+	/// Deletes the checkpoint of the given name. Returns false if there was none.
+	pub(crate) fn delete(&mut self, name: &str) -> bool {
+		let count = self.snapshots.len();
+		self.snapshots.retain(|snapshot| snapshot.name != name);
+		self.snapshots.len() != count
+	}
+
+	/// Returns the cells whose values differ between the checkpoint of the given name and the board, together with the
+	/// value in the checkpoint and the value on the board, or None if there is no such checkpoint.
+	pub(crate) fn diff_against(
+		&self,
+		name: &str,
+		board: &Str8ts,
+	) -> Option<Vec<(Position, CellValue, CellValue)>> {
+		let saved = self.restore(name)?;
+		let mut diff = Vec::new();
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				let (from, to) = (
+					saved.get_cell(row, col).value,
+					board.get_cell(row, col).value,
+				);
+				if from != to {
+					diff.push(((row, col), from, to));
+				}
+			}
+		}
+		Some(diff)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn saving_under_an_existing_name_replaces_the_checkpoint() {
+		let mut board = Str8ts::new();
+		let mut snapshots = Snapshots::default();
+		snapshots.save("start", &board);
+		snapshots.save("other", &board);
+		board.set_cell_value(0, 0, CellValue::One);
+		snapshots.save("start", &board);
+		assert_eq!(snapshots.list(), vec!["start", "other"]);
+		assert_eq!(snapshots.restore("start"), Some(board));
+		assert_eq!(snapshots.restore("missing"), None);
+	}
+
+	#[test]
+	fn deleting_keeps_the_other_checkpoints() {
+		let board = Str8ts::new();
+		let mut snapshots = Snapshots::default();
+		snapshots.save("first", &board);
+		snapshots.save("second", &board);
+		assert!(snapshots.delete("first"));
+		assert!(!snapshots.delete("first"));
+		assert_eq!(snapshots.list(), vec!["second"]);
+	}
+
+	#[test]
+	fn diff_lists_the_changed_cells() {
+		let mut board = Str8ts::new();
+		board.set_cell_value(0, 0, CellValue::One);
+		let mut snapshots = Snapshots::default();
+		snapshots.save("start", &board);
+		board.set_cell_value(0, 0, CellValue::Two);
+		board.set_cell_value(4, 4, CellValue::Five);
+		assert_eq!(
+			snapshots.diff_against("start", &board),
+			Some(vec![
+				((0, 0), CellValue::One, CellValue::Two),
+				((4, 4), CellValue::Empty, CellValue::Five),
+			])
+		);
+		assert_eq!(snapshots.diff_against("missing", &board), None);
+	}
+}
@@ -1,157 +1,756 @@
-use std::collections::HashMap;
-use std::collections::LinkedList;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Arguments, Display, Write};
+use std::rc::Rc;
+use std::time::Duration;
 
 use russcip::prelude::*;
+use russcip::{ProblemCreated, Variable};
 
-use crate::str8ts::{Cell, CellColor, CellValue, Str8ts};
+use crate::str8ts::{
+	Cell, CellColor, CellValue, CompartmentId, LabelScheme, Orientation, Position, Str8ts, CELLS,
+	SIZE,
+};
+use crate::str8ts_symmetry::Symmetry;
+
+/// The number of random values `Str8ts::random_full_solution` tries to seed a layout with.
+const RANDOM_SEED_VALUES: usize = 8;
+
+/// The outcome of solving a str8ts game, together with statistics of the solving process.
+#[derive(Debug)]
+pub(crate) struct SolveReport {
+	/// The solved str8ts game, if a solution was found.
+	pub(crate) solution: Option<Str8ts>,
+	/// The final status of SCIP.
+	pub(crate) status: Status,
+	/// The number of branch-and-bound nodes SCIP explored.
+	pub(crate) nodes: usize,
+	/// The number of LP iterations SCIP performed.
+	pub(crate) lp_iterations: usize,
+	/// The time SCIP spent solving, in seconds.
+	pub(crate) solving_time: f64,
+}
+
+/// The reason a str8ts game could not be solved.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SolveError {
+	/// The str8ts game has no solution.
+	Infeasible,
+	/// A cell selected to be filled from the solution is black.
+	BlackCell(Position),
+	/// A cell selected to be filled from the solution already holds a value other than the solution's.
+	Contradiction(Position),
+}
+
+impl Display for SolveError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let labels = LabelScheme::default();
+		match self {
+			SolveError::Infeasible => write!(f, "the puzzle has no solution"),
+			SolveError::BlackCell((row, col)) => {
+				write!(f, "{} is a black cell", labels.cell_reference(*row, *col))
+			}
+			SolveError::Contradiction((row, col)) => write!(
+				f,
+				"the value of {} contradicts the solution",
+				labels.cell_reference(*row, *col)
+			),
+		}
+	}
+}
+
+impl std::error::Error for SolveError {}
+
+/// The cells `Str8ts::fill_from` fills.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FillSelection {
+	/// The given cells.
+	Cells(Vec<Position>),
+	/// The white cells of the given row.
+	Row(u8),
+	/// The white cells of the given column.
+	Column(u8),
+	/// The cells of the given compartment, or none if there is no such compartment.
+	Compartment(CompartmentId),
+	/// This many empty white cells, picked at random (or all of them, if there are fewer).
+	Random(usize),
+}
+
+/// Whether a str8ts game has no, exactly one, or multiple solutions.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub(crate) enum Uniqueness {
+	/// The str8ts game has no solution.
+	Infeasible,
+	/// The str8ts game has exactly this solution.
+	Unique(Str8ts),
+	/// The str8ts game has (at least) these two distinct solutions.
+	Multiple(Str8ts, Str8ts),
+}
+
+impl Uniqueness {
+	/// Returns the indices of the cells in which the two solutions differ, if there are multiple solutions.
+	pub(crate) fn differing_cells(&self) -> Vec<u8> {
+		match self {
+			Uniqueness::Multiple(first, second) => (0..CELLS as u8)
+				.filter(|index| {
+					first.get_cell_by_index(*index).value != second.get_cell_by_index(*index).value
+				})
+				.collect(),
+			_ => Vec::new(),
+		}
+	}
+}
+
+/// The solution of a str8ts game, computed once to answer many queries (hints, checks, reveals).
+#[derive(Debug, Clone)]
+pub(crate) struct SolvedBoard {
+	solution: Str8ts,
+}
+
+impl SolvedBoard {
+	/// Wraps a solution that is already known, e.g. from a `SolutionCache`.
+	pub(crate) fn new(solution: Str8ts) -> SolvedBoard {
+		SolvedBoard { solution }
+	}
+
+	/// Returns the value of the given cell in the solution.
+	///
+	/// For black cells, this is their (possibly empty) clue.
+	pub(crate) fn value_at(&self, row: u8, col: u8) -> CellValue {
+		self.solution.get_cell(row, col).value
+	}
+
+	/// Whether the given value is the solution's value of the given cell.
+	pub(crate) fn is_correct(&self, row: u8, col: u8, value: CellValue) -> bool {
+		self.value_at(row, col) == value
+	}
+
+	/// Returns the white cells whose value on the given board differs from the solution, together with the correct value.
+	///
+	/// Empty white cells are included, so an empty list means the board is solved.
+	pub(crate) fn diff_from(&self, board: &Str8ts) -> Vec<(Position, CellValue)> {
+		let mut diff = Vec::new();
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				let cell = board.get_cell(row, col);
+				if cell.color == CellColor::White && !self.is_correct(row, col, cell.value) {
+					diff.push(((row, col), self.value_at(row, col)));
+				}
+			}
+		}
+		diff
+	}
+}
+
+/// Create an empty minimization problem with SCIP's default plugins included and its output hidden.
+///
+/// russcip 0.2 consumes a model when solving it and offers no way to clone or reset one, so every solve needs a fresh
+/// SCIP instance. All kinds of solves create it here, so the setup stays the same for all of them.
+fn create_model(name: &str) -> Model<ProblemCreated> {
+	Model::new()
+		.hide_output()
+		.include_default_plugins()
+		.create_prob(name)
+		.set_obj_sense(ObjSense::Minimize)
+}
+
+/// Optional house rules the solver can enforce on top of the rules of the str8ts game.
+///
+/// All options are off by default, which solves the str8ts game by its regular rules.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SolveOptions {
+	/// Forbids the same value in two white cells of a line that are separated by a single black cell only.
+	pub(crate) forbid_across_black: bool,
+	/// If set, the variables and constraints are added to the model in an order shuffled by this seed.
+	///
+	/// This is meant for studying how the order affects SCIP's solving time. It does not change the model itself, so
+	/// the str8ts game keeps its solutions (though another one of several may be found). The same seed always produces
+	/// the same order, and thus the same solution.
+	pub(crate) shuffle_seed: Option<u64>,
+}
+
+/// The `x_{i}_{k}` variables of the model, keyed by the cell index `i` and the value `k`.
+type CellVariables = HashMap<(usize, CellValue), Rc<Variable>>;
+
+impl SolveReport {
+	/// Whether the puzzle was solved without branching.
+	///
+	/// As the model has a constant objective of zero, the objective value of the root LP relaxation carries no information.
+	/// What matters is whether the root node (its LP relaxation, together with presolving and the root heuristics)
+	/// already produced a feasible integral solution, or whether SCIP had to branch to find one.
+	/// The former indicates an "easy" puzzle, the latter a puzzle that requires some guessing.
+	pub(crate) fn solved_at_root(&self) -> bool {
+		self.solution.is_some() && self.nodes <= 1
+	}
+}
 
 impl Str8ts {
 	/// Solve the str8ts game.
 	///
 	/// Returns the solved Str8ts game if the str8ts game was solved successfully. Otherwise, returns None.
 	pub fn solve(&self) -> Option<Str8ts> {
-		// Preprocess the str8ts game.
-		let compartments = find_compartments(self);
-		for compartment in compartments.iter() {
-			print!("Compartment: ");
-			for index in compartment.iter() {
-				let (row, col) = trans_index_to_row_col!(*index);
-				print!("({},{}), ", row, col);
+		self.solve_with_report().solution
+	}
+
+	/// Solve the str8ts game, reporting statistics of the solving process alongside the solution.
+	pub(crate) fn solve_with_report(&self) -> SolveReport {
+		self.solve_with_options(SolveOptions::default())
+	}
+
+	/// Solve the str8ts game, enforcing the given house rules on top of its regular rules.
+	///
+	/// A blank board (all white, nothing placed, no variant rules) has countless solutions, and the one SCIP picks is an
+	/// accident of its heuristics. Unless a shuffle seed asks for another one, it is always solved with
+	/// `blank_completion` instead, without building a model.
+	pub(crate) fn solve_with_options(&self, options: SolveOptions) -> SolveReport {
+		if options.shuffle_seed.is_none() && *self == Str8ts::new() {
+			return SolveReport {
+				solution: Some(Str8ts::blank_completion()),
+				status: Status::Optimal,
+				nodes: 0,
+				lp_iterations: 0,
+				solving_time: 0.,
+			};
+		}
+		let (model, x) = self.build_model(options);
+		self.solve_model(model, &x)
+	}
+
+	/// Check whether the str8ts game has a solution.
+	///
+	/// This is cheaper than `solve().is_some()`, as only the status of SCIP is checked and no solution is read back.
+	pub(crate) fn is_solvable(&self) -> bool {
+		let (model, _) = self.build_model(SolveOptions::default());
+		model.solve().status() == Status::Optimal
+	}
+
+	/// Whether the str8ts game has a solution, like `is_solvable`, but giving up after the time limit (rounded up to
+	/// whole seconds). Returns None if SCIP did not decide it in time.
+	pub(crate) fn is_solvable_within(&self, time_limit: Duration) -> Option<bool> {
+		let (model, _) = self.build_model(SolveOptions::default());
+		let seconds = time_limit.as_secs() + u64::from(time_limit.subsec_nanos() > 0);
+		match model.set_time_limit(seconds as usize).solve().status() {
+			Status::Optimal => Some(true),
+			Status::Infeasible => Some(false),
+			_ => None,
+		}
+	}
+
+	/// Returns the number of variables and constraints of the model of the str8ts game, without solving it.
+	///
+	/// Only the black cells shrink the model. Values placed merely fix the bounds of their variables, which SCIP's
+	/// presolving removes before solving, but which are still counted here.
+	pub(crate) fn model_size(&self) -> (usize, usize) {
+		let (mut model, _) = self.build_model(SolveOptions::default());
+		(model.n_vars(), model.n_conss())
+	}
+
+	/// Returns the solution of the blank board that `solve` always returns for it.
+	///
+	/// Row `r` holds the values 1-9 shifted by `r` places, so every row and column holds each value once, and every
+	/// (single, full-length) compartment forms a straight.
+	pub(crate) fn blank_completion() -> Str8ts {
+		let mut board = Str8ts::new();
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				board.set_cell_value(row, col, CellValue::from((row + col) % SIZE as u8 + 1));
+			}
+		}
+		board
+	}
+
+	/// Creates a random, completely filled str8ts game.
+	///
+	/// A random layout, symmetric under a rotation by 180 degrees like most published puzzles, is seeded with a few
+	/// random values and solved with a shuffled model. Layouts without a solution are discarded and replaced.
+	pub(crate) fn random_full_solution(rng: &mut SplitMix64) -> Str8ts {
+		loop {
+			let mut board = Str8ts::new();
+			// The cells up to the center determine the layout.
+			for index in 0..=CELLS as u8 / 2 {
+				if rng.next_u64().is_multiple_of(5) {
+					let (row, col) = Str8ts::coords(index);
+					board.set_cell_color(row, col, CellColor::Black);
+					let (row, col) = Symmetry::Rotate180.map((row, col));
+					board.set_cell_color(row, col, CellColor::Black);
+				}
+			}
+			for _ in 0..RANDOM_SEED_VALUES {
+				let (row, col) = Str8ts::coords((rng.next_u64() % CELLS as u64) as u8);
+				let value = CellValue::from((rng.next_u64() % SIZE as u64 + 1) as u8);
+				let cell = board.get_cell(row, col);
+				if cell.color == CellColor::White
+					&& cell.value == CellValue::Empty
+					&& board.can_place(row, col, value)
+				{
+					board.set_cell_value(row, col, value);
+				}
+			}
+			let options = SolveOptions {
+				shuffle_seed: Some(rng.next_u64()),
+				..SolveOptions::default()
+			};
+			if let Some(solution) = board.solve_with_options(options).solution {
+				return solution;
 			}
-			println!();
 		}
+	}
+
+	/// Solve the str8ts game once, to answer many queries about its solution.
+	///
+	/// Returns None if the str8ts game has no solution.
+	pub(crate) fn solved(&self) -> Option<SolvedBoard> {
+		self.solve().map(|solution| SolvedBoard { solution })
+	}
+
+	/// Fills the selected cells with their values in the given solution of the puzzle, and returns the cells that were
+	/// empty before, together with the values they got.
+	///
+	/// The solution is that of the puzzle, i.e. of the givens only, so the values entered so far are checked against it:
+	/// if a selected cell holds another value, or if a selected cell is black, nothing is filled. Rows and columns select
+	/// only their white cells.
+	pub(crate) fn fill_from(
+		&mut self,
+		solution: &Str8ts,
+		selection: FillSelection,
+		rng: &mut SplitMix64,
+	) -> Result<Vec<(Position, CellValue)>, SolveError> {
+		let cells: Vec<Position> = match selection {
+			FillSelection::Cells(cells) => cells,
+			FillSelection::Row(row) => self
+				.white_cells()
+				.map(|(position, _)| position)
+				.filter(|(cell_row, _)| *cell_row == row)
+				.collect(),
+			FillSelection::Column(col) => self
+				.white_cells()
+				.map(|(position, _)| position)
+				.filter(|(_, cell_col)| *cell_col == col)
+				.collect(),
+			FillSelection::Compartment(id) => self.compartment(id).unwrap_or_default(),
+			FillSelection::Random(count) => {
+				let mut empty: Vec<Position> = self
+					.empty_white_cells()
+					.map(|(position, _)| position)
+					.collect();
+				rng.shuffle(&mut empty);
+				empty.truncate(count);
+				empty.sort();
+				empty
+			}
+		};
+
+		let mut filled = Vec::new();
+		for (row, col) in cells {
+			let cell = self.get_cell(row, col);
+			let value = solution.get_cell(row, col).value;
+			if cell.color == CellColor::Black {
+				return Err(SolveError::BlackCell((row, col)));
+			} else if cell.value == CellValue::Empty {
+				filled.push(((row, col), value));
+			} else if cell.value != value {
+				return Err(SolveError::Contradiction((row, col)));
+			}
+		}
+		for ((row, col), value) in filled.iter() {
+			self.set_cell_value(*row, *col, *value);
+		}
+		Ok(filled)
+	}
+
+	/// Check whether the str8ts game has no, exactly one, or multiple solutions.
+	///
+	/// This solves the str8ts game twice: once to find a solution, and once more with that solution excluded.
+	pub(crate) fn uniqueness(&self) -> Uniqueness {
+		let Some(solution) = self.solve() else {
+			return Uniqueness::Infeasible;
+		};
+
+		// Exclude the found solution: not all of the previously empty white cells may keep their value.
+		let (mut model, x) = self.build_model(SolveOptions::default());
+		let vars = self
+			.into_iter()
+			.enumerate()
+			.filter(|(_, cell)| cell.color == CellColor::White && cell.value == CellValue::Empty)
+			.map(|(index, _)| {
+				let value = solution.get_cell_by_index(index as u8).value;
+				x.get(&(index, value)).unwrap().clone()
+			})
+			.collect::<Vec<_>>();
+		let coeffs = vec![1.; vars.len()];
+		let rhs = vars.len() as f64 - 1.;
+		model.add_cons(vars, &coeffs, -f64::INFINITY, rhs, "c_exclude");
+
+		match self.solve_model(model, &x).solution {
+			Some(other_solution) => Uniqueness::Multiple(solution, other_solution),
+			None => Uniqueness::Unique(solution),
+		}
+	}
+
+	/// Finds two solutions of the str8ts game that differ in as many cells as possible, together with that number.
+	///
+	/// Like `uniqueness`, this solves the str8ts game twice: once to find a solution, and once more minimizing the
+	/// number of previously empty white cells that keep their value. Returns None if the solution is unique, and
+	/// `SolveError::Infeasible` if there is none.
+	pub(crate) fn max_divergent_solutions(
+		&self,
+	) -> Result<Option<(Str8ts, Str8ts, usize)>, SolveError> {
+		let solution = self.solve().ok_or(SolveError::Infeasible)?;
+
+		// a_{i} = 1 if the cell with index i keeps the value of the found solution, which the objective penalizes.
+		let (mut model, x) = self.build_model(SolveOptions::default());
+		for ((row, col), _) in self.empty_white_cells() {
+			let index = Str8ts::index(row, col);
+			let value = solution.get_cell(row, col).value;
+			let a_i = model.add_var(0., 1., 1., &format!("a_{}", index), VarType::Binary);
+			let x_i_k = x.get(&(usize::from(index), value)).unwrap().clone();
+			model.add_cons(
+				vec![a_i, x_i_k],
+				&[1., -1.],
+				0.,
+				f64::INFINITY,
+				&format!("c_agree_{}", index),
+			);
+		}
+
+		let other_solution = self
+			.solve_model(model, &x)
+			.solution
+			.ok_or(SolveError::Infeasible)?;
+		let distance = solution
+			.into_iter()
+			.zip(other_solution)
+			.filter(|(first, second)| first.value != second.value)
+			.count();
+		Ok((distance > 0).then_some((solution, other_solution, distance)))
+	}
+
+	/// Solve the str8ts game, with none of the given cells (by index) holding the value paired with it.
+	///
+	/// This explores the solutions around a cell, e.g. whether the str8ts game can still be solved if a cell is not a 5.
+	/// Forbidding the value a white cell already holds makes the str8ts game unsolvable. Black cells are not part of
+	/// the model, so forbidding values of them has no effect.
+	pub(crate) fn solve_excluding(&self, forbidden: &[(u8, CellValue)]) -> Option<Str8ts> {
+		let (mut model, x) = self.build_model(SolveOptions::default());
+		for (index, value) in forbidden {
+			if let Some(x_i_k) = x.get(&(usize::from(*index), *value)) {
+				model.add_cons(
+					vec![x_i_k.clone()],
+					&[1.],
+					0.,
+					0.,
+					&format!("c_forbid_{}_{}", index, value),
+				);
+			}
+		}
+		self.solve_model(model, &x).solution
+	}
+
+	/// Solve the str8ts game with the values of each white cell restricted to the given candidates, as computed by an
+	/// external propagator, in reading order.
+	///
+	/// All other values are forbidden as in `solve_excluding`, which shrinks the problem SCIP has to solve. The
+	/// candidates are trusted: leaving out a value a solution needs makes the str8ts game appear to have no solution.
+	pub(crate) fn solve_with_candidates(
+		&self,
+		candidates: &[[BTreeSet<CellValue>; SIZE]; SIZE],
+	) -> Option<Str8ts> {
+		let forbidden: Vec<(u8, CellValue)> = self
+			.white_cells()
+			.flat_map(|((row, col), _)| {
+				let allowed = &candidates[row as usize][col as usize];
+				CellValue::into_iter(false)
+					.filter(|value| !allowed.contains(value))
+					.map(move |value| (Str8ts::index(row, col), value))
+			})
+			.collect();
+		self.solve_excluding(&forbidden)
+	}
+
+	/// Returns the indices of the cells that are responsible for the str8ts game having multiple solutions.
+	///
+	/// These are the cells in which two solutions differ, so a clue in one of them rules out at least one of the
+	/// solutions. Returns an empty list if there is no or exactly one solution.
+	pub(crate) fn ambiguous_cells(&self) -> Vec<u8> {
+		self.uniqueness().differing_cells()
+	}
+
+	/// Returns the indices (into `find_compartments`) of the compartments whose values are not forced by the rest of
+	/// the board, i.e. that some solution fills differently than another one.
+	///
+	/// Such a compartment typically holds the same straight in another order, or shifts its straight by one. The
+	/// author has to fix a value in each of them (or change the layout around them) to make the solution unique.
+	/// The compartments with an `ambiguous_cells` cell are unforced right away; the str8ts game is then solved once
+	/// more for every other compartment with an empty white cell, with its values from a solution excluded as a whole. Returns an empty list if there is no or exactly one solution.
+	pub(crate) fn unforced_compartments(&self) -> Vec<usize> {
+		let ambiguous = self.ambiguous_cells();
+		let Some(solution) = self.solve().filter(|_| !ambiguous.is_empty()) else {
+			return Vec::new();
+		};
+		find_compartments(self)
+			.iter()
+			.enumerate()
+			.filter(|(_, compartment)| {
+				if compartment.iter().any(|index| ambiguous.contains(index)) {
+					return true;
+				}
+				let empty: Vec<u8> = compartment
+					.iter()
+					.copied()
+					.filter(|index| self.get_cell_by_index(*index).value == CellValue::Empty)
+					.collect();
+				if empty.is_empty() {
+					return false;
+				}
+
+				// Not all of the compartment's empty cells may keep their value from the first solution.
+				let (mut model, x) = self.build_model(SolveOptions::default());
+				let vars = empty
+					.iter()
+					.map(|index| {
+						let value = solution.get_cell_by_index(*index).value;
+						x.get(&(usize::from(*index), value)).unwrap().clone()
+					})
+					.collect::<Vec<_>>();
+				let coeffs = vec![1.; vars.len()];
+				let rhs = vars.len() as f64 - 1.;
+				model.add_cons(vars, &coeffs, -f64::INFINITY, rhs, "c_exclude");
+				self.solve_model(model, &x).solution.is_some()
+			})
+			.map(|(index, _)| index)
+			.collect()
+	}
+
+	/// Returns the indices of the white givens that can each be removed on its own, with the str8ts game still having a
+	/// unique solution.
+	///
+	/// This is expensive: every given is checked with `uniqueness`, so the str8ts game is solved twice per given.
+	/// Removing a given never removes solutions, so none is redundant if the str8ts game is not uniquely solvable.
+	pub(crate) fn redundant_clues(&self) -> Vec<u8> {
+		(0..CELLS as u8)
+			.filter(|index| {
+				let cell = self.get_cell_by_index(*index);
+				cell.color == CellColor::White && cell.given && cell.value != CellValue::Empty
+			})
+			.filter(|index| {
+				let mut without_clue = *self;
+				let (row, col) = Str8ts::coords(*index);
+				without_clue.set_cell_value(row, col, CellValue::Empty);
+				without_clue.set_cell_given(row, col, false);
+				matches!(without_clue.uniqueness(), Uniqueness::Unique(_))
+			})
+			.collect()
+	}
+
+	/// Removes white givens one after another, in reading order, as long as the str8ts game keeps a unique solution.
+	///
+	/// The givens with the indices in `pinned` are kept regardless, e.g. because they are part of the puzzle's theme.
+	/// Like `redundant_clues`, this solves the str8ts game twice per given, and removes nothing if the str8ts game is
+	/// not uniquely solvable to begin with.
+	pub(crate) fn minimize_clues(&self, pinned: &[u8]) -> Str8ts {
+		let mut minimized = *self;
+		for index in 0..CELLS as u8 {
+			let cell = minimized.get_cell_by_index(index);
+			if cell.color != CellColor::White
+				|| !cell.given
+				|| cell.value == CellValue::Empty
+				|| pinned.contains(&index)
+			{
+				continue;
+			}
+			let mut without_clue = minimized;
+			let (row, col) = Str8ts::coords(index);
+			without_clue.set_cell_value(row, col, CellValue::Empty);
+			without_clue.set_cell_given(row, col, false);
+			if matches!(without_clue.uniqueness(), Uniqueness::Unique(_)) {
+				minimized = without_clue;
+			}
+		}
+		minimized
+	}
+
+	/// Suggests empty white cells to turn black so the str8ts game gets a unique solution.
+	///
+	/// Each empty white cell is turned black on its own, or together with its counterpart under a rotation by 180
+	/// degrees if `symmetric` is set, and checked with `uniqueness`. As every check solves the str8ts game twice, at most
+	/// `max_checks` cells are checked, in reading order. The suggestions are ranked by how few cells they turn black.
+	///
+	/// Turning cells black cannot make an unsolvable str8ts game solvable, so that is an error rather than no
+	/// suggestion.
+	pub(crate) fn suggest_black_cells(
+		&self,
+		max_checks: usize,
+		symmetric: bool,
+	) -> Result<Vec<(Position, Uniqueness)>, SolveError> {
+		if !self.is_solvable() {
+			return Err(SolveError::Infeasible);
+		}
+		let mut suggestions: Vec<(usize, Position, Uniqueness)> = (0..CELLS as u8)
+			// A symmetric pair is checked once, by its first cell.
+			.filter(|index| !symmetric || *index <= CELLS as u8 / 2)
+			.map(Str8ts::coords)
+			.filter(|(row, col)| {
+				let cell = self.get_cell(*row, *col);
+				let (partner_row, partner_col) = Symmetry::Rotate180.map((*row, *col));
+				let partner = self.get_cell(partner_row, partner_col);
+				cell.color == CellColor::White
+					&& cell.value == CellValue::Empty
+					&& (!symmetric
+						|| (partner.color == CellColor::White && partner.value == CellValue::Empty))
+			})
+			.take(max_checks)
+			.filter_map(|(row, col)| {
+				let mut changed = vec![(row, col)];
+				let partner = Symmetry::Rotate180.map((row, col));
+				if symmetric && partner != (row, col) {
+					changed.push(partner);
+				}
+				let mut board = *self;
+				for (row, col) in changed.iter() {
+					board.set_cell_color(*row, *col, CellColor::Black);
+				}
+				match board.uniqueness() {
+					uniqueness @ Uniqueness::Unique(_) => {
+						Some((changed.len(), (row, col), uniqueness))
+					}
+					_ => None,
+				}
+			})
+			.collect();
+		suggestions.sort_by_key(|(changed, position, _)| (*changed, *position));
+		Ok(suggestions
+			.into_iter()
+			.map(|(_, position, uniqueness)| (position, uniqueness))
+			.collect())
+	}
+
+	/// Build the MILP model of the str8ts game.
+	///
+	/// Returns the model together with its `x_{i}_{k}` variables, which are needed to read back a solution.
+	fn build_model(&self, options: SolveOptions) -> (Model<ProblemCreated>, CellVariables) {
+		// Preprocess the str8ts game.
+		let compartments = find_compartments(self);
 
 		// Create the model.
-		let mut model = Model::new()
-			.hide_output()
-			.include_default_plugins()
-			.create_prob("Str8ts")
-			.set_obj_sense(ObjSense::Minimize);
+		let mut model = create_model("Str8ts");
+
+		// All variables and constraints are named through this one buffer.
+		let mut name = String::new();
 
 		// Create variables:
 		// x_{i}_{k} = 1 if the cell with index i contains the value k. Only relevant for white cells.
+		let mut rng = options.shuffle_seed.map(SplitMix64);
+		let mut x_order = Vec::with_capacity(CELLS * SIZE);
+		for ((row, col), cell) in self.white_cells() {
+			let index = Str8ts::index(row, col) as usize;
+			x_order.extend(CellValue::into_iter(false).map(|value| (index, cell, value)));
+		}
+		if let Some(rng) = rng.as_mut() {
+			rng.shuffle(&mut x_order);
+		}
 		let mut x = HashMap::new();
-		for (index, cell) in self.into_iter().enumerate() {
-			if cell.color == CellColor::White {
-				for value in CellValue::into_iter(false) {
-					match cell.value {
-						CellValue::Empty => {
-							x.insert(
-								(index, value),
-								model.add_var(
-									0.,
-									1.,
-									0.,
-									&format!("x_{}_{}", index, value),
-									VarType::Binary,
-								),
-							);
-						}
-						v if v == value => {
-							// Force to be used
-							x.insert(
-								(index, value),
-								model.add_var(
-									1.,
-									1.,
-									0.,
-									&format!("x_{}_{}", index, value),
-									VarType::Binary,
-								),
-							);
-						}
-						_ => {
-							// Force to be not used
-							x.insert(
-								(index, value),
-								model.add_var(
-									0.,
-									0.,
-									0.,
-									&format!("x_{}_{}", index, value),
-									VarType::Binary,
-								),
-							);
-						}
-					}
-				}
-			}
+		for (index, cell, value) in x_order {
+			let (lb, ub) = match cell.value {
+				// Force to be not used if the cell's constraint rules it out
+				CellValue::Empty if cell.constraint.is_some_and(|c| !c.allows(value)) => (0., 0.),
+				CellValue::Empty => (0., 1.),
+				// Force to be used
+				v if v == value => (1., 1.),
+				// Force to be not used
+				_ => (0., 0.),
+			};
+			let x_i_k = model.add_var(
+				lb,
+				ub,
+				0.,
+				set_name(&mut name, format_args!("x_{}_{}", index, value)),
+				VarType::Binary,
+			);
+			x.insert((index, value), x_i_k);
 		}
 		// y_{c}_{k} = 1 if the compartment with index c has the least value k
-		let mut y = HashMap::new();
+		// A compartment of a single cell is a straight no matter its value, so it needs no y_{c}_{k} (and no constraints 4 and 5).
+		let mut y_order = Vec::with_capacity(compartments.len() * 9);
 		for (compartment_index, compartment) in compartments.iter().enumerate() {
-			for value in CellValue::into_iter(false) {
-				let numer_value: usize = value.into();
-				if compartment.len() <= 9 - numer_value + 1 {
-					y.insert(
-						(compartment_index, value),
-						model.add_var(
-							0.,
-							1.,
-							0.,
-							&format!("y_{}_{}", compartment_index, value),
-							VarType::Binary,
-						),
-					);
-				} else {
-					y.insert(
-						(compartment_index, value),
-						model.add_var(
-							0.,
-							0.,
-							0.,
-							&format!("y_{}_{}", compartment_index, value),
-							VarType::Binary,
-						),
-					);
-				}
+			if compartment.len() > 1 {
+				y_order.extend(
+					CellValue::into_iter(false)
+						.map(|value| (compartment_index, compartment, value)),
+				);
 			}
 		}
+		if let Some(rng) = rng.as_mut() {
+			rng.shuffle(&mut y_order);
+		}
+		let mut y = HashMap::new();
+		for (compartment_index, compartment, value) in y_order {
+			let numer_value: usize = value.into();
+			// The least value must leave room for the rest of the compartment.
+			let ub = if compartment.len() <= 9 - numer_value + 1 {
+				1.
+			} else {
+				0.
+			};
+			let y_c_k = model.add_var(
+				0.,
+				ub,
+				0.,
+				set_name(&mut name, format_args!("y_{}_{}", compartment_index, value)),
+				VarType::Binary,
+			);
+			y.insert((compartment_index, value), y_c_k);
+		}
 
 		// Create constraints:
+		// With a shuffle seed, they are collected and only added at the end, in shuffled order.
+		let mut constraints = PendingConstraints {
+			pending: rng.is_some().then(Vec::new),
+		};
 		// 1. Each cell contains exactly one value.
 		for (index, cell) in self.into_iter().enumerate() {
 			if cell.color == CellColor::White {
 				// grab all the x_i_k variables for this cell with index i
-				let x_i = x
-					.iter()
-					.filter(|(key, _)| key.0 == index)
-					.map(|(_, value)| value.clone())
+				let x_i = CellValue::into_iter(false)
+					.map(|value| x[&(index, value)].clone())
 					.collect::<Vec<_>>();
-				// create a vector of coefficients for the x_i_k variables (all 1)
-				let coeffs = vec![1.; x_i.len()];
 				// Add the constraint
-				model.add_cons(x_i, &coeffs, 1., 1., &format!("c_1_{}", index));
+				constraints.add(
+					&mut model,
+					x_i,
+					&ONES,
+					1.,
+					1.,
+					set_name(&mut name, format_args!("c_1_{}", index)),
+				);
 			}
 		}
 
 		// 2. Each value is used at most once in each row.
 		// 2.a No two white cells in the same row have the same value.
-		for row in 0..9 {
+		for row in 0..SIZE as u8 {
 			for value in CellValue::into_iter(false) {
 				// grab all the x_i_k variables for this row and value
-				let x_i = x
-					.iter()
-					.filter(|(key, _)| key.0 / 9 == row && key.1 == value)
-					.map(|(_, value)| value.clone())
+				let x_i = (0..SIZE as u8)
+					.filter_map(|col| x.get(&(Str8ts::index(row, col) as usize, value)))
+					.cloned()
 					.collect::<Vec<_>>();
-				// create a vector of coefficients for the x_i_k variables (all 1)
-				let coeffs = vec![1.; x_i.len()];
+				let coeffs = &ONES[..x_i.len()];
 				// Add the constraint
-				model.add_cons(
+				constraints.add(
+					&mut model,
 					x_i,
-					&coeffs,
+					coeffs,
 					-f64::INFINITY,
 					1.,
-					&format!("c_2a_{}_{}", row, value),
+					set_name(&mut name, format_args!("c_2a_{}_{}", row, value)),
 				);
 			}
 		}
 		// 2.b No white cell has the same value as a black cell in the same row.
-		for row in 0..9 {
+		for row in 0..SIZE as u8 {
 			// grab all the non-empty values of black cells in this row
 			let mut black_values = Vec::new();
-			for col in 0..9 {
+			for col in 0..SIZE as u8 {
 				let cell = self.get_cell(row, col);
 				if cell.color == CellColor::Black && cell.value != CellValue::Empty {
 					black_values.push(cell.value);
@@ -169,51 +768,49 @@ impl Str8ts {
 			);
 			for value in black_values.iter() {
 				// grab all the x_i_k variables for this row and value
-				let x_i = x
-					.iter()
-					.filter(|(key, _)| key.0 / 9 == row.into() && key.1 == *value)
-					.map(|(_, value)| value.clone())
-					.collect::<Vec<_>>();
-				for x_i_k in x_i.iter() {
-					// Add the constraint
-					model.add_cons(
-						vec![x_i_k.clone()],
-						&[1.],
-						-f64::INFINITY,
-						0.,
-						&format!("c_2b_{}_{}", row, value),
-					);
+				for col in 0..SIZE as u8 {
+					let index = Str8ts::index(row, col) as usize;
+					if let Some(x_i_k) = x.get(&(index, *value)) {
+						// Add the constraint
+						constraints.add(
+							&mut model,
+							vec![x_i_k.clone()],
+							&ONES[..1],
+							-f64::INFINITY,
+							0.,
+							set_name(&mut name, format_args!("c_2b_{}_{}", row, value)),
+						);
+					}
 				}
 			}
 		}
 
 		// 3. Each value is used at most once in each column.
 		// 3.a No two white cells in the same column have the same value.
-		for col in 0..9 {
+		for col in 0..SIZE as u8 {
 			for value in CellValue::into_iter(false) {
 				// grab all the x_i_k variables for this column and value
-				let x_i = x
-					.iter()
-					.filter(|(key, _)| key.0 % 9 == col && key.1 == value)
-					.map(|(_, value)| value.clone())
+				let x_i = (0..SIZE as u8)
+					.filter_map(|row| x.get(&(Str8ts::index(row, col) as usize, value)))
+					.cloned()
 					.collect::<Vec<_>>();
-				// create a vector of coefficients for the x_i_k variables (all 1)
-				let coeffs = vec![1.; x_i.len()];
+				let coeffs = &ONES[..x_i.len()];
 				// Add the constraint
-				model.add_cons(
+				constraints.add(
+					&mut model,
 					x_i,
-					&coeffs,
+					coeffs,
 					-f64::INFINITY,
 					1.,
-					&format!("c_3_{}_{}", col, value),
+					set_name(&mut name, format_args!("c_3_{}_{}", col, value)),
 				);
 			}
 		}
 		// 3.b No white cell has the same value as a black cell in the same column.
-		for col in 0..9 {
+		for col in 0..SIZE as u8 {
 			// grab all the non-empty values of black cells in this column
 			let mut black_values = Vec::new();
-			for row in 0..9 {
+			for row in 0..SIZE as u8 {
 				let cell = self.get_cell(row, col);
 				if cell.color == CellColor::Black && cell.value != CellValue::Empty {
 					black_values.push(cell.value);
@@ -231,50 +828,59 @@ impl Str8ts {
 			);
 			for value in black_values.iter() {
 				// grab all the x_i_k variables for this column and value
-				let x_i = x
-					.iter()
-					.filter(|(key, _)| key.0 % 9 == col.into() && key.1 == *value)
-					.map(|(_, value)| value.clone())
-					.collect::<Vec<_>>();
-				for x_i_k in x_i.iter() {
-					// Add the constraint
-					model.add_cons(
-						vec![x_i_k.clone()],
-						&[1.],
-						-f64::INFINITY,
-						0.,
-						&format!("c_3b_{}_{}", col, value),
-					);
+				for row in 0..SIZE as u8 {
+					let index = Str8ts::index(row, col) as usize;
+					if let Some(x_i_k) = x.get(&(index, *value)) {
+						// Add the constraint
+						constraints.add(
+							&mut model,
+							vec![x_i_k.clone()],
+							&ONES[..1],
+							-f64::INFINITY,
+							0.,
+							set_name(&mut name, format_args!("c_3b_{}_{}", col, value)),
+						);
+					}
 				}
 			}
 		}
 
 		// 4. Each compartment has exactly one least value.
-		for (compartment_index, _) in compartments.iter().enumerate() {
+		for (compartment_index, compartment) in compartments.iter().enumerate() {
+			if compartment.len() == 1 {
+				continue;
+			}
 			// grab all the y_c_k variables for this compartment with index c
-			let y_c = y
-				.iter()
-				.filter(|(key, _)| key.0 == compartment_index)
-				.map(|(_, value)| value.clone())
+			let y_c = CellValue::into_iter(false)
+				.map(|value| y[&(compartment_index, value)].clone())
 				.collect::<Vec<_>>();
-			// create a vector of coefficients for the y_c_k variables (all 1)
-			let coeffs = vec![1.; y_c.len()];
 			// Add the constraint
-			model.add_cons(y_c, &coeffs, 1., 1., &format!("c_4_{}", compartment_index));
+			constraints.add(
+				&mut model,
+				y_c,
+				&ONES,
+				1.,
+				1.,
+				set_name(&mut name, format_args!("c_4_{}", compartment_index)),
+			);
 		}
 
 		// 5. Each compartment has adjacent values.
 		for (compartment_index, compartment) in compartments.iter().enumerate() {
+			if compartment.len() == 1 {
+				continue;
+			}
+			// coefficients for the x_i_k variables (all 1) and the y_c_k variable (-1)
+			let mut coeffs = ONES[..compartment.len()].to_vec();
+			coeffs.push(-1.);
+
 			for value in CellValue::into_iter(false) {
 				let number_value: usize = value.into();
 				if compartment.len() > 9 - number_value + 1 {
 					break;
 				}
 				// get the y_c_k variable for this compartment and value
-				let y_c_k = y.get(&(compartment_index, value)).unwrap();
-				// create a vector of coefficients for the x_i_k variables (all 1) and the y_c_k variable (-1)
-				let mut coeffs = vec![1.; compartment.len() + 1];
-				coeffs[compartment.len()] = -1.;
+				let y_c_k = &y[&(compartment_index, value)];
 
 				let mut count = compartment.len();
 				for next_value in CellValue::into_iter(false) {
@@ -285,42 +891,190 @@ impl Str8ts {
 						break;
 					}
 					// grab all the x_i_k variables for this compartment and value
-					let mut vars = Vec::new();
+					let mut vars = Vec::with_capacity(compartment.len() + 1);
 					for index in compartment {
-						vars.push(x.get(&((*index as usize), next_value)).unwrap().clone());
+						vars.push(x[&(*index as usize, next_value)].clone());
 					}
-					// get the y_c_k variable for this compartment and value
 					vars.push(y_c_k.clone());
-					model.add_cons(
+					constraints.add(
+						&mut model,
 						vars,
 						&coeffs,
 						0.,
 						f64::INFINITY,
-						&format!("c_5_{}_{}_{}", compartment_index, value, next_value),
+						set_name(
+							&mut name,
+							format_args!("c_5_{}_{}_{}", compartment_index, value, next_value),
+						),
 					);
 					count -= 1;
 				}
 			}
 		}
 
+		// 6. Each value is used at most once in each group of cells of the variant rules.
+		for (group_index, group) in self.rules.distinct_groups().iter().enumerate() {
+			let indices = group
+				.cells
+				.iter()
+				.map(|(row, col)| Str8ts::index(*row, *col) as usize)
+				.collect::<Vec<_>>();
+			for value in CellValue::into_iter(false) {
+				// a black cell in the group already holding the value rules it out for all white cells
+				let is_clue = indices.iter().any(|index| {
+					let cell = self.get_cell_by_index(*index as u8);
+					cell.color == CellColor::Black && cell.value == value
+				});
+				// grab all the x_i_k variables for this group and value
+				let x_i = indices
+					.iter()
+					.filter_map(|index| x.get(&(*index, value)))
+					.cloned()
+					.collect::<Vec<_>>();
+				if x_i.is_empty() {
+					// only black cells, which are not part of the model
+					continue;
+				}
+				let coeffs = &ONES[..x_i.len()];
+				constraints.add(
+					&mut model,
+					x_i,
+					coeffs,
+					-f64::INFINITY,
+					if is_clue { 0. } else { 1. },
+					set_name(&mut name, format_args!("c_6_{}_{}", group_index, value)),
+				);
+			}
+		}
+
+		// 7. Each compartment with a sum clue adds up to it.
+		let compartments = find_compartments_rows(self)
+			.into_iter()
+			.map(|compartment| (compartment, Orientation::Row))
+			.chain(
+				find_compartments_cols(self)
+					.into_iter()
+					.map(|compartment| (compartment, Orientation::Column)),
+			);
+		for (compartment, orientation) in compartments {
+			let first = Str8ts::coords(compartment[0]);
+			let Some(sum) = self.compartment_sum(first, orientation) else {
+				continue;
+			};
+			// grab all the x_i_k variables for this compartment, weighted by their value k
+			let mut vars = Vec::with_capacity(compartment.len() * 9);
+			let mut coeffs = Vec::with_capacity(compartment.len() * 9);
+			for index in compartment.iter() {
+				for value in CellValue::into_iter(false) {
+					vars.push(x[&(*index as usize, value)].clone());
+					coeffs.push(u8::from(value) as f64);
+				}
+			}
+			constraints.add(
+				&mut model,
+				vars,
+				&coeffs,
+				sum as f64,
+				sum as f64,
+				set_name(
+					&mut name,
+					format_args!("c_7_{}_{:?}", compartment[0], orientation),
+				),
+			);
+		}
+
+		// 8. Optionally, the two white cells next to a black cell in a line hold different values.
+		if options.forbid_across_black {
+			for row in 0..SIZE as u8 {
+				for col in 0..SIZE as u8 {
+					if self.get_cell(row, col).color != CellColor::Black {
+						continue;
+					}
+					let mut pairs = Vec::new();
+					if col > 0 && col < SIZE as u8 - 1 {
+						pairs.push(((row, col - 1), (row, col + 1), "row"));
+					}
+					if row > 0 && row < SIZE as u8 - 1 {
+						pairs.push(((row - 1, col), (row + 1, col), "col"));
+					}
+					for ((row_a, col_a), (row_b, col_b), line) in pairs {
+						let index_a = Str8ts::index(row_a, col_a) as usize;
+						let index_b = Str8ts::index(row_b, col_b) as usize;
+						for value in CellValue::into_iter(false) {
+							// black neighbors have no x_i_k variables
+							let (Some(x_a), Some(x_b)) =
+								(x.get(&(index_a, value)), x.get(&(index_b, value)))
+							else {
+								continue;
+							};
+							constraints.add(
+								&mut model,
+								vec![x_a.clone(), x_b.clone()],
+								&ONES[..2],
+								-f64::INFINITY,
+								1.,
+								set_name(
+									&mut name,
+									format_args!("c_8_{}_{}_{}_{}", row, col, line, value),
+								),
+							);
+						}
+					}
+				}
+			}
+		}
+
+		if let (Some(pending), Some(rng)) = (constraints.pending.as_mut(), rng.as_mut()) {
+			rng.shuffle(pending);
+			for constraint in pending.drain(..) {
+				model.add_cons(
+					constraint.vars,
+					&constraint.coeffs,
+					constraint.lhs,
+					constraint.rhs,
+					&constraint.name,
+				);
+			}
+		}
+
+		(model, x)
+	}
+
+	/// Solve the given model of the str8ts game, and read back the solution.
+	fn solve_model(&self, model: Model<ProblemCreated>, x: &CellVariables) -> SolveReport {
 		// Solve the model.
 		let solved_model = model.solve();
 
-		if solved_model.status() != Status::Optimal {
-			return None;
+		let mut report = SolveReport {
+			solution: None,
+			status: solved_model.status(),
+			nodes: solved_model.n_nodes(),
+			lp_iterations: solved_model.n_lp_iterations(),
+			solving_time: solved_model.solving_time(),
+		};
+		if report.status != Status::Optimal {
+			return report;
 		}
 
 		// Get the solution.
 		let solution = solved_model.best_sol().unwrap();
 
 		// Set the values of the str8ts game.
-		let mut solved_str8ts = Str8ts::new();
+		let mut solved_str8ts = Str8ts {
+			rules: self.rules,
+			..Str8ts::new()
+		};
 		for (index, cell) in self.into_iter().enumerate() {
 			if cell.color == CellColor::White {
 				for value in CellValue::into_iter(false) {
 					if solution.val(x.get(&(index, value)).unwrap().clone()) >= 0.5 {
-						solved_str8ts
-							.set_cell_by_index(index as u8, Cell::new(CellColor::White, value));
+						solved_str8ts.set_cell_by_index(
+							index as u8,
+							Cell {
+								given: cell.given,
+								..Cell::new(CellColor::White, value)
+							},
+						);
 					}
 				}
 			} else {
@@ -339,88 +1093,834 @@ impl Str8ts {
 			}
 		}
 
-		Some(solved_str8ts)
+		report.solution = Some(solved_str8ts);
+		report
+	}
+}
+
+/// A constraint whose addition to the model is deferred, see `SolveOptions::shuffle_seed`.
+struct PendingConstraint {
+	vars: Vec<Rc<Variable>>,
+	coeffs: Vec<f64>,
+	lhs: f64,
+	rhs: f64,
+	name: String,
+}
+
+/// Adds constraints to the model right away, or collects them to be added later if `pending` is set.
+struct PendingConstraints {
+	pending: Option<Vec<PendingConstraint>>,
+}
+
+impl PendingConstraints {
+	fn add(
+		&mut self,
+		model: &mut Model<ProblemCreated>,
+		vars: Vec<Rc<Variable>>,
+		coeffs: &[f64],
+		lhs: f64,
+		rhs: f64,
+		name: &str,
+	) {
+		match self.pending.as_mut() {
+			Some(pending) => pending.push(PendingConstraint {
+				vars,
+				coeffs: coeffs.to_vec(),
+				lhs,
+				rhs,
+				name: name.to_string(),
+			}),
+			None => {
+				model.add_cons(vars, coeffs, lhs, rhs, name);
+			}
+		}
+	}
+}
+
+/// A small deterministic pseudo-random number generator (SplitMix64), to shuffle models and create random boards
+/// reproducibly.
+#[derive(Debug, Clone)]
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+	pub(crate) fn next_u64(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+
+	/// Shuffle the slice in place (Fisher-Yates).
+	fn shuffle<T>(&mut self, slice: &mut [T]) {
+		for i in (1..slice.len()).rev() {
+			let j = (self.next_u64() % (i as u64 + 1)) as usize;
+			slice.swap(i, j);
+		}
 	}
 }
 
+/// Coefficients of constraints summing up variables, sliced to the number of variables (at most 9).
+const ONES: [f64; 9] = [1.; 9];
+
+/// Write the given name into the buffer, replacing its previous content, and return it.
+fn set_name<'a>(buffer: &'a mut String, name: Arguments) -> &'a str {
+	buffer.clear();
+	buffer.write_fmt(name).unwrap();
+	buffer
+}
+
 /// Find all compartments in the str8ts game.
 ///
 /// A compartment is a set of adjecent white cells either within the same row or within the same column.
 /// Therefore, compartments are seperated by black cells and the border of the str8ts game.
-fn find_compartments(str8ts: &Str8ts) -> LinkedList<LinkedList<u8>> {
-	let mut compartments = LinkedList::new();
-
+/// A white cell with black cells (or the border) on both sides forms a compartment of length 1.
+pub(crate) fn find_compartments(str8ts: &Str8ts) -> Vec<Vec<u8>> {
 	// Search for compartments in each row.
-	let row_compartments = find_compartments_rows(str8ts);
-	for row_compartment in row_compartments {
-		compartments.push_back(row_compartment);
-	}
+	let mut compartments = find_compartments_rows(str8ts);
 
 	// Search for compartments in each column.
-	let col_compartments = find_compartments_cols(str8ts);
-	for col_compartment in col_compartments {
-		compartments.push_back(col_compartment);
-	}
+	compartments.extend(find_compartments_cols(str8ts));
 
 	compartments
 }
 
 /// Find all row-compartments in the str8ts game.
-fn find_compartments_rows(str8ts: &Str8ts) -> LinkedList<LinkedList<u8>> {
-	let mut compartments = LinkedList::new();
+pub(crate) fn find_compartments_rows(str8ts: &Str8ts) -> Vec<Vec<u8>> {
+	let mut compartments = Vec::new();
 	// Search for compartments in each row.
-	for row in 0..9 {
+	for row in 0..SIZE as u8 {
 		// A compartment is a set of adjecent white cells within the same row.
-		let mut compartment = LinkedList::new();
-		for col in 0..9 {
+		let mut compartment = Vec::new();
+		for col in 0..SIZE as u8 {
 			let cell = str8ts.get_cell(row, col);
 			match cell.color {
 				CellColor::Black => {
 					if !compartment.is_empty() {
 						// If the first cell in that row is black, we have no compartment to add.
-						compartments.push_back(compartment.clone());
-						compartment.clear();
+						compartments.push(std::mem::take(&mut compartment));
 					}
 				}
 				CellColor::White => {
-					compartment.push_back(trans_row_col_to_index!(row, col));
+					compartment.push(Str8ts::index(row, col));
 				}
 			}
 		}
 		if !compartment.is_empty() {
 			// If the last cell in that row is white, we have a compartment to add.
-			compartments.push_back(compartment);
+			compartments.push(compartment);
 		}
 	}
 	compartments
 }
 
 /// Find all column-compartments in the str8ts game.
-fn find_compartments_cols(str8ts: &Str8ts) -> LinkedList<LinkedList<u8>> {
-	let mut compartments = LinkedList::new();
+pub(crate) fn find_compartments_cols(str8ts: &Str8ts) -> Vec<Vec<u8>> {
+	let mut compartments = Vec::new();
 	// Search for compartments in each column.
-	for col in 0..9 {
+	for col in 0..SIZE as u8 {
 		// A compartment is a set of adjecent white cells within the same column.
-		let mut compartment = LinkedList::new();
-		for row in 0..9 {
+		let mut compartment = Vec::new();
+		for row in 0..SIZE as u8 {
 			let cell = str8ts.get_cell(row, col);
 			match cell.color {
 				CellColor::Black => {
 					if !compartment.is_empty() {
 						// If the first cell in that row is black, we have no compartment to add.
-						compartments.push_back(compartment.clone());
-						compartment.clear();
+						compartments.push(std::mem::take(&mut compartment));
 					}
 				}
 				CellColor::White => {
-					compartment.push_back(trans_row_col_to_index!(row, col));
+					compartment.push(Str8ts::index(row, col));
 				}
 			}
 		}
 		if !compartment.is_empty() {
 			// If the last cell in that row is white, we have a compartment to add.
-			compartments.push_back(compartment);
+			compartments.push(compartment);
 		}
 	}
 	compartments
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts::ValueConstraint;
+
+	#[test]
+	fn row_compartments_skip_leading_and_trailing_black_cells() {
+		let str8ts = str8ts![
+			"##...#..#",
+			"#########",
+			".........",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert_eq!(
+			find_compartments_rows(&str8ts),
+			vec![vec![2, 3, 4], vec![6, 7], (18..27).collect::<Vec<u8>>(),]
+		);
+	}
+
+	#[test]
+	fn col_compartments_skip_leading_and_trailing_black_cells() {
+		let str8ts = str8ts![
+			"#.#######",
+			"#.#######",
+			"..#######",
+			"#.#######",
+			"#########",
+			"..#######",
+			"#.#######",
+			"#.#######",
+			"#.#######",
+		];
+		assert_eq!(
+			find_compartments_cols(&str8ts),
+			vec![
+				vec![18],
+				vec![45],
+				vec![1, 10, 19, 28],
+				vec![46, 55, 64, 73]
+			]
+		);
+	}
+
+	#[test]
+	fn all_black_board_has_no_compartments() {
+		let str8ts = str8ts![
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert!(find_compartments(&str8ts).is_empty());
+	}
+
+	#[test]
+	fn all_white_board_has_full_width_compartments() {
+		let compartments = find_compartments(&Str8ts::new());
+		assert_eq!(compartments.len(), 2 * SIZE);
+		assert!(compartments
+			.iter()
+			.all(|compartment| compartment.len() == SIZE));
+		assert_eq!(compartments[0], (0..SIZE as u8).collect::<Vec<u8>>());
+		assert_eq!(
+			compartments[SIZE],
+			(0..SIZE as u8)
+				.map(|row| Str8ts::index(row, 0))
+				.collect::<Vec<u8>>()
+		);
+	}
+
+	#[test]
+	fn isolated_white_cell_is_a_solvable_single_cell_compartment() {
+		let str8ts = str8ts![
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"####.####",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let index = Str8ts::index(4, 4);
+		assert_eq!(find_compartments(&str8ts), vec![vec![index], vec![index]]);
+		let solution = str8ts
+			.solve()
+			.expect("a single white cell can hold any value");
+		assert_ne!(solution.get_cell(4, 4).value, CellValue::Empty);
+	}
+
+	/// An isolated cell bounded to at most 5, while its row leaves 5 to 9.
+	fn bounded_puzzle() -> Str8ts {
+		let mut str8ts = str8ts![
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"abcd.####",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		str8ts.set_cell_constraint(4, 4, Some(ValueConstraint::AtMost(CellValue::Five)));
+		str8ts
+	}
+
+	/// R1C1 is 1 or 2 by its row, R2C2 is 1 by its row and column, and both lie on the main diagonal.
+	fn x_puzzle() -> Str8ts {
+		let mut str8ts = str8ts![
+			".#cdefghi",
+			"#.icdefgh",
+			"#b#######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		str8ts.rules.diagonals = true;
+		str8ts
+	}
+
+	/// R1C1 is 1 or 2 by its row, R2C3 is 1 by its row and column, and they are a knight's move apart.
+	fn anti_knight_puzzle() -> Str8ts {
+		let mut str8ts = str8ts![
+			".#cdefghi",
+			"#b.efghid",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		str8ts.rules.anti_knight = true;
+		str8ts
+	}
+
+	/// Only 1 and 2 add up to 3, and R1C1 cannot be 1 due to the clue below it.
+	fn sum_puzzle() -> Str8ts {
+		let mut str8ts = str8ts![
+			"..#######",
+			"a########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		str8ts.set_compartment_sum((0, 0), Orientation::Row, Some(3));
+		str8ts
+	}
+
+	fn unique_solution(str8ts: &Str8ts) -> Str8ts {
+		match str8ts.uniqueness() {
+			Uniqueness::Unique(solution) => solution,
+			uniqueness => panic!("expected a unique solution, got {:?}", uniqueness),
+		}
+	}
+
+	#[test]
+	fn at_most_constraint_bounds_the_value() {
+		let mut unbounded = bounded_puzzle();
+		unbounded.set_cell_constraint(4, 4, None);
+		assert!(matches!(unbounded.uniqueness(), Uniqueness::Multiple(..)));
+		let solution = unique_solution(&bounded_puzzle());
+		assert_eq!(solution.get_cell(4, 4).value, CellValue::Five);
+	}
+
+	#[test]
+	fn diagonals_make_the_x_puzzle_unique() {
+		let mut plain = x_puzzle();
+		plain.rules.diagonals = false;
+		assert!(matches!(plain.uniqueness(), Uniqueness::Multiple(..)));
+		let solution = unique_solution(&x_puzzle());
+		assert_eq!(solution.get_cell(0, 0).value, CellValue::Two);
+		assert_eq!(solution.get_cell(1, 1).value, CellValue::One);
+	}
+
+	#[test]
+	fn anti_knight_makes_the_puzzle_unique() {
+		let mut plain = anti_knight_puzzle();
+		plain.rules.anti_knight = false;
+		assert!(matches!(plain.uniqueness(), Uniqueness::Multiple(..)));
+		let solution = unique_solution(&anti_knight_puzzle());
+		assert_eq!(solution.get_cell(0, 0).value, CellValue::Two);
+		assert_eq!(solution.get_cell(1, 2).value, CellValue::One);
+	}
+
+	#[test]
+	fn sum_clue_makes_the_puzzle_unique() {
+		let mut plain = sum_puzzle();
+		plain.set_compartment_sum((0, 0), Orientation::Row, None);
+		assert!(matches!(plain.uniqueness(), Uniqueness::Multiple(..)));
+		let solution = unique_solution(&sum_puzzle());
+		assert_eq!(solution.get_cell(0, 0).value, CellValue::Two);
+		assert_eq!(solution.get_cell(0, 1).value, CellValue::One);
+	}
+
+	#[test]
+	fn transposed_puzzles_have_transposed_solutions() {
+		// The row and column constraints are built separately, so they must agree on every puzzle and its transpose.
+		for puzzle in [
+			bounded_puzzle(),
+			x_puzzle(),
+			anti_knight_puzzle(),
+			sum_puzzle(),
+		] {
+			assert_eq!(
+				unique_solution(&puzzle.transpose()),
+				unique_solution(&puzzle).transpose(),
+				"{}",
+				puzzle
+			);
+		}
+	}
+
+	#[test]
+	fn only_the_middle_of_a_given_straight_is_redundant() {
+		// Without 2 or 4, the straight could also be 3-4-5 or 1-2-3. Without 3, it can only be 2-3-4.
+		let str8ts = str8ts![
+			"234######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert_eq!(str8ts.redundant_clues(), vec![Str8ts::index(0, 1)]);
+	}
+
+	#[test]
+	fn forbid_across_black_separates_the_neighbors_of_black_cells() {
+		let str8ts = str8ts![
+			".#.######",
+			"#########",
+			".########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let report = str8ts.solve_with_options(SolveOptions {
+			forbid_across_black: true,
+			..SolveOptions::default()
+		});
+		let solution = report.solution.unwrap();
+		assert_ne!(solution.get_cell(0, 0).value, solution.get_cell(0, 2).value);
+		assert_ne!(solution.get_cell(0, 0).value, solution.get_cell(2, 0).value);
+	}
+
+	#[test]
+	fn same_shuffle_seed_gives_the_same_solution() {
+		let str8ts = str8ts![
+			"...######",
+			"...######",
+			"...######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let shuffled = |seed| {
+			str8ts
+				.solve_with_options(SolveOptions {
+					shuffle_seed: Some(seed),
+					..SolveOptions::default()
+				})
+				.solution
+		};
+		let solution = shuffled(42);
+		assert!(solution.is_some());
+		assert_eq!(shuffled(42), solution);
+	}
+
+	#[test]
+	fn duplicate_givens_make_the_puzzle_unsolvable() {
+		assert!(x_puzzle().is_solvable());
+		let str8ts = str8ts![
+			"11#######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert!(!str8ts.is_solvable());
+	}
+
+	#[test]
+	fn only_black_cells_shrink_the_model() {
+		let blank = Str8ts::new().model_size();
+		assert_eq!(Str8ts::blank_completion().model_size(), blank);
+		let (vars, conss) = x_puzzle().model_size();
+		assert!(vars < blank.0);
+		assert!(conss < blank.1);
+	}
+
+	#[test]
+	fn ambiguous_cells_are_where_two_solutions_differ() {
+		// The straight is either 2-3-4 or 3-4-5.
+		let str8ts = str8ts![
+			".34######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert_eq!(str8ts.ambiguous_cells(), vec![Str8ts::index(0, 0)]);
+		assert!(x_puzzle().ambiguous_cells().is_empty());
+	}
+
+	#[test]
+	fn minimize_clues_keeps_pinned_givens() {
+		let str8ts = str8ts![
+			"234######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let minimized = str8ts.minimize_clues(&[]);
+		assert_eq!(minimized.get_cell(0, 1).value, CellValue::Empty);
+		assert!(!minimized.get_cell(0, 1).given);
+		assert_eq!(minimized.redundant_clues(), vec![]);
+		assert_eq!(str8ts.minimize_clues(&[Str8ts::index(0, 1)]), str8ts);
+	}
+
+	#[test]
+	fn excluded_values_are_avoided() {
+		// The straight is either 2-3-4 or 3-4-5.
+		let str8ts = str8ts![
+			".34######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let solution = str8ts.solve_excluding(&[(0, CellValue::Two)]).unwrap();
+		assert_eq!(solution.get_cell(0, 0).value, CellValue::Five);
+		assert_eq!(
+			str8ts.solve_excluding(&[(0, CellValue::Two), (0, CellValue::Five)]),
+			None
+		);
+		assert_eq!(str8ts.solve_excluding(&[(1, CellValue::Three)]), None);
+	}
+
+	#[test]
+	fn candidates_restrict_the_solution() {
+		let str8ts = str8ts![
+			".34######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let mut candidates: [[BTreeSet<CellValue>; SIZE]; SIZE] =
+			std::array::from_fn(|_| std::array::from_fn(|_| CellValue::into_iter(false).collect()));
+		candidates[0][0] = BTreeSet::from([CellValue::Five]);
+		let solution = str8ts.solve_with_candidates(&candidates).unwrap();
+		assert_eq!(solution.get_cell(0, 0).value, CellValue::Five);
+		// Trusting candidates that leave out every solution's value makes the puzzle unsolvable.
+		candidates[0][0] = BTreeSet::from([CellValue::One]);
+		assert_eq!(str8ts.solve_with_candidates(&candidates), None);
+	}
+
+	#[test]
+	fn unforced_compartments_contain_ambiguous_cells() {
+		let str8ts = str8ts![
+			".34######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let compartments = find_compartments(&str8ts);
+		let unforced = str8ts.unforced_compartments();
+		assert_eq!(unforced, vec![0, 1]);
+		assert_eq!(compartments[0], vec![0, 1, 2]);
+		assert_eq!(compartments[1], vec![0]);
+		assert!(x_puzzle().unforced_compartments().is_empty());
+	}
+
+	#[test]
+	fn unforced_compartments_are_found_beyond_two_solutions() {
+		// The two rows are ambiguous on their own, so a second solution may differ from the first in one of them only.
+		let str8ts = str8ts![
+			".34######",
+			"#########",
+			"###.67###",
+			"#########",
+			"1.3######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let compartments = find_compartments(&str8ts);
+		let unforced: Vec<&Vec<u8>> = str8ts
+			.unforced_compartments()
+			.into_iter()
+			.map(|index| &compartments[index])
+			.collect();
+		assert_eq!(
+			unforced,
+			vec![&vec![0, 1, 2], &vec![21, 22, 23], &vec![0], &vec![21]]
+		);
+	}
+
+	#[test]
+	fn divergent_solutions_differ_in_the_ambiguous_cells() {
+		let str8ts = str8ts![
+			".34######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let (first, second, distance) = str8ts.max_divergent_solutions().unwrap().unwrap();
+		assert_eq!(distance, 1);
+		assert_ne!(first.get_cell(0, 0).value, second.get_cell(0, 0).value);
+		assert!(matches!(x_puzzle().max_divergent_solutions(), Ok(None)));
+		let mut unsolvable = str8ts;
+		unsolvable.set_cell_value(0, 0, CellValue::Three);
+		assert!(matches!(
+			unsolvable.max_divergent_solutions(),
+			Err(SolveError::Infeasible)
+		));
+	}
+
+	#[test]
+	fn fill_from_fills_the_selected_cells() {
+		let solution = Str8ts::blank_completion();
+		let mut rng = SplitMix64(7);
+		let mut board = Str8ts::new();
+		let filled = board
+			.fill_from(&solution, FillSelection::Row(1), &mut rng)
+			.unwrap();
+		assert_eq!(filled.len(), SIZE);
+		assert!(filled
+			.iter()
+			.all(|((row, col), value)| *row == 1 && *value == solution.get_cell(1, *col).value));
+		let filled = board
+			.fill_from(&solution, FillSelection::Column(4), &mut rng)
+			.unwrap();
+		assert_eq!(filled.len(), SIZE - 1);
+		assert!(filled
+			.iter()
+			.all(|((row, col), value)| *col == 4 && *value == solution.get_cell(*row, 4).value));
+		let column = CompartmentId {
+			orientation: Orientation::Column,
+			first: (0, 0),
+		};
+		// The cell of the row filled before is skipped.
+		let filled = board
+			.fill_from(&solution, FillSelection::Compartment(column), &mut rng)
+			.unwrap();
+		assert_eq!(filled.len(), SIZE - 1);
+		let filled = board
+			.fill_from(
+				&solution,
+				FillSelection::Cells(vec![(8, 8), (7, 7)]),
+				&mut rng,
+			)
+			.unwrap();
+		assert_eq!(
+			filled,
+			vec![
+				((8, 8), solution.get_cell(8, 8).value),
+				((7, 7), solution.get_cell(7, 7).value)
+			]
+		);
+		assert_eq!(board.get_cell(8, 8).value, solution.get_cell(8, 8).value);
+		let filled = board
+			.fill_from(&solution, FillSelection::Random(3), &mut rng)
+			.unwrap();
+		assert_eq!(filled.len(), 3);
+		assert!(filled.windows(2).all(|pair| pair[0].0 < pair[1].0));
+	}
+
+	#[test]
+	fn fill_from_rejects_black_and_contradicting_cells() {
+		let solution = Str8ts::blank_completion();
+		let mut rng = SplitMix64(7);
+		let mut board = Str8ts::new();
+		board.set_cell_color(0, 0, CellColor::Black);
+		board.set_cell_value(0, 1, CellValue::One);
+		assert_eq!(
+			board.fill_from(&solution, FillSelection::Cells(vec![(0, 0)]), &mut rng),
+			Err(SolveError::BlackCell((0, 0)))
+		);
+		assert_eq!(
+			board.fill_from(
+				&solution,
+				FillSelection::Cells(vec![(0, 2), (0, 1)]),
+				&mut rng
+			),
+			Err(SolveError::Contradiction((0, 1)))
+		);
+		// Nothing is filled on error.
+		assert_eq!(board.get_cell(0, 2).value, CellValue::Empty);
+	}
+
+	#[test]
+	fn black_cell_suggestions_make_the_solution_unique() {
+		// The last two cells of the first row hold 3 and 4 in either order, unless the last one is black.
+		let str8ts = str8ts![
+			"12..#####",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let suggestions = str8ts.suggest_black_cells(10, false).unwrap();
+		assert_eq!(suggestions.len(), 1);
+		assert_eq!(suggestions[0].0, (0, 3));
+		assert!(matches!(suggestions[0].1, Uniqueness::Unique(_)));
+	}
+
+	#[test]
+	fn symmetric_black_cell_suggestions_turn_pairs_black() {
+		let str8ts = str8ts![
+			"12..#####",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#####..21",
+		];
+		// Either half alone leaves the other one ambiguous.
+		assert!(str8ts.suggest_black_cells(10, false).unwrap().is_empty());
+		let suggestions = str8ts.suggest_black_cells(10, true).unwrap();
+		assert_eq!(suggestions.len(), 1);
+		assert_eq!(suggestions[0].0, (0, 3));
+		assert!(matches!(suggestions[0].1, Uniqueness::Unique(_)));
+	}
+
+	#[test]
+	fn no_black_cells_are_suggested_without_a_solution() {
+		let str8ts = str8ts![
+			"11.######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert_eq!(
+			str8ts
+				.suggest_black_cells(10, false)
+				.map(|suggestions| suggestions.len()),
+			Err(SolveError::Infeasible)
+		);
+	}
+
+	/// Two cells completing a straight each, next to a black clue.
+	fn solved_board() -> SolvedBoard {
+		str8ts![
+			"1.3d#####",
+			"#########",
+			"7.9######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		]
+		.solved()
+		.unwrap()
+	}
+
+	#[test]
+	fn solved_board_answers_values_of_white_and_black_cells() {
+		let solved = solved_board();
+		assert_eq!(solved.value_at(0, 1), CellValue::Two);
+		assert_eq!(solved.value_at(2, 1), CellValue::Eight);
+		assert_eq!(solved.value_at(0, 3), CellValue::Four);
+		assert_eq!(solved.value_at(1, 0), CellValue::Empty);
+		assert!(solved.is_correct(0, 1, CellValue::Two));
+		assert!(!solved.is_correct(0, 1, CellValue::Three));
+		assert!(!solved.is_correct(2, 1, CellValue::Empty));
+	}
+
+	#[test]
+	fn solved_board_diff_lists_empty_and_wrong_white_cells() {
+		let solved = solved_board();
+		let mut board = str8ts![
+			"1.3d#####",
+			"#########",
+			"7.9######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		board.set_cell_value(0, 1, CellValue::Five);
+		// Black cells are never listed, even if their clue differs.
+		board.set_cell_value(0, 3, CellValue::Empty);
+		assert_eq!(
+			solved.diff_from(&board),
+			vec![((0, 1), CellValue::Two), ((2, 1), CellValue::Eight)]
+		);
+		board.set_cell_value(0, 1, CellValue::Two);
+		board.set_cell_value(2, 1, CellValue::Eight);
+		assert!(solved.diff_from(&board).is_empty());
+	}
+
+	#[test]
+	fn blank_board_is_always_solved_the_same_way() {
+		let first = Str8ts::new().solve().unwrap();
+		assert_eq!(Str8ts::new().solve(), Some(first));
+		assert_eq!(first, Str8ts::blank_completion());
+		assert!(first.conflicts().is_empty());
+		assert!(first
+			.white_cells()
+			.all(|(_, cell)| cell.value != CellValue::Empty));
+	}
+}
@@ -0,0 +1,247 @@
+use crate::str8ts::{Cell, CellValue, Orientation, Position, Str8ts, ValueConstraint, CELLS, SIZE};
+use crate::str8ts_solver::{find_compartments_cols, find_compartments_rows};
+
+/// A symmetry of the square grid: one of its rotations or reflections.
+///
+/// Rows, columns, compartments and the variant rules all map onto themselves under each of them, so they turn a str8ts
+/// game into an equivalent one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Symmetry {
+	Identity,
+	/// Rotation by 90 degrees clockwise.
+	Rotate90,
+	Rotate180,
+	/// Rotation by 270 degrees clockwise (90 degrees counterclockwise).
+	Rotate270,
+	/// Reflection swapping the left and right side.
+	MirrorHorizontal,
+	/// Reflection swapping the top and bottom side.
+	MirrorVertical,
+	/// Reflection along the main diagonal.
+	Transpose,
+	/// Reflection along the anti-diagonal.
+	AntiTranspose,
+}
+
+impl Symmetry {
+	pub(crate) const ALL: [Symmetry; 8] = [
+		Symmetry::Identity,
+		Symmetry::Rotate90,
+		Symmetry::Rotate180,
+		Symmetry::Rotate270,
+		Symmetry::MirrorHorizontal,
+		Symmetry::MirrorVertical,
+		Symmetry::Transpose,
+		Symmetry::AntiTranspose,
+	];
+
+	/// Returns the position the given cell is moved to.
+	pub(crate) fn map(&self, position: Position) -> Position {
+		let (row, col) = position;
+		let last = SIZE as u8 - 1;
+		match self {
+			Symmetry::Identity => (row, col),
+			Symmetry::Rotate90 => (col, last - row),
+			Symmetry::Rotate180 => (last - row, last - col),
+			Symmetry::Rotate270 => (last - col, row),
+			Symmetry::MirrorHorizontal => (row, last - col),
+			Symmetry::MirrorVertical => (last - row, col),
+			Symmetry::Transpose => (col, row),
+			Symmetry::AntiTranspose => (last - col, last - row),
+		}
+	}
+
+	/// Whether rows become columns and vice versa.
+	fn swaps_orientation(&self) -> bool {
+		matches!(
+			self,
+			Symmetry::Rotate90
+				| Symmetry::Rotate270
+				| Symmetry::Transpose
+				| Symmetry::AntiTranspose
+		)
+	}
+}
+
+/// A transformation turning a str8ts game into an equivalent one: a symmetry of the grid, optionally followed by
+/// replacing every value `v` with `10 - v` (which maps straights onto straights).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Isomorphism {
+	pub(crate) symmetry: Symmetry,
+	pub(crate) reversed: bool,
+}
+
+impl Str8ts {
+	/// Returns the str8ts game transformed by the given isomorphism.
+	///
+	/// Value constraints and sum clues are transformed along with the cells, so the result has exactly the transformed
+	/// solutions.
+	pub(crate) fn transformed(&self, isomorphism: Isomorphism) -> Str8ts {
+		let Isomorphism { symmetry, reversed } = isomorphism;
+		let reverse = |value: CellValue| match value {
+			CellValue::Empty => CellValue::Empty,
+			value if reversed => CellValue::from(10 - u8::from(value)),
+			value => value,
+		};
+
+		let mut result = *self;
+		result.sums = [[None; 2]; CELLS];
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				let cell = self.get_cell(row, col);
+				let constraint = cell.constraint.map(|constraint| match constraint {
+					ValueConstraint::AtMost(bound) if reversed => {
+						ValueConstraint::AtLeast(reverse(bound))
+					}
+					ValueConstraint::AtLeast(bound) if reversed => {
+						ValueConstraint::AtMost(reverse(bound))
+					}
+					constraint => constraint,
+				});
+				let (new_row, new_col) = symmetry.map((row, col));
+				result.set_cell(
+					new_row,
+					new_col,
+					Cell {
+						value: reverse(cell.value),
+						constraint,
+						..cell
+					},
+				);
+			}
+		}
+
+		// Sum clues belong to the first cell of their compartment, which might now be its last one.
+		let compartments = find_compartments_rows(self)
+			.into_iter()
+			.map(|compartment| (compartment, Orientation::Row))
+			.chain(
+				find_compartments_cols(self)
+					.into_iter()
+					.map(|compartment| (compartment, Orientation::Column)),
+			);
+		for (compartment, orientation) in compartments {
+			let first = Str8ts::coords(compartment[0]);
+			let Some(sum) = self.compartment_sum(first, orientation) else {
+				continue;
+			};
+			let new_first = compartment
+				.iter()
+				.map(|index| symmetry.map(Str8ts::coords(*index)))
+				.min()
+				.unwrap();
+			let new_orientation = match (orientation, symmetry.swaps_orientation()) {
+				(Orientation::Row, true) => Orientation::Column,
+				(Orientation::Column, true) => Orientation::Row,
+				(orientation, false) => orientation,
+			};
+			let new_sum = if reversed {
+				10 * compartment.len() as u8 - sum
+			} else {
+				sum
+			};
+			result.set_compartment_sum(new_first, new_orientation, Some(new_sum));
+		}
+		result
+	}
+
+	/// Returns an isomorphism that transforms this str8ts game into the other one, if there is any.
+	///
+	/// The identity is preferred, followed by the pure symmetries. All 16 isomorphisms are tried, so this is cheap.
+	pub(crate) fn is_isomorphic_to(&self, other: &Str8ts) -> Option<Isomorphism> {
+		[false, true]
+			.into_iter()
+			.flat_map(|reversed| {
+				Symmetry::ALL
+					.into_iter()
+					.map(move |symmetry| Isomorphism { symmetry, reversed })
+			})
+			.find(|isomorphism| self.transformed(*isomorphism) == *other)
+	}
+
+	/// Returns the canonical form of the str8ts game: the one among its rotations and reflections whose text (see
+	/// `file_contents`) is lexicographically smallest.
+	///
+	/// Two str8ts games are rotations or reflections of each other exactly if their canonical forms are equal, so the
+	/// canonical form identifies duplicates in a collection of puzzles.
+	pub(crate) fn canonical(&self) -> Str8ts {
+		Symmetry::ALL
+			.into_iter()
+			.map(|symmetry| {
+				let transformed = self.transformed(Isomorphism {
+					symmetry,
+					reversed: false,
+				});
+				(transformed.file_contents(), transformed)
+			})
+			.min_by(|(first, _), (second, _)| first.cmp(second))
+			.map(|(_, canonical)| canonical)
+			.unwrap()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts::CellColor;
+	use crate::str8ts_tutorial::rules_board;
+
+	fn puzzle() -> Str8ts {
+		let mut puzzle = rules_board();
+		puzzle.set_cell_color(4, 8, CellColor::White);
+		puzzle
+	}
+
+	#[test]
+	fn rotated_copy_is_isomorphic() {
+		let rotation = Isomorphism {
+			symmetry: Symmetry::Rotate90,
+			reversed: false,
+		};
+		let rotated = puzzle().transformed(rotation);
+		assert_eq!(rotated.get_cell(0, 8).value, CellValue::One);
+		assert_eq!(puzzle().is_isomorphic_to(&rotated), Some(rotation));
+	}
+
+	#[test]
+	fn value_reversed_copy_is_isomorphic() {
+		let mut reversed = puzzle();
+		for ((row, col), cell) in puzzle().cells() {
+			if cell.value != CellValue::Empty {
+				reversed.set_cell_value(row, col, CellValue::from(10 - u8::from(cell.value)));
+			}
+		}
+		assert_eq!(
+			puzzle().is_isomorphic_to(&reversed),
+			Some(Isomorphism {
+				symmetry: Symmetry::Identity,
+				reversed: true,
+			})
+		);
+	}
+
+	#[test]
+	fn different_puzzle_is_not_isomorphic() {
+		let mut different = puzzle();
+		different.toggle_cell_color(4, 8);
+		assert_eq!(puzzle().is_isomorphic_to(&different), None);
+		different = puzzle();
+		different.set_cell_value(1, 0, CellValue::Three);
+		assert_eq!(puzzle().is_isomorphic_to(&different), None);
+	}
+
+	#[test]
+	fn rotations_and_reflections_share_their_canonical_form() {
+		let canonical = puzzle().canonical();
+		for symmetry in Symmetry::ALL {
+			let transformed = puzzle().transformed(Isomorphism {
+				symmetry,
+				reversed: false,
+			});
+			assert_eq!(transformed.canonical(), canonical);
+		}
+		let mut different = puzzle();
+		different.toggle_cell_color(4, 8);
+		assert_ne!(different.canonical(), canonical);
+	}
+}
@@ -0,0 +1,82 @@
+use std::io::IsTerminal;
+
+use crate::str8ts::{CellColor, CellValue, Str8ts, SIZE};
+
+/// ANSI escape sequence for black text on a light background.
+const ANSI_WHITE_CELL: &str = "\x1b[30;107m";
+/// ANSI escape sequence for white text on a dark background.
+const ANSI_BLACK_CELL: &str = "\x1b[97;40m";
+/// ANSI escape sequence resetting all colors.
+const ANSI_RESET: &str = "\x1b[0m";
+
+impl Str8ts {
+	/// Draws the str8ts game as plain text, three characters per cell.
+	///
+	/// White cells are drawn as ` 5 ` (or ` . ` if empty), black cells as `#5#` (or `###` if empty).
+	pub(crate) fn to_ascii_art(self) -> String {
+		let mut result = String::new();
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				let cell = self.get_cell(row, col);
+				result.push_str(&match (cell.color, cell.value) {
+					(CellColor::White, CellValue::Empty) => String::from(" . "),
+					(CellColor::White, value) => format!(" {} ", value),
+					(CellColor::Black, CellValue::Empty) => String::from("###"),
+					(CellColor::Black, value) => format!("#{}#", value),
+				});
+			}
+			result.push('\n');
+		}
+		result
+	}
+
+	/// Draws the str8ts game for a color terminal, with black cells on a dark and white cells on a light background.
+	pub(crate) fn to_ansi(self) -> String {
+		let mut result = String::new();
+		for row in 0..SIZE as u8 {
+			for col in 0..SIZE as u8 {
+				let cell = self.get_cell(row, col);
+				result.push_str(match cell.color {
+					CellColor::White => ANSI_WHITE_CELL,
+					CellColor::Black => ANSI_BLACK_CELL,
+				});
+				result.push_str(&format!(" {} ", cell.value));
+			}
+			result.push_str(ANSI_RESET);
+			result.push('\n');
+		}
+		result
+	}
+
+	/// Draws the str8ts game for stdout: with colors if it is a terminal, as plain text otherwise (e.g. when piped to a file).
+	pub(crate) fn to_terminal(self) -> String {
+		if std::io::stdout().is_terminal() {
+			self.to_ansi()
+		} else {
+			self.to_ascii_art()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn ansi_colors_white_and_black_cells() {
+		let str8ts = str8ts![
+			"5c.######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let first_line = str8ts.to_ansi().lines().next().unwrap().to_string();
+		assert!(
+			first_line.starts_with("\x1b[30;107m 5 \x1b[97;40m 3 \x1b[30;107m   \x1b[97;40m   ")
+		);
+		assert!(first_line.ends_with("\x1b[0m"));
+	}
+}
@@ -0,0 +1,200 @@
+use crate::str8ts::{CellValue, Position, Str8ts};
+
+/// Something the player does in the editor, as far as tutorials are concerned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TutorialAction {
+	/// The player read the text and asked for the next step.
+	Continue,
+	/// The player entered the value into the cell.
+	EnterValue(Position, CellValue),
+	/// The player toggled the color of the cell.
+	ToggleColor(Position),
+	/// The player filled some cells from the solution.
+	Fill,
+	/// The player solved the board.
+	Solve,
+}
+
+/// A single step of a tutorial: a text for the player, the cells it is about, and the action that completes it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TutorialStep {
+	pub(crate) text: &'static str,
+	/// The cells to highlight while the step is shown.
+	pub(crate) highlighted: &'static [Position],
+	pub(crate) expected: TutorialAction,
+}
+
+/// The steps of the rules tutorial, played on `rules_board`.
+const RULES_STEPS: [TutorialStep; 8] = [
+	TutorialStep {
+		text: "Welcome to Str8ts! The board has white cells to fill and black cells that only separate them. This small \
+		       example has nine white cells in its top left corner.",
+		highlighted: &[],
+		expected: TutorialAction::Continue,
+	},
+	TutorialStep {
+		text: "A compartment is a run of white cells in a row or column, ended by black cells or the border. Every \
+		       compartment must form a straight: consecutive values, in any order. The highlighted cells form one.",
+		highlighted: &[(0, 0), (0, 1), (0, 2)],
+		expected: TutorialAction::Continue,
+	},
+	TutorialStep {
+		text: "The compartment already holds 1 and 2, and a straight of three cells containing both can only be 1-2-3. \
+		       Enter 3 into R1C3.",
+		highlighted: &[(0, 2)],
+		expected: TutorialAction::EnterValue((0, 2), CellValue::Three),
+	},
+	TutorialStep {
+		text: "No value may appear twice in a row or column, even across black cells. A value in a black cell (a clue) \
+		       counts too, but is never part of a straight: the 4 in R3C4 keeps 4 out of row 3.",
+		highlighted: &[(2, 3)],
+		expected: TutorialAction::Continue,
+	},
+	TutorialStep {
+		text: "In Edit Mode, the small button next to a cell toggles its color. Turn R4C1 white: the compartment of \
+		       column 1 grows to four cells.",
+		highlighted: &[(3, 0)],
+		expected: TutorialAction::ToggleColor((3, 0)),
+	},
+	TutorialStep {
+		text: "Now turn R4C1 black again, to get back to the original puzzle.",
+		highlighted: &[(3, 0)],
+		expected: TutorialAction::ToggleColor((3, 0)),
+	},
+	TutorialStep {
+		text: "Stuck while solving? Switch to Solve Mode, select a cell of row 2, and press Fill Row to fill in its row \
+		       from the solution.",
+		highlighted: &[(1, 0), (1, 1), (1, 2)],
+		expected: TutorialAction::Fill,
+	},
+	TutorialStep {
+		text: "Press Solve to fill in the rest of the board. That's all there is to it!",
+		highlighted: &[],
+		expected: TutorialAction::Solve,
+	},
+];
+
+/// A scripted sequence of steps that teaches the rules or the editor on an example board.
+///
+/// The tutorial only tracks the player's progress. Showing the steps and reporting the player's actions is up to the
+/// editor, so further tutorials only need a board and a list of steps.
+#[derive(Debug, Clone)]
+pub(crate) struct Tutorial {
+	board: Str8ts,
+	steps: &'static [TutorialStep],
+	/// The index of the current step, which equals the number of steps once the tutorial is finished.
+	current: usize,
+}
+
+impl Tutorial {
+	/// Returns the tutorial teaching the rules of Str8ts and the basics of the editor.
+	pub(crate) fn rules() -> Tutorial {
+		Tutorial {
+			board: rules_board(),
+			steps: &RULES_STEPS,
+			current: 0,
+		}
+	}
+
+	/// Returns the board the tutorial is played on.
+	pub(crate) fn board(&self) -> Str8ts {
+		self.board
+	}
+
+	/// Returns the step to show, or None once the tutorial is finished.
+	pub(crate) fn current_step(&self) -> Option<&TutorialStep> {
+		self.steps.get(self.current)
+	}
+
+	/// Returns the number of the current step (starting at 1) and the number of steps.
+	pub(crate) fn progress(&self) -> (usize, usize) {
+		((self.current + 1).min(self.steps.len()), self.steps.len())
+	}
+
+	/// Whether all steps have been completed.
+	pub(crate) fn is_finished(&self) -> bool {
+		self.current >= self.steps.len()
+	}
+
+	/// Tells the tutorial about an action of the player. Returns true if it completed the current step, which moves
+	/// the tutorial on to the next one.
+	pub(crate) fn observe(&mut self, action: TutorialAction) -> bool {
+		match self.current_step() {
+			Some(step) if step.expected == action => {
+				self.current += 1;
+				true
+			}
+			_ => false,
+		}
+	}
+}
+
+/// Returns the board of the rules tutorial: a 3x3 block of white cells in the top left corner, surrounded by black cells,
+/// with a unique solution. Without the clue 4 in R3C4, the bottom right cell could also be 4.
+pub(crate) fn rules_board() -> Str8ts {
+	str8ts![
+		"12.######",
+		".1.######",
+		"...d#####",
+		"#########",
+		"#########",
+		"#########",
+		"#########",
+		"#########",
+		"#########",
+	]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts::CellColor;
+
+	#[test]
+	fn only_the_expected_action_advances_the_tutorial() {
+		let mut tutorial = Tutorial::rules();
+		assert_eq!(tutorial.progress(), (1, RULES_STEPS.len()));
+		assert!(!tutorial.observe(TutorialAction::Solve));
+		assert_eq!(tutorial.current_step(), Some(&RULES_STEPS[0]));
+		assert!(tutorial.observe(TutorialAction::Continue));
+		assert_eq!(tutorial.current_step(), Some(&RULES_STEPS[1]));
+		assert_eq!(tutorial.progress(), (2, RULES_STEPS.len()));
+
+		assert!(tutorial.observe(TutorialAction::Continue));
+		assert!(!tutorial.observe(TutorialAction::EnterValue((0, 2), CellValue::Four)));
+		assert!(!tutorial.observe(TutorialAction::EnterValue((1, 2), CellValue::Three)));
+		assert!(tutorial.observe(TutorialAction::EnterValue((0, 2), CellValue::Three)));
+		assert!(!tutorial.is_finished());
+	}
+
+	#[test]
+	fn rules_tutorial_can_be_completed_on_its_board() {
+		let mut tutorial = Tutorial::rules();
+		let mut board = tutorial.board();
+		let solution = board.solve().unwrap();
+		while let Some(step) = tutorial.current_step().copied() {
+			match step.expected {
+				TutorialAction::EnterValue((row, col), value) => {
+					assert_eq!(board.get_cell(row, col).value, CellValue::Empty);
+					assert_eq!(solution.get_cell(row, col).value, value);
+					board.set_cell_value(row, col, value);
+				}
+				TutorialAction::ToggleColor((row, col)) => {
+					let color = match board.get_cell(row, col).color {
+						CellColor::White => CellColor::Black,
+						CellColor::Black => CellColor::White,
+					};
+					board.set_cell_color(row, col, color);
+				}
+				_ => {}
+			}
+			assert!(tutorial.observe(step.expected));
+		}
+		assert!(tutorial.is_finished());
+		assert_eq!(tutorial.current_step(), None);
+		assert_eq!(tutorial.progress(), (RULES_STEPS.len(), RULES_STEPS.len()));
+		assert!(!tutorial.observe(TutorialAction::Continue));
+		// The color toggles undo each other, leaving the puzzle as it began.
+		assert_eq!(board.solve(), Some(solution));
+	}
+}
@@ -0,0 +1,393 @@
+use std::fmt::Display;
+
+use crate::str8ts::{CellColor, CellValue, LabelScheme, Orientation, Position, Str8ts, SIZE};
+use crate::str8ts_rules::Rule;
+use crate::str8ts_solver::{find_compartments_cols, find_compartments_rows};
+
+/// A violation of the rules of str8ts among the values currently placed on the board.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Conflict {
+	/// The value appears more than once in the row.
+	RowDuplicate {
+		row: u8,
+		value: CellValue,
+		cells: Vec<Position>,
+	},
+	/// The value appears more than once in the column.
+	ColumnDuplicate {
+		col: u8,
+		value: CellValue,
+		cells: Vec<Position>,
+	},
+	/// The value appears more than once in a group of cells of a variant rule.
+	RuleDuplicate {
+		rule: Rule,
+		value: CellValue,
+		cells: Vec<Position>,
+	},
+	/// The values placed in the compartment are too far apart to be part of a straight.
+	CompartmentNotStraight { cells: Vec<Position> },
+	/// The values placed in the compartment cannot add up to its sum clue.
+	CompartmentSum { cells: Vec<Position>, sum: u8 },
+}
+
+impl Conflict {
+	/// Returns the cells involved in the conflict.
+	pub(crate) fn cells(&self) -> &[Position] {
+		match self {
+			Conflict::RowDuplicate { cells, .. }
+			| Conflict::ColumnDuplicate { cells, .. }
+			| Conflict::RuleDuplicate { cells, .. }
+			| Conflict::CompartmentNotStraight { cells }
+			| Conflict::CompartmentSum { cells, .. } => cells,
+		}
+	}
+}
+
+impl Display for Conflict {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let labels = LabelScheme::default();
+		match self {
+			Conflict::RowDuplicate { row, value, cells } => write!(
+				f,
+				"{} appears {} times in row {}",
+				value,
+				cells.len(),
+				labels.row_label(*row)
+			),
+			Conflict::ColumnDuplicate { col, value, cells } => write!(
+				f,
+				"{} appears {} times in column {}",
+				value,
+				cells.len(),
+				labels.col_label(*col)
+			),
+			Conflict::RuleDuplicate { rule, value, cells } => {
+				write!(f, "{} appears {} times {}", value, cells.len(), rule)
+			}
+			Conflict::CompartmentNotStraight { cells } => {
+				let (first_row, first_col) = cells[0];
+				let (last_row, last_col) = cells[cells.len() - 1];
+				write!(
+					f,
+					"Compartment {}-{} cannot form a straight",
+					labels.cell_reference(first_row, first_col),
+					labels.cell_reference(last_row, last_col)
+				)
+			}
+			Conflict::CompartmentSum { cells, sum } => {
+				let (first_row, first_col) = cells[0];
+				let (last_row, last_col) = cells[cells.len() - 1];
+				write!(
+					f,
+					"Compartment {}-{} cannot add up to {}",
+					labels.cell_reference(first_row, first_col),
+					labels.cell_reference(last_row, last_col),
+					sum
+				)
+			}
+		}
+	}
+}
+
+/// The first rule a board breaks, see `Str8ts::first_violation`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Violation {
+	/// Values placed on the board conflict with each other.
+	Conflict(Conflict),
+	/// The white cell has no value yet.
+	EmptyCell(Position),
+}
+
+impl Display for Violation {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Violation::Conflict(conflict) => write!(f, "{}", conflict),
+			Violation::EmptyCell((row, col)) => write!(
+				f,
+				"{} is still empty",
+				LabelScheme::default().cell_reference(*row, *col)
+			),
+		}
+	}
+}
+
+impl Str8ts {
+	/// Find all conflicts among the values currently placed on the board.
+	///
+	/// Empty cells never conflict, so a board without conflicts is not necessarily solvable.
+	/// Conflicts are ordered by kind: row duplicates, then column duplicates, then duplicates of variant rules,
+	/// then compartments.
+	pub(crate) fn conflicts(&self) -> Vec<Conflict> {
+		let mut conflicts = Vec::new();
+
+		// 1. No value appears twice in a row (black cells included).
+		for row in 0..SIZE as u8 {
+			for value in CellValue::into_iter(false) {
+				let cells: Vec<Position> = (0..SIZE as u8)
+					.filter(|col| self.get_cell(row, *col).value == value)
+					.map(|col| (row, col))
+					.collect();
+				if cells.len() > 1 {
+					conflicts.push(Conflict::RowDuplicate { row, value, cells });
+				}
+			}
+		}
+
+		// 2. No value appears twice in a column (black cells included).
+		for col in 0..SIZE as u8 {
+			for value in CellValue::into_iter(false) {
+				let cells: Vec<Position> = (0..SIZE as u8)
+					.filter(|row| self.get_cell(*row, col).value == value)
+					.map(|row| (row, col))
+					.collect();
+				if cells.len() > 1 {
+					conflicts.push(Conflict::ColumnDuplicate { col, value, cells });
+				}
+			}
+		}
+
+		// 3. No value appears twice in a group of a variant rule (black cells included).
+		for group in self.rules.distinct_groups() {
+			for value in CellValue::into_iter(false) {
+				let cells: Vec<Position> = group
+					.cells
+					.iter()
+					.copied()
+					.filter(|(row, col)| self.get_cell(*row, *col).value == value)
+					.collect();
+				if cells.len() > 1 {
+					conflicts.push(Conflict::RuleDuplicate {
+						rule: group.rule,
+						value,
+						cells,
+					});
+				}
+			}
+		}
+
+		// 4. The values of each compartment fit into a straight of the compartment's length, and its sum clue (if any).
+		let compartments = find_compartments_rows(self)
+			.into_iter()
+			.map(|compartment| (compartment, Orientation::Row))
+			.chain(
+				find_compartments_cols(self)
+					.into_iter()
+					.map(|compartment| (compartment, Orientation::Column)),
+			);
+		for (compartment, orientation) in compartments {
+			let cells: Vec<Position> = compartment
+				.iter()
+				.map(|index| Str8ts::coords(*index))
+				.collect();
+			let values: Vec<u8> = cells
+				.iter()
+				.map(|(row, col)| self.get_cell(*row, *col))
+				.filter(|cell| cell.color == CellColor::White && cell.value != CellValue::Empty)
+				.map(|cell| cell.value.into())
+				.collect();
+			if let (Some(min), Some(max)) = (values.iter().min(), values.iter().max()) {
+				if usize::from(max - min) >= cells.len() {
+					conflicts.push(Conflict::CompartmentNotStraight {
+						cells: cells.clone(),
+					});
+				}
+			}
+			conflicts.extend(self.sum_conflict(&cells, orientation));
+		}
+
+		conflicts
+	}
+
+	/// Returns the first rule the board breaks, or None if it is solved.
+	///
+	/// Conflicts among the placed values come first, in the order of `conflicts`, followed by the first empty white cell
+	/// in reading order.
+	pub(crate) fn first_violation(&self) -> Option<Violation> {
+		if let Some(conflict) = self.conflicts().into_iter().next() {
+			return Some(Violation::Conflict(conflict));
+		}
+		self.empty_white_cells()
+			.next()
+			.map(|(position, _)| Violation::EmptyCell(position))
+	}
+
+	/// Checks the values placed in the compartment against its sum clue, if it has one.
+	///
+	/// The values conflict with the clue if they already exceed it, or if the compartment is full and they fall short.
+	pub(crate) fn sum_conflict(
+		&self,
+		cells: &[Position],
+		orientation: Orientation,
+	) -> Option<Conflict> {
+		let sum = self.compartment_sum(cells[0], orientation)?;
+		let values: Vec<u8> = cells
+			.iter()
+			.map(|(row, col)| u8::from(self.get_cell(*row, *col).value))
+			.filter(|value| *value != 0)
+			.collect();
+		let total: u8 = values.iter().sum();
+		if total > sum || (values.len() == cells.len() && total != sum) {
+			Some(Conflict::CompartmentSum {
+				cells: cells.to_vec(),
+				sum,
+			})
+		} else {
+			None
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::str8ts_rules::Diagonal;
+
+	#[test]
+	fn knight_conflict_is_reported() {
+		let mut str8ts = str8ts![
+			"1.#######",
+			"..1######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert!(str8ts.conflicts().is_empty());
+		str8ts.rules.anti_knight = true;
+		assert_eq!(
+			str8ts.conflicts(),
+			vec![Conflict::RuleDuplicate {
+				rule: Rule::AntiKnight,
+				value: CellValue::One,
+				cells: vec![(0, 0), (1, 2)],
+			}]
+		);
+	}
+
+	#[test]
+	fn conflicts_are_reported_before_empty_cells() {
+		let mut str8ts = str8ts![
+			"1..######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert_eq!(str8ts.first_violation(), Some(Violation::EmptyCell((0, 1))));
+		str8ts.set_cell_value(0, 2, CellValue::One);
+		assert_eq!(
+			str8ts.first_violation(),
+			Some(Violation::Conflict(Conflict::RowDuplicate {
+				row: 0,
+				value: CellValue::One,
+				cells: vec![(0, 0), (0, 2)],
+			}))
+		);
+		str8ts.set_cell_value(0, 1, CellValue::Two);
+		str8ts.set_cell_value(0, 2, CellValue::Three);
+		assert_eq!(str8ts.first_violation(), None);
+	}
+
+	#[test]
+	fn column_duplicate_is_the_first_violation() {
+		let str8ts = str8ts![
+			"1########",
+			"1########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert_eq!(
+			str8ts.first_violation(),
+			Some(Violation::Conflict(Conflict::ColumnDuplicate {
+				col: 0,
+				value: CellValue::One,
+				cells: vec![(0, 0), (1, 0)],
+			}))
+		);
+	}
+
+	#[test]
+	fn diagonal_duplicate_is_the_first_violation() {
+		let mut str8ts = str8ts![
+			"1########",
+			"#1#######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert_eq!(str8ts.first_violation(), None);
+		str8ts.rules.diagonals = true;
+		assert_eq!(
+			str8ts.first_violation(),
+			Some(Violation::Conflict(Conflict::RuleDuplicate {
+				rule: Rule::Diagonal(Diagonal::Main),
+				value: CellValue::One,
+				cells: vec![(0, 0), (1, 1)],
+			}))
+		);
+	}
+
+	#[test]
+	fn compartment_without_a_straight_is_the_first_violation() {
+		let str8ts = str8ts![
+			"1.5######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		assert_eq!(
+			str8ts.first_violation(),
+			Some(Violation::Conflict(Conflict::CompartmentNotStraight {
+				cells: vec![(0, 0), (0, 1), (0, 2)],
+			}))
+		);
+	}
+
+	#[test]
+	fn missed_sum_clue_is_the_first_violation() {
+		let mut str8ts = str8ts![
+			"12.######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		str8ts.set_compartment_sum((0, 0), Orientation::Row, Some(4));
+		// The sum can only be missed once the compartment is full.
+		assert_eq!(str8ts.first_violation(), Some(Violation::EmptyCell((0, 2))));
+		str8ts.set_cell_value(0, 2, CellValue::Three);
+		assert_eq!(
+			str8ts.first_violation(),
+			Some(Violation::Conflict(Conflict::CompartmentSum {
+				cells: vec![(0, 0), (0, 1), (0, 2)],
+				sum: 4,
+			}))
+		);
+	}
+}
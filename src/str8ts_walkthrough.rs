@@ -0,0 +1,268 @@
+use crate::str8ts::{LabelScheme, Position, Str8ts};
+use crate::str8ts_explain::explain;
+use crate::str8ts_solver::SolveError;
+
+/// A single instruction of a walkthrough.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct WalkthroughEntry {
+	/// The number of the instruction, starting at 1.
+	pub(crate) number: usize,
+	/// The instruction as a full sentence.
+	pub(crate) instruction: String,
+	/// The cells the instruction is about: the cell it fills first, followed by the rest of the compartment the
+	/// deduction was made in, if any.
+	pub(crate) positions: Vec<Position>,
+	/// Whether no technique of the logical solver finds the value, so it takes trial and error.
+	pub(crate) requires_trial: bool,
+}
+
+/// The instructions solving a str8ts game from its current state, in order.
+///
+/// The steps of the logical solver come first. The cells it leaves empty follow in reading order, with their values
+/// taken from the solution.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Walkthrough {
+	pub(crate) entries: Vec<WalkthroughEntry>,
+}
+
+impl Walkthrough {
+	/// Writes the walkthrough as plain text, one numbered instruction per line.
+	pub(crate) fn to_text(&self) -> String {
+		self.entries
+			.iter()
+			.map(|entry| {
+				format!(
+					"{}. {}{}\n",
+					entry.number,
+					entry.instruction,
+					if entry.requires_trial {
+						" (requires trial)"
+					} else {
+						""
+					}
+				)
+			})
+			.collect()
+	}
+
+	/// Writes the walkthrough as a Markdown ordered list.
+	pub(crate) fn to_markdown(&self) -> String {
+		self.entries
+			.iter()
+			.map(|entry| {
+				format!(
+					"{}. {}{}\n",
+					entry.number,
+					entry.instruction,
+					if entry.requires_trial {
+						" *(requires trial)*"
+					} else {
+						""
+					}
+				)
+			})
+			.collect()
+	}
+
+	/// Writes the walkthrough as an HTML ordered list.
+	pub(crate) fn to_html(&self) -> String {
+		let mut html = String::from("<ol>\n");
+		for entry in self.entries.iter() {
+			html.push_str(&format!(
+				"<li value=\"{}\">{}{}</li>\n",
+				entry.number,
+				escape_html(&entry.instruction),
+				if entry.requires_trial {
+					" <em>(requires trial)</em>"
+				} else {
+					""
+				}
+			));
+		}
+		html.push_str("</ol>\n");
+		html
+	}
+}
+
+/// Escapes the characters with a special meaning in HTML.
+fn escape_html(text: &str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
+
+impl Str8ts {
+	/// Returns the instructions that solve the str8ts game from its current state, see `Walkthrough`.
+	///
+	/// The solution is only needed (and SCIP only run) if the logical solver gets stuck.
+	pub(crate) fn solution_walkthrough(&self) -> Result<Walkthrough, SolveError> {
+		let logical = self.solve_logically();
+		let mut entries: Vec<WalkthroughEntry> = logical
+			.steps
+			.iter()
+			.enumerate()
+			.map(|(index, step)| {
+				let mut positions = vec![(step.row, step.col)];
+				if let Some((first, last)) = step.compartment {
+					positions.extend(
+						compartment_span(first, last)
+							.filter(|position| *position != (step.row, step.col)),
+					);
+				}
+				WalkthroughEntry {
+					number: index + 1,
+					instruction: explain(step),
+					positions,
+					requires_trial: false,
+				}
+			})
+			.collect();
+
+		if !logical.is_complete() {
+			let solution = logical.board.solve().ok_or(SolveError::Infeasible)?;
+			let labels = LabelScheme::default();
+			for ((row, col), _) in logical.board.empty_white_cells() {
+				entries.push(WalkthroughEntry {
+					number: entries.len() + 1,
+					instruction: format!(
+						"{} is {}.",
+						labels.cell_reference(row, col),
+						solution.get_cell(row, col).value
+					),
+					positions: vec![(row, col)],
+					requires_trial: true,
+				});
+			}
+		}
+		Ok(Walkthrough { entries })
+	}
+}
+
+/// Returns the cells from the first to the last cell of a compartment, which share a row or a column.
+fn compartment_span(first: Position, last: Position) -> impl Iterator<Item = Position> {
+	let ((first_row, first_col), (last_row, last_col)) = (first, last);
+	(first_row..=last_row).flat_map(move |row| (first_col..=last_col).map(move |col| (row, col)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Two white cells, each completing a straight between the givens on either side.
+	fn easy_walkthrough() -> Walkthrough {
+		str8ts![
+			"1.3######",
+			"#########",
+			"7.9######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		]
+		.solution_walkthrough()
+		.unwrap()
+	}
+
+	#[test]
+	fn walkthrough_lists_the_deductions_with_their_compartments() {
+		let walkthrough = easy_walkthrough();
+		assert_eq!(walkthrough.entries.len(), 2);
+		assert_eq!(
+			walkthrough.entries[0].positions,
+			vec![(0, 1), (0, 0), (0, 2)]
+		);
+		assert!(walkthrough
+			.entries
+			.iter()
+			.all(|entry| !entry.requires_trial));
+	}
+
+	#[test]
+	fn walkthrough_is_written_as_text() {
+		assert_eq!(
+			easy_walkthrough().to_text(),
+			"1. The compartment in row 1 spanning columns 1-3 must form a straight and has a single gap left, which only 2 \
+			 fills, so R1C2 must be 2.\n\
+			 2. The compartment in row 3 spanning columns 1-3 must form a straight and has a single gap left, which only 8 \
+			 fills, so R3C2 must be 8.\n"
+		);
+	}
+
+	#[test]
+	fn walkthrough_is_written_as_markdown() {
+		assert_eq!(
+			easy_walkthrough().to_markdown(),
+			"1. The compartment in row 1 spanning columns 1-3 must form a straight and has a single gap left, which only 2 \
+			 fills, so R1C2 must be 2.\n\
+			 2. The compartment in row 3 spanning columns 1-3 must form a straight and has a single gap left, which only 8 \
+			 fills, so R3C2 must be 8.\n"
+		);
+	}
+
+	#[test]
+	fn walkthrough_is_written_as_html() {
+		assert_eq!(
+			easy_walkthrough().to_html(),
+			"<ol>\n\
+			 <li value=\"1\">The compartment in row 1 spanning columns 1-3 must form a straight and has a single gap left, \
+			 which only 2 fills, so R1C2 must be 2.</li>\n\
+			 <li value=\"2\">The compartment in row 3 spanning columns 1-3 must form a straight and has a single gap left, \
+			 which only 8 fills, so R3C2 must be 8.</li>\n\
+			 </ol>\n"
+		);
+	}
+
+	#[test]
+	fn html_walkthrough_escapes_special_characters() {
+		let walkthrough = Walkthrough {
+			entries: vec![WalkthroughEntry {
+				number: 1,
+				instruction: String::from("R1C1 is <b>\"1\" & more</b>."),
+				positions: vec![(0, 0)],
+				requires_trial: true,
+			}],
+		};
+		assert_eq!(
+			walkthrough.to_html(),
+			"<ol>\n<li value=\"1\">R1C1 is &lt;b&gt;&quot;1&quot; &amp; more&lt;/b&gt;. <em>(requires trial)</em></li>\n</ol>\n"
+		);
+	}
+
+	#[test]
+	fn cells_the_logical_solver_cannot_fill_require_trial() {
+		let str8ts = str8ts![
+			"..#######",
+			"..#######",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+			"#########",
+		];
+		let walkthrough = str8ts.solution_walkthrough().unwrap();
+		let solution = str8ts.solve().unwrap();
+		let expected: Vec<WalkthroughEntry> = [(0, 0), (0, 1), (1, 0), (1, 1)]
+			.into_iter()
+			.enumerate()
+			.map(|(index, (row, col))| WalkthroughEntry {
+				number: index + 1,
+				instruction: format!(
+					"R{}C{} is {}.",
+					row + 1,
+					col + 1,
+					solution.get_cell(row, col).value
+				),
+				positions: vec![(row, col)],
+				requires_trial: true,
+			})
+			.collect();
+		assert_eq!(walkthrough.entries, expected);
+		assert!(walkthrough.to_text().ends_with(" (requires trial)\n"));
+		assert!(walkthrough.to_markdown().ends_with(" *(requires trial)*\n"));
+	}
+}
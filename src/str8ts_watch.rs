@@ -0,0 +1,182 @@
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write as _;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::str8ts::{LabelScheme, Position, Str8ts, SIZE};
+use crate::str8ts_cache::SolutionCache;
+use crate::str8ts_solver::{find_compartments, Uniqueness};
+use crate::str8ts_symmetry::Symmetry;
+
+/// How often the file is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// How long the file must stay unchanged before it is checked, so a burst of saves is checked only once.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// How many cells of an ambiguous puzzle are tried as black cells, as every try solves the puzzle twice.
+const SUGGESTION_CHECKS: usize = 10;
+
+/// Checks the puzzle file whenever it changes, until the process is interrupted (e.g. by Ctrl+C).
+///
+/// Every check clears the screen and prints the board, its conflicts, whether it has a unique solution (and if not, how far
+/// its solutions diverge, which compartments are not forced and which black cells would make it unique), its rating,
+/// and the size of the search space the logical solver leaves.
+/// There is no file watching backend among the dependencies, so the modification time is polled. A missing file is
+/// waited for, as editors often replace a file by deleting and renaming when saving.
+pub(crate) fn watch(path: &Path) -> ! {
+	let mut last_checked = None;
+	let mut is_missing = false;
+	// Saves that leave the puzzle as it is, e.g. reformatting it, are answered from the cache.
+	let mut cache = SolutionCache::default();
+	loop {
+		match modified(path) {
+			Some(time) if last_checked != Some(time) => {
+				thread::sleep(DEBOUNCE);
+				if modified(path) == Some(time) {
+					last_checked = Some(time);
+					is_missing = false;
+					let report = check(path, &mut cache)
+						.unwrap_or_else(|error| format!("INVALID: {}\n", error));
+					// Clear the screen and move the cursor to the top left corner.
+					print!("\x1b[2J\x1b[H{}\n{}", path.display(), report);
+					let _ = std::io::stdout().flush();
+				}
+				continue;
+			}
+			Some(_) => {}
+			None if !is_missing => {
+				is_missing = true;
+				println!(
+					"{} is missing, waiting for it to reappear...",
+					path.display()
+				);
+			}
+			None => {}
+		}
+		thread::sleep(POLL_INTERVAL);
+	}
+}
+
+/// Returns the modification time of the file, or None if it cannot be read (e.g. because it is missing).
+fn modified(path: &Path) -> Option<SystemTime> {
+	fs::metadata(path)
+		.and_then(|metadata| metadata.modified())
+		.ok()
+}
+
+/// Load and check a single puzzle file, and describe the result.
+///
+/// Defects are written in upper case, so they stand out.
+fn check(path: &Path, cache: &mut SolutionCache) -> Result<String, Box<dyn Error>> {
+	let str8ts: Str8ts = fs::read_to_string(path)?.parse()?;
+	let mut report = str8ts.to_terminal();
+	report.push('\n');
+
+	let conflicts = str8ts.conflicts();
+	if !conflicts.is_empty() {
+		for conflict in conflicts {
+			writeln!(report, "CONFLICT: {}", conflict)?;
+		}
+		return Ok(report);
+	}
+
+	let labels = LabelScheme::default();
+	let uniqueness = cache.uniqueness(&str8ts);
+	match &uniqueness {
+		Uniqueness::Infeasible => {
+			writeln!(report, "INFEASIBLE: the puzzle has no solution")?;
+			return Ok(report);
+		}
+		Uniqueness::Unique(_) => writeln!(report, "Unique solution")?,
+		Uniqueness::Multiple(_, _) => {
+			let cells: Vec<String> = uniqueness
+				.differing_cells()
+				.into_iter()
+				.map(|index| {
+					let (row, col) = Str8ts::coords(index);
+					labels.cell_reference(row, col)
+				})
+				.collect();
+			writeln!(
+				report,
+				"AMBIGUOUS: two solutions differ in {}",
+				cells.join(", ")
+			)?;
+			if let Ok(Some((_, _, distance))) = str8ts.max_divergent_solutions() {
+				writeln!(report, "Solutions differ in up to {} cells", distance)?;
+			}
+			let all_compartments = find_compartments(&str8ts);
+			let compartments: Vec<String> = str8ts
+				.unforced_compartments()
+				.into_iter()
+				.map(|index| {
+					let compartment = &all_compartments[index];
+					let (first, last) = (compartment[0], compartment[compartment.len() - 1]);
+					compartment_reference(&labels, Str8ts::coords(first), Str8ts::coords(last))
+				})
+				.collect();
+			writeln!(
+				report,
+				"Compartments not forced by the rest of the board: {}",
+				compartments.join(", ")
+			)?;
+
+			// Keep a symmetric layout symmetric by suggesting pairs of black cells.
+			let symmetric = str8ts.cells().all(|(position, cell)| {
+				let (row, col) = Symmetry::Rotate180.map(position);
+				str8ts.get_cell(row, col).color == cell.color
+			});
+			let suggestions: Vec<String> = str8ts
+				.suggest_black_cells(SUGGESTION_CHECKS, symmetric)?
+				.into_iter()
+				.map(|((row, col), _)| labels.cell_reference(row, col))
+				.collect();
+			if !suggestions.is_empty() {
+				writeln!(
+					report,
+					"Turning one of these cells black{} makes the solution unique: {}",
+					if symmetric {
+						" (with its symmetric partner)"
+					} else {
+						""
+					},
+					suggestions.join(", ")
+				)?;
+			}
+		}
+	}
+	writeln!(report, "Difficulty: {}", cache.difficulty(&str8ts))?;
+
+	// A puzzle the logical solver completes leaves no search space to describe.
+	let search_space = str8ts.search_space();
+	let largest_compartment = search_space
+		.compartments
+		.iter()
+		.max_by(|(_, a), (_, b)| a.total_cmp(b));
+	if let Some((cells, size)) = largest_compartment.filter(|_| search_space.total > 0.0) {
+		let row = (0..SIZE)
+			.max_by(|a, b| search_space.rows[*a].total_cmp(&search_space.rows[*b]))
+			.unwrap_or_default();
+		writeln!(
+			report,
+			"Search space after logic: 10^{:.1}, largest in row {} (10^{:.1}) and compartment {} (10^{:.1})",
+			search_space.total,
+			labels.row_label(row as u8),
+			search_space.rows[row],
+			compartment_reference(&labels, cells[0], cells[cells.len() - 1]),
+			size
+		)?;
+	}
+	Ok(report)
+}
+
+/// Refer to a compartment by its first and last cell, e.g. `A1-A3`.
+fn compartment_reference(labels: &LabelScheme, first: Position, last: Position) -> String {
+	format!(
+		"{}-{}",
+		labels.cell_reference(first.0, first.1),
+		labels.cell_reference(last.0, last.1)
+	)
+}